@@ -0,0 +1,346 @@
+use crate::client::ClientError;
+use crate::{Error, Result};
+use reqwest::StatusCode;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Configures the circuit breaker which protects accounts from being locked by too many failed logins.
+///
+/// After `failure_threshold` consecutive failed logins of an account, further logins are rejected with [`AuthError::CircuitOpen`] until `cool_down` has passed.
+/// Afterwards a single login attempt is allowed again, other logins of the account are rejected with
+/// [`AuthError::LoginInProgress`] until it finishes.
+/// If the credentials are rejected they are not retried at all until they change, instead [`AuthError::InvalidCredentials`] is returned.
+///
+/// The state is tracked per account and shared by every [`NadeoClient`] in the process, including clones and rebuilt clients.
+///
+/// [`NadeoClient`]: crate::NadeoClient
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct CircuitBreakerConfig {
+    pub failure_threshold: u32,
+    pub cool_down: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            cool_down: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+/// Kinds of login failures.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum LoginFailure {
+    /// The credentials were rejected. Retrying will not help.
+    InvalidCredentials,
+    /// The login failed for another reason, e.g. a network error or rate limiting.
+    Transient,
+}
+
+impl LoginFailure {
+    /// Classifies an error returned while logging in.
+    pub fn classify(err: &Error) -> Self {
+        match err {
+            Error::Auth(AuthError::InvalidCredentials) => Self::InvalidCredentials,
+            Error::NadeoApi(e) if e.status().is_some_and(is_rejected_status) => {
+                Self::InvalidCredentials
+            }
+            Error::Client(ClientError::CoalescedRequestFailed {
+                status: Some(status),
+                ..
+            }) if is_rejected_status(*status) => Self::InvalidCredentials,
+            _ => Self::Transient,
+        }
+    }
+}
+
+fn is_rejected_status(status: StatusCode) -> bool {
+    status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN
+}
+
+/// Errors while logging in.
+#[derive(Error, Debug, Clone)]
+pub enum AuthError {
+    #[error("the credentials were rejected and will not be retried until they change")]
+    InvalidCredentials,
+    #[error("logging in failed repeatedly, next attempt is allowed in {}s", retry_after.as_secs())]
+    CircuitOpen { retry_after: Duration },
+    #[error("logging in failed repeatedly and another login attempt is in progress")]
+    LoginInProgress,
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    /// Hash of the secret which was rejected.
+    rejected_secret: Option<u64>,
+    /// Whether the single attempt allowed after the cool-down is running.
+    half_open_in_flight: bool,
+}
+
+/// Marks the attempt allowed after the cool-down as finished when it is dropped, including when the login is cancelled.
+struct HalfOpenAttempt<'a> {
+    key: &'a str,
+}
+
+impl Drop for HalfOpenAttempt<'_> {
+    fn drop(&mut self) {
+        if let Some(state) = registry().lock().unwrap().get_mut(self.key) {
+            state.half_open_in_flight = false;
+        }
+    }
+}
+
+fn registry() -> &'static Mutex<HashMap<String, BreakerState>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<String, BreakerState>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+fn hash_secret(secret: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    secret.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl CircuitBreakerConfig {
+    /// Runs a login attempt for the account `key` authenticating with `secret` if the circuit breaker allows it.
+    pub(crate) async fn guard<T, F>(&self, key: &str, secret: &str, login: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        let secret = hash_secret(secret);
+        let _attempt = self.acquire(key, secret)?;
+
+        let res = login.await;
+
+        let mut registry = registry().lock().unwrap();
+        let state = registry.entry(key.to_string()).or_default();
+        match res {
+            Ok(val) => {
+                *state = BreakerState::default();
+                Ok(val)
+            }
+            Err(e) => {
+                state.consecutive_failures += 1;
+                if state.consecutive_failures >= self.failure_threshold {
+                    state.opened_at = Some(Instant::now());
+                }
+
+                match LoginFailure::classify(&e) {
+                    LoginFailure::InvalidCredentials => {
+                        state.rejected_secret = Some(secret);
+                        Err(Error::from(AuthError::InvalidCredentials))
+                    }
+                    LoginFailure::Transient => Err(e),
+                }
+            }
+        }
+    }
+
+    /// Checks whether a login is allowed. After the cool-down the attempt is marked as in flight while the lock is held,
+    /// so only one caller gets through.
+    fn acquire<'a>(&self, key: &'a str, secret: u64) -> Result<Option<HalfOpenAttempt<'a>>> {
+        let mut registry = registry().lock().unwrap();
+        let Some(state) = registry.get_mut(key) else {
+            return Ok(None);
+        };
+
+        if state.rejected_secret == Some(secret) {
+            return Err(Error::from(AuthError::InvalidCredentials));
+        }
+        let Some(opened_at) = state.opened_at else {
+            return Ok(None);
+        };
+        let elapsed = opened_at.elapsed();
+        if elapsed < self.cool_down {
+            return Err(Error::from(AuthError::CircuitOpen {
+                retry_after: self.cool_down - elapsed,
+            }));
+        }
+        if state.half_open_in_flight {
+            return Err(Error::from(AuthError::LoginInProgress));
+        }
+        state.half_open_in_flight = true;
+
+        Ok(Some(HalfOpenAttempt { key }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> CircuitBreakerConfig {
+        CircuitBreakerConfig {
+            failure_threshold: 2,
+            cool_down: Duration::from_millis(50),
+        }
+    }
+
+    async fn fail(config: &CircuitBreakerConfig, key: &str) -> Error {
+        config
+            .guard(key, "secret", async {
+                Err::<(), _>(Error::from(ClientError::MissingNadeoAuth))
+            })
+            .await
+            .unwrap_err()
+    }
+
+    async fn succeed(config: &CircuitBreakerConfig, key: &str, secret: &str) -> Result<()> {
+        config.guard(key, secret, async { Ok(()) }).await
+    }
+
+    #[test]
+    fn classifies_coalesced_failures_by_status() {
+        let coalesced = |status| {
+            Error::from(ClientError::CoalescedRequestFailed {
+                status,
+                message: String::new(),
+            })
+        };
+
+        assert_eq!(
+            LoginFailure::classify(&coalesced(Some(StatusCode::UNAUTHORIZED))),
+            LoginFailure::InvalidCredentials
+        );
+        assert_eq!(
+            LoginFailure::classify(&coalesced(Some(StatusCode::TOO_MANY_REQUESTS))),
+            LoginFailure::Transient
+        );
+        assert_eq!(
+            LoginFailure::classify(&coalesced(None)),
+            LoginFailure::Transient
+        );
+    }
+
+    #[tokio::test]
+    async fn opens_after_threshold() {
+        let config = config();
+        let key = "test:threshold";
+
+        assert!(matches!(
+            fail(&config, key).await,
+            Error::Client(ClientError::MissingNadeoAuth)
+        ));
+        assert!(matches!(
+            fail(&config, key).await,
+            Error::Client(ClientError::MissingNadeoAuth)
+        ));
+        assert!(matches!(
+            succeed(&config, key, "secret").await,
+            Err(Error::Auth(AuthError::CircuitOpen { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn closes_after_cool_down() {
+        let config = config();
+        let key = "test:cool_down";
+        fail(&config, key).await;
+        fail(&config, key).await;
+
+        tokio::time::sleep(config.cool_down).await;
+        succeed(&config, key, "secret").await.unwrap();
+
+        // the success resets the failure count
+        fail(&config, key).await;
+        succeed(&config, key, "secret").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_attempt_after_cool_down_reopens() {
+        let config = config();
+        let key = "test:reopen";
+        fail(&config, key).await;
+        fail(&config, key).await;
+
+        tokio::time::sleep(config.cool_down).await;
+        fail(&config, key).await;
+        assert!(matches!(
+            succeed(&config, key, "secret").await,
+            Err(Error::Auth(AuthError::CircuitOpen { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn allows_single_attempt_after_cool_down() {
+        let config = config();
+        let key = "test:half_open";
+        fail(&config, key).await;
+        fail(&config, key).await;
+        tokio::time::sleep(config.cool_down).await;
+
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let first = config.guard(key, "secret", async {
+            rx.await.unwrap();
+            Ok(())
+        });
+        let second = async {
+            let res = succeed(&config, key, "secret").await;
+            tx.send(()).unwrap();
+            res
+        };
+        let (first, second) = tokio::join!(first, second);
+
+        first.unwrap();
+        assert!(matches!(
+            second,
+            Err(Error::Auth(AuthError::LoginInProgress))
+        ));
+        succeed(&config, key, "secret").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn cancelled_attempt_after_cool_down_is_released() {
+        let config = config();
+        let key = "test:cancelled";
+        fail(&config, key).await;
+        fail(&config, key).await;
+        tokio::time::sleep(config.cool_down).await;
+
+        let pending = config.guard(key, "secret", futures::future::pending::<Result<()>>());
+        assert!(tokio::time::timeout(Duration::from_millis(10), pending)
+            .await
+            .is_err());
+
+        succeed(&config, key, "secret").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn rejected_secret_is_not_retried() {
+        let config = config();
+        let key = "test:rejected";
+        let res = config
+            .guard(key, "wrong", async {
+                Err::<(), _>(Error::from(AuthError::InvalidCredentials))
+            })
+            .await;
+        assert!(matches!(
+            res,
+            Err(Error::Auth(AuthError::InvalidCredentials))
+        ));
+
+        let mut attempted = false;
+        let res = config
+            .guard(key, "wrong", async {
+                attempted = true;
+                Ok(())
+            })
+            .await;
+        assert!(matches!(
+            res,
+            Err(Error::Auth(AuthError::InvalidCredentials))
+        ));
+        assert!(!attempted);
+
+        // a changed secret is tried again
+        succeed(&config, key, "correct").await.unwrap();
+    }
+}
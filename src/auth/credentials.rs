@@ -0,0 +1,570 @@
+use crate::{Error, Result};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use serde::Deserialize;
+use std::fmt::{Debug, Formatter};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Credentials of a Ubisoft account. Used for [`AuthType::NadeoServices`] and [`AuthType::NadeoLiveServices`].
+///
+/// [`AuthType::NadeoServices`]: crate::auth::AuthType::NadeoServices
+/// [`AuthType::NadeoLiveServices`]: crate::auth::AuthType::NadeoLiveServices
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+pub struct UbisoftCredentials {
+    pub email: String,
+    pub password: String,
+}
+
+/// Credentials of a dedicated server account. Used for [`AuthType::NadeoServices`] and [`AuthType::NadeoLiveServices`].
+///
+/// [`AuthType::NadeoServices`]: crate::auth::AuthType::NadeoServices
+/// [`AuthType::NadeoLiveServices`]: crate::auth::AuthType::NadeoLiveServices
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+pub struct ServerCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// Credentials of an OAuth app. Used for [`AuthType::OAuth`].
+///
+/// [`AuthType::OAuth`]: crate::auth::AuthType::OAuth
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+pub struct OAuthCredentials {
+    pub identifier: String,
+    pub secret: String,
+}
+
+/// A set of credentials for every supported auth method. Missing methods are `None`.
+#[derive(Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Credentials {
+    #[serde(default)]
+    pub normal_auth: Option<UbisoftCredentials>,
+    #[serde(default)]
+    pub server_auth: Option<ServerCredentials>,
+    #[serde(default, rename = "oauth")]
+    pub o_auth: Option<OAuthCredentials>,
+    /// Ubisoft remember-me ticket. It is preferred over `normal_auth` which is only used if the ticket is rejected.
+    #[serde(default)]
+    pub remember_me_ticket: Option<String>,
+}
+
+impl Credentials {
+    /// Returns `true` if no credentials are set.
+    pub fn is_empty(&self) -> bool {
+        self.normal_auth.is_none()
+            && self.server_auth.is_none()
+            && self.o_auth.is_none()
+            && self.remember_me_ticket.is_none()
+    }
+
+    /// Fills every missing auth method with the one from `other`.
+    pub(crate) fn or(self, other: Credentials) -> Self {
+        Self {
+            normal_auth: self.normal_auth.or(other.normal_auth),
+            server_auth: self.server_auth.or(other.server_auth),
+            o_auth: self.o_auth.or(other.o_auth),
+            remember_me_ticket: self.remember_me_ticket.or(other.remember_me_ticket),
+        }
+    }
+}
+
+/// Formats a secret without revealing it.
+pub(crate) struct Redacted;
+
+impl Debug for Redacted {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+impl Debug for UbisoftCredentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UbisoftCredentials")
+            .field("email", &self.email)
+            .field("password", &Redacted)
+            .finish()
+    }
+}
+
+impl Debug for ServerCredentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServerCredentials")
+            .field("username", &self.username)
+            .field("password", &Redacted)
+            .finish()
+    }
+}
+
+impl Debug for OAuthCredentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OAuthCredentials")
+            .field("identifier", &self.identifier)
+            .field("secret", &Redacted)
+            .finish()
+    }
+}
+
+impl Debug for Credentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("normal_auth", &self.normal_auth)
+            .field("server_auth", &self.server_auth)
+            .field("o_auth", &self.o_auth)
+            .field(
+                "remember_me_ticket",
+                &self.remember_me_ticket.as_ref().map(|_| Redacted),
+            )
+            .finish()
+    }
+}
+
+/// A source of [`Credentials`].
+///
+/// The provider is asked for credentials when the [`NadeoClient`] is built and again whenever a full re-login is required,
+/// e.g. when the refresh token expired. This allows providers to hand out rotated secrets.
+///
+/// # Examples
+///
+/// A provider fetching credentials from a secret store.
+/// ```rust
+/// # use nadeo_api::auth::credentials::{CredentialProvider, Credentials, OAuthCredentials};
+/// # use futures::future::BoxFuture;
+/// # use futures::FutureExt;
+/// #[derive(Debug)]
+/// struct Vault;
+///
+/// impl CredentialProvider for Vault {
+///     fn credentials(&self) -> BoxFuture<'_, nadeo_api::Result<Credentials>> {
+///         async {
+///             Ok(Credentials {
+///                 o_auth: Some(OAuthCredentials {
+///                     identifier: "my_identifier".to_string(),
+///                     secret: "my_secret".to_string(),
+///                 }),
+///                 ..Default::default()
+///             })
+///         }
+///         .boxed()
+///     }
+/// }
+/// ```
+///
+/// [`NadeoClient`]: crate::NadeoClient
+pub trait CredentialProvider: Debug + Send + Sync {
+    /// Fetches the current credentials.
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials>>;
+
+    /// Called with the rotated remember-me ticket after every successful Ubisoft login.
+    /// Providers can store the ticket so it can be used for the next login. Does nothing by default.
+    fn persist_remember_me_ticket(&self, _ticket: &str) -> BoxFuture<'_, Result<()>> {
+        async { Ok(()) }.boxed()
+    }
+
+    /// Returns a UserAgent if the provider has one configured. It is only used if no UserAgent was set on the builder.
+    fn user_agent(&self) -> BoxFuture<'_, Option<String>> {
+        async { None }.boxed()
+    }
+}
+
+/// Reads [`Credentials`] from environment variables.
+///
+/// With the default prefix `NADEO` the following variables are used:
+/// - `NADEO_EMAIL` and `NADEO_PASSWORD` for [`UbisoftCredentials`]
+/// - `NADEO_SERVER_USERNAME` and `NADEO_SERVER_PASSWORD` for [`ServerCredentials`]
+/// - `NADEO_OAUTH_ID` and `NADEO_OAUTH_SECRET` for [`OAuthCredentials`]
+/// - `NADEO_REMEMBER_ME_TICKET` for a Ubisoft remember-me ticket
+/// - `NADEO_USER_AGENT` for the UserAgent
+///
+/// Variables are read every time credentials are requested. Rotated remember-me tickets can't be written back to the
+/// environment, use [`NadeoClient::remember_me_ticket`] to store them elsewhere.
+///
+/// [`NadeoClient::remember_me_ticket`]: crate::NadeoClient::remember_me_ticket
+#[derive(Debug, Clone)]
+pub struct EnvCredentials {
+    prefix: String,
+}
+
+impl Default for EnvCredentials {
+    fn default() -> Self {
+        Self::with_prefix("NADEO")
+    }
+}
+
+impl EnvCredentials {
+    /// Uses `{prefix}_EMAIL`, `{prefix}_PASSWORD`, etc. instead of the default `NADEO_` variables.
+    pub fn with_prefix(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+        }
+    }
+
+    fn var(&self, name: &str) -> Option<String> {
+        std::env::var(format!("{}_{}", self.prefix, name))
+            .ok()
+            .filter(|val| !val.is_empty())
+    }
+
+    /// Reads a pair of variables. Returns an error if only one of them is set.
+    fn pair(&self, first: &str, second: &str) -> Result<Option<(String, String)>> {
+        match (self.var(first), self.var(second)) {
+            (Some(a), Some(b)) => Ok(Some((a, b))),
+            (None, None) => Ok(None),
+            (Some(_), None) => Err(Error::from(CredentialError::MissingVariable(format!(
+                "{}_{}",
+                self.prefix, second
+            )))),
+            (None, Some(_)) => Err(Error::from(CredentialError::MissingVariable(format!(
+                "{}_{}",
+                self.prefix, first
+            )))),
+        }
+    }
+
+    fn read(&self) -> Result<Credentials> {
+        let normal_auth = self
+            .pair("EMAIL", "PASSWORD")?
+            .map(|(email, password)| UbisoftCredentials { email, password });
+        let server_auth = self
+            .pair("SERVER_USERNAME", "SERVER_PASSWORD")?
+            .map(|(username, password)| ServerCredentials { username, password });
+        let o_auth = self
+            .pair("OAUTH_ID", "OAUTH_SECRET")?
+            .map(|(identifier, secret)| OAuthCredentials { identifier, secret });
+
+        Ok(Credentials {
+            normal_auth,
+            server_auth,
+            o_auth,
+            remember_me_ticket: self.var("REMEMBER_ME_TICKET"),
+        })
+    }
+}
+
+impl CredentialProvider for EnvCredentials {
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials>> {
+        let res = self.read();
+        async move { res }.boxed()
+    }
+
+    fn user_agent(&self) -> BoxFuture<'_, Option<String>> {
+        let user_agent = self.var("USER_AGENT");
+        async move { user_agent }.boxed()
+    }
+}
+
+/// Reads [`Credentials`] from a TOML file. The file is read every time credentials are requested.
+///
+/// Rotated remember-me tickets are written back to `remember_me_ticket`. Comments and formatting of the file are not
+/// kept when it is rewritten.
+///
+/// # Examples
+///
+/// ```toml
+/// user_agent = "My amazing app / my.email.address@gmail.com"
+/// remember_me_ticket = "my_remember_me_ticket" # optional
+///
+/// [normal_auth]
+/// email = "ubisoft_account_email"
+/// password = "ubisoft_account_password"
+///
+/// [server_auth]
+/// username = "my_username"
+/// password = "my_password"
+///
+/// [oauth]
+/// identifier = "my_identifier"
+/// secret = "my_secret"
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConfigFileCredentials {
+    path: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct ConfigFile {
+    user_agent: Option<String>,
+    #[serde(flatten)]
+    credentials: Credentials,
+}
+
+impl ConfigFileCredentials {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    async fn read(&self) -> Result<ConfigFile> {
+        let content = tokio::fs::read_to_string(&self.path)
+            .await
+            .map_err(CredentialError::from)?;
+        let config = toml::from_str(&content).map_err(CredentialError::from)?;
+
+        Ok(config)
+    }
+
+    /// Replaces the remember-me ticket in the file. The file is replaced atomically so a failed write can't corrupt it.
+    async fn write_remember_me_ticket(&self, ticket: &str) -> Result<()> {
+        let content = tokio::fs::read_to_string(&self.path)
+            .await
+            .map_err(CredentialError::from)?;
+        let mut table = toml::from_str::<toml::Table>(&content).map_err(CredentialError::from)?;
+        table.insert(
+            "remember_me_ticket".to_string(),
+            toml::Value::String(ticket.to_string()),
+        );
+        let content = toml::to_string(&table).map_err(CredentialError::from)?;
+
+        let mut tmp = self.path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        tokio::fs::write(&tmp, content)
+            .await
+            .map_err(CredentialError::from)?;
+        tokio::fs::rename(&tmp, &self.path)
+            .await
+            .map_err(CredentialError::from)?;
+
+        Ok(())
+    }
+}
+
+impl CredentialProvider for ConfigFileCredentials {
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials>> {
+        async { self.read().await.map(|config| config.credentials) }.boxed()
+    }
+
+    fn persist_remember_me_ticket(&self, ticket: &str) -> BoxFuture<'_, Result<()>> {
+        let ticket = ticket.to_string();
+        async move { self.write_remember_me_ticket(&ticket).await }.boxed()
+    }
+
+    fn user_agent(&self) -> BoxFuture<'_, Option<String>> {
+        async { self.read().await.ok().and_then(|config| config.user_agent) }.boxed()
+    }
+}
+
+/// Errors while reading credentials from a [`CredentialProvider`].
+#[derive(Error, Debug)]
+pub enum CredentialError {
+    #[error("environment variable {0} is not set")]
+    MissingVariable(String),
+    #[error("config file could not be read or written: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("config file is invalid: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("config file could not be serialized: {0}")]
+    TomlSerialize(#[from] toml::ser::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_vars(prefix: &str, vars: &[(&str, &str)]) -> EnvCredentials {
+        for (name, val) in vars {
+            std::env::set_var(format!("{prefix}_{name}"), val);
+        }
+
+        EnvCredentials::with_prefix(prefix)
+    }
+
+    #[test]
+    fn env_reads_complete_pairs() {
+        let env = set_vars(
+            "NADEO_TEST_COMPLETE",
+            &[
+                ("EMAIL", "email"),
+                ("PASSWORD", "password"),
+                ("OAUTH_ID", "id"),
+                ("OAUTH_SECRET", "secret"),
+                ("REMEMBER_ME_TICKET", "ticket"),
+            ],
+        );
+
+        let credentials = env.read().unwrap();
+        assert_eq!(
+            credentials.normal_auth,
+            Some(UbisoftCredentials {
+                email: "email".to_string(),
+                password: "password".to_string(),
+            })
+        );
+        assert_eq!(credentials.server_auth, None);
+        assert_eq!(
+            credentials.o_auth,
+            Some(OAuthCredentials {
+                identifier: "id".to_string(),
+                secret: "secret".to_string(),
+            })
+        );
+        assert_eq!(credentials.remember_me_ticket.as_deref(), Some("ticket"));
+    }
+
+    #[test]
+    fn env_rejects_incomplete_pairs() {
+        let env = set_vars("NADEO_TEST_MISSING_SECOND", &[("SERVER_USERNAME", "user")]);
+        assert!(matches!(
+            env.read(),
+            Err(Error::Credentials(CredentialError::MissingVariable(name)))
+                if name == "NADEO_TEST_MISSING_SECOND_SERVER_PASSWORD"
+        ));
+
+        let env = set_vars("NADEO_TEST_MISSING_FIRST", &[("OAUTH_SECRET", "secret")]);
+        assert!(matches!(
+            env.read(),
+            Err(Error::Credentials(CredentialError::MissingVariable(name)))
+                if name == "NADEO_TEST_MISSING_FIRST_OAUTH_ID"
+        ));
+    }
+
+    #[test]
+    fn env_ignores_empty_variables() {
+        let env = set_vars("NADEO_TEST_EMPTY", &[("EMAIL", ""), ("PASSWORD", "")]);
+
+        assert!(env.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn config_file_schema() {
+        let config = toml::from_str::<ConfigFile>(
+            r#"
+            user_agent = "agent"
+            remember_me_ticket = "ticket"
+
+            [normal_auth]
+            email = "email"
+            password = "password"
+
+            [server_auth]
+            username = "user"
+            password = "server_password"
+
+            [oauth]
+            identifier = "id"
+            secret = "secret"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.user_agent.as_deref(), Some("agent"));
+        assert_eq!(
+            config.credentials,
+            Credentials {
+                normal_auth: Some(UbisoftCredentials {
+                    email: "email".to_string(),
+                    password: "password".to_string(),
+                }),
+                server_auth: Some(ServerCredentials {
+                    username: "user".to_string(),
+                    password: "server_password".to_string(),
+                }),
+                o_auth: Some(OAuthCredentials {
+                    identifier: "id".to_string(),
+                    secret: "secret".to_string(),
+                }),
+                remember_me_ticket: Some("ticket".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn config_file_sections_are_optional() {
+        let config = toml::from_str::<ConfigFile>(
+            r#"
+            [oauth]
+            identifier = "id"
+            secret = "secret"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.user_agent, None);
+        assert!(config.credentials.normal_auth.is_none());
+        assert!(config.credentials.o_auth.is_some());
+    }
+
+    #[test]
+    fn config_file_rejects_incomplete_sections() {
+        let res = toml::from_str::<ConfigFile>(
+            r#"
+            [normal_auth]
+            email = "email"
+            "#,
+        );
+
+        assert!(res.is_err());
+    }
+
+    #[tokio::test]
+    async fn config_file_is_read_on_every_request() {
+        let path =
+            std::env::temp_dir().join(format!("nadeo-api-credentials-{}.toml", std::process::id()));
+        tokio::fs::write(
+            &path,
+            "user_agent = \"agent\"\n[oauth]\nidentifier = \"a\"\nsecret = \"b\"\n",
+        )
+        .await
+        .unwrap();
+        let provider = ConfigFileCredentials::new(&path);
+
+        assert_eq!(provider.user_agent().await.as_deref(), Some("agent"));
+        assert_eq!(
+            provider.credentials().await.unwrap().o_auth.unwrap().secret,
+            "b"
+        );
+
+        tokio::fs::write(&path, "[oauth]\nidentifier = \"a\"\nsecret = \"c\"\n")
+            .await
+            .unwrap();
+        assert_eq!(
+            provider.credentials().await.unwrap().o_auth.unwrap().secret,
+            "c"
+        );
+
+        provider
+            .persist_remember_me_ticket("rotated")
+            .await
+            .unwrap();
+        let credentials = provider.credentials().await.unwrap();
+        assert_eq!(credentials.remember_me_ticket.as_deref(), Some("rotated"));
+        assert_eq!(credentials.o_auth.unwrap().secret, "c");
+
+        tokio::fs::remove_file(&path).await.unwrap();
+        assert!(matches!(
+            provider.credentials().await,
+            Err(Error::Credentials(CredentialError::Io(_)))
+        ));
+    }
+
+    #[test]
+    fn debug_output_redacts_secrets() {
+        let credentials = Credentials {
+            normal_auth: Some(UbisoftCredentials {
+                email: "mail@example.com".to_string(),
+                password: "ubi-password".to_string(),
+            }),
+            server_auth: Some(ServerCredentials {
+                username: "server".to_string(),
+                password: "server-password".to_string(),
+            }),
+            o_auth: Some(OAuthCredentials {
+                identifier: "app".to_string(),
+                secret: "oauth-secret".to_string(),
+            }),
+            remember_me_ticket: Some("ticket-secret".to_string()),
+        };
+
+        let debug = format!("{credentials:?}");
+        for secret in [
+            "ubi-password",
+            "server-password",
+            "oauth-secret",
+            "ticket-secret",
+        ] {
+            assert!(!debug.contains(secret), "{debug}");
+        }
+        assert!(debug.contains("mail@example.com"));
+        assert!(debug.contains("<redacted>"));
+    }
+}
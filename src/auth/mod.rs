@@ -1,32 +1,40 @@
+use crate::auth::circuit_breaker::CircuitBreakerConfig;
+use crate::auth::credentials::Credentials;
+use crate::auth::o_auth::OAuthInfo;
 use crate::auth::token::access_token::AccessToken;
 use crate::auth::token::refresh_token::RefreshToken;
+use crate::auth::ubi::UbiSession;
 use crate::client::{
     EXPIRATION_TIME_BUFFER, NADEO_AUTH_URL, NADEO_REFRESH_URL, NADEO_SERVER_AUTH_URL,
-    UBISOFT_APP_ID,
 };
 use crate::request::metadata::MetaData;
 use crate::{Error, NadeoRequest, Result};
 use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
 use base64::Engine;
+use futures::future::join;
 use reqwest::header::{HeaderMap, HeaderValue};
 use reqwest::{Client, Response};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::str::FromStr;
 
+pub mod circuit_breaker;
+pub mod credentials;
 pub mod o_auth;
 pub mod token;
-
-const UBISOFT_AUTH_URL: &str = "https://public-ubiservices.ubi.com/v3/profiles/sessions";
+pub mod ubi;
 
 /// Defines authentication credentials used for the Nadeo API.
-#[derive(strum::Display, Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(strum::Display, Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
 pub enum AuthType {
     #[strum(to_string = "NadeoServices")]
     NadeoServices,
     #[strum(to_string = "NadeoLiveServices")]
     NadeoLiveServices,
     OAuth,
+    /// Ubisoft Services (`public-ubiservices.ubi.com`). Requires a Ubisoft account.
+    #[strum(to_string = "UbiServices")]
+    UbiServices,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -147,7 +155,8 @@ impl AuthInfo {
             .json(&body)
             .send()
             .await
-            .map_err(Error::from)?;
+            .map_err(Error::from)?
+            .error_for_status()?;
 
         let json = res.json::<Value>().await.map_err(Error::from)?;
 
@@ -171,7 +180,7 @@ impl AuthInfo {
     /// [`Error`]: Error
     /// [`force_refresh`]: AuthInfo::force_refresh
     pub(crate) async fn refresh(&mut self, meta_data: &MetaData, client: &Client) -> Result<bool> {
-        if !self.expires_in() < EXPIRATION_TIME_BUFFER {
+        if self.expires_in() > EXPIRATION_TIME_BUFFER {
             return Ok(false);
         }
 
@@ -208,8 +217,8 @@ impl AuthInfo {
                 meta_data.user_agent.parse::<HeaderValue>().unwrap(),
             )
             .headers(request.headers);
-        if let Some(json) = request.body {
-            res = res.body(json);
+        if let Some(body) = request.body {
+            res = body.apply(res)?;
         }
 
         let res = res.send().await?.error_for_status()?;
@@ -218,7 +227,7 @@ impl AuthInfo {
     }
 }
 
-fn encode_auth(username: &str, password: &str) -> String {
+pub(crate) fn encode_auth(username: &str, password: &str) -> String {
     let auth = format!("{}:{}", username, password);
     let auth = auth.as_bytes();
 
@@ -227,31 +236,83 @@ fn encode_auth(username: &str, password: &str) -> String {
     b64
 }
 
-pub(crate) async fn get_ubi_auth_ticket(
-    email: &str,
-    password: &str,
+/// Result of logging in to [`AuthType::NadeoServices`] and [`AuthType::NadeoLiveServices`].
+#[derive(Debug, Clone)]
+pub(crate) struct NadeoLogin {
+    pub(crate) normal_auth: AuthInfo,
+    pub(crate) live_auth: AuthInfo,
+    /// The Ubisoft session if a Ubisoft account was used.
+    pub(crate) ubi_session: Option<UbiSession>,
+}
+
+/// Logs in to [`AuthType::NadeoServices`] and [`AuthType::NadeoLiveServices`] using the given credentials.
+/// Ubisoft credentials (or a remember-me ticket) are preferred over server credentials.
+/// Returns `None` if neither are present.
+pub(crate) async fn nadeo_login(
+    credentials: &Credentials,
     meta_data: &MetaData,
     client: &Client,
-) -> Result<String> {
-    let mut headers = HeaderMap::new();
-
-    headers.insert("Content-Type", "application/json".parse().unwrap());
-    headers.insert("Ubi-AppId", UBISOFT_APP_ID.parse().unwrap());
-    headers.insert("User-Agent", meta_data.user_agent.parse().unwrap());
+    circuit_breaker: &CircuitBreakerConfig,
+) -> Result<Option<NadeoLogin>> {
+    let ubi_session = UbiSession::login(credentials, meta_data, client, circuit_breaker).await?;
+    let (normal_auth, live_auth) = if let Some(ref session) = ubi_session {
+        let (normal_auth, live_auth) = join(
+            AuthInfo::new(AuthType::NadeoServices, &session.ticket, meta_data, client),
+            AuthInfo::new(
+                AuthType::NadeoLiveServices,
+                &session.ticket,
+                meta_data,
+                client,
+            ),
+        )
+        .await;
+
+        (normal_auth?, live_auth?)
+    } else if let Some(ref auth) = credentials.server_auth {
+        let key = format!("server:{}", auth.username);
+        let login = async {
+            let (normal_auth, live_auth) = join(
+                AuthInfo::new_server(
+                    AuthType::NadeoServices,
+                    meta_data,
+                    &auth.username,
+                    &auth.password,
+                    client,
+                ),
+                AuthInfo::new_server(
+                    AuthType::NadeoLiveServices,
+                    meta_data,
+                    &auth.username,
+                    &auth.password,
+                    client,
+                ),
+            )
+            .await;
 
-    let ubi_auth_token = format!("Basic {}", encode_auth(email, password));
-    headers.insert("Authorization", ubi_auth_token.parse().unwrap());
+            Ok((normal_auth?, live_auth?))
+        };
 
-    // get ubisoft ticket
-    let res = client
-        .post(UBISOFT_AUTH_URL)
-        .headers(headers)
-        .send()
-        .await?
-        .error_for_status()?;
+        circuit_breaker.guard(&key, &auth.password, login).await?
+    } else {
+        return Ok(None);
+    };
 
-    let json = res.json::<Value>().await?;
-    let ticket = json["ticket"].as_str().unwrap().to_string();
+    Ok(Some(NadeoLogin {
+        normal_auth,
+        live_auth,
+        ubi_session,
+    }))
+}
 
-    Ok(ticket)
+/// Requests an OAuth access token using the given credentials. Returns `None` if no OAuth credentials are present.
+pub(crate) async fn oauth_login(
+    credentials: &Credentials,
+    client: &Client,
+) -> Result<Option<OAuthInfo>> {
+    match credentials.o_auth {
+        Some(ref auth) => Ok(Some(
+            OAuthInfo::new(&auth.identifier, &auth.secret, client).await?,
+        )),
+        None => Ok(None),
+    }
 }
@@ -57,6 +57,18 @@ impl OAuthInfo {
         Ok(json)
     }
 
+    /// A token which does not expire during tests.
+    #[cfg(test)]
+    pub(crate) fn for_tests(access_token: &str) -> Self {
+        Self {
+            identifier: "identifier".to_string(),
+            secret: "secret".to_string(),
+            token_type: "Bearer".to_string(),
+            exp: Local::now().timestamp() + 24 * 3600,
+            access_token: access_token.to_string(),
+        }
+    }
+
     /// Send a request to the Nadeo OAuth API to get a new access token.
     pub(crate) async fn force_refresh(&mut self, client: &Client) -> Result<()> {
         let new = Self::new(&self.identifier, &self.secret, client).await?;
@@ -108,8 +120,8 @@ impl OAuthInfo {
                 meta_data.user_agent.parse::<HeaderValue>().unwrap(),
             )
             .headers(request.headers);
-        if let Some(json) = request.body {
-            res = res.body(json);
+        if let Some(body) = request.body {
+            res = body.apply(res)?;
         }
 
         let res = res.send().await?.error_for_status()?;
@@ -1,4 +1,5 @@
 use crate::auth::token::ParseTokenError;
+use crate::types::AccountId;
 use crate::Error;
 use base64::prelude::BASE64_URL_SAFE_NO_PAD;
 use base64::Engine;
@@ -53,6 +54,11 @@ impl AccessToken {
     pub(crate) fn expires_in(&self) -> i64 {
         self.payload.expires_in()
     }
+
+    /// Returns the *accountID* of the account the token was issued for.
+    pub(crate) fn account_id(&self) -> Result<AccountId, Error> {
+        AccountId::try_from(&self.payload)
+    }
 }
 
 /// Deserialized version of the payload of an [`AccessToken`].
@@ -91,6 +97,15 @@ impl FromStr for AccessPayload {
     }
 }
 
+impl TryFrom<&AccessPayload> for AccountId {
+    type Error = Error;
+
+    /// Parses the `sub` claim, which contains the *accountID*.
+    fn try_from(payload: &AccessPayload) -> Result<Self, Self::Error> {
+        Ok(payload.sub.parse()?)
+    }
+}
+
 impl AccessPayload {
     /// Serializes the payload (part of the [`AccessToken`]) into the format required for API requests.
     ///
@@ -0,0 +1,207 @@
+use crate::auth::circuit_breaker::CircuitBreakerConfig;
+use crate::auth::credentials::Credentials;
+use crate::auth::{encode_auth, AuthType};
+use crate::client::{EXPIRATION_TIME_BUFFER, UBISOFT_APP_ID};
+use crate::request::metadata::MetaData;
+use crate::{Error, NadeoRequest, Result};
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::{Client, Method, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+const UBISOFT_AUTH_URL: &str = "https://public-ubiservices.ubi.com/v3/profiles/sessions";
+
+/// A Ubisoft Services session. Used for authenticating with Ubisoft Services and for getting Nadeo tokens.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UbiSession {
+    pub(crate) ticket: String,
+    pub(crate) session_id: String,
+    pub(crate) expiration: DateTime<Utc>,
+    pub(crate) profile_id: String,
+    pub(crate) user_id: String,
+    /// Rotated remember-me ticket. Can be used for logging in without a password.
+    #[serde(default)]
+    pub(crate) remember_me_ticket: Option<String>,
+}
+
+impl UbiSession {
+    /// Creates a session using either the email and password (`Basic`) or a remember-me ticket (`rm_v1`).
+    async fn create(authorization: &str, meta_data: &MetaData, client: &Client) -> Result<Self> {
+        Self::request(Method::POST, authorization, None, meta_data, client).await
+    }
+
+    async fn request(
+        method: Method,
+        authorization: &str,
+        session_id: Option<&str>,
+        meta_data: &MetaData,
+        client: &Client,
+    ) -> Result<Self> {
+        let mut headers = HeaderMap::new();
+
+        headers.insert("Content-Type", "application/json".parse().unwrap());
+        headers.insert("Ubi-AppId", UBISOFT_APP_ID.parse().unwrap());
+        headers.insert("User-Agent", meta_data.user_agent.parse().unwrap());
+        headers.insert("Authorization", authorization.parse().unwrap());
+        if let Some(session_id) = session_id {
+            headers.insert("Ubi-SessionId", session_id.parse().unwrap());
+        }
+
+        let body = json!(
+            {
+                "rememberMe": true
+            }
+        );
+
+        let res = client
+            .request(method, UBISOFT_AUTH_URL)
+            .headers(headers)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(res.json::<Self>().await?)
+    }
+
+    /// Logs in using an email and password.
+    pub(crate) async fn with_password(
+        email: &str,
+        password: &str,
+        meta_data: &MetaData,
+        client: &Client,
+    ) -> Result<Self> {
+        let ubi_auth_token = format!("Basic {}", encode_auth(email, password));
+
+        Self::create(&ubi_auth_token, meta_data, client).await
+    }
+
+    /// Logs in using a remember-me ticket instead of a password.
+    pub(crate) async fn with_remember_me_ticket(
+        remember_me_ticket: &str,
+        meta_data: &MetaData,
+        client: &Client,
+    ) -> Result<Self> {
+        let ubi_auth_token = format!("rm_v1 t={}", remember_me_ticket);
+
+        Self::create(&ubi_auth_token, meta_data, client).await
+    }
+
+    /// Logs in to Ubisoft. A remember-me ticket is preferred and the password is only used if the ticket is missing or rejected.
+    /// Returns `None` if neither are present.
+    /// Password logins are guarded by the circuit breaker.
+    pub(crate) async fn login(
+        credentials: &Credentials,
+        meta_data: &MetaData,
+        client: &Client,
+        circuit_breaker: &CircuitBreakerConfig,
+    ) -> Result<Option<Self>> {
+        if let Some(ref remember_me_ticket) = credentials.remember_me_ticket {
+            match Self::with_remember_me_ticket(remember_me_ticket, meta_data, client).await {
+                Ok(session) => return Ok(Some(session)),
+                Err(Error::NadeoApi(e)) if is_rejected(&e) && credentials.normal_auth.is_some() => {
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        match credentials.normal_auth {
+            Some(ref auth) => {
+                let key = format!("ubi:{}", auth.email);
+                let login = Self::with_password(&auth.email, &auth.password, meta_data, client);
+
+                Ok(Some(
+                    circuit_breaker.guard(&key, &auth.password, login).await?,
+                ))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Extends the session and replaces the ticket.
+    pub(crate) async fn force_refresh(
+        &mut self,
+        meta_data: &MetaData,
+        client: &Client,
+    ) -> Result<()> {
+        let authorization = format!("Ubi_v1 t={}", self.ticket);
+        let new = Self::request(
+            Method::PUT,
+            &authorization,
+            Some(&self.session_id),
+            meta_data,
+            client,
+        )
+        .await?;
+
+        let remember_me_ticket = new.remember_me_ticket.or(self.remember_me_ticket.take());
+        *self = Self {
+            remember_me_ticket,
+            ..new
+        };
+
+        Ok(())
+    }
+
+    /// Checks whether the ticket is about to expire. If so [`UbiSession::force_refresh`] is called and `Ok(true)` or `Err(Error)` is returned.
+    /// If the ticket is still valid `Ok(false)` is returned.
+    pub(crate) async fn refresh(&mut self, meta_data: &MetaData, client: &Client) -> Result<bool> {
+        if self.expires_in() > EXPIRATION_TIME_BUFFER {
+            return Ok(false);
+        }
+
+        self.force_refresh(meta_data, client).await.map(|_| true)
+    }
+
+    /// Returns the amount of **seconds** until the ticket expires.
+    pub(crate) fn expires_in(&self) -> i64 {
+        (self.expiration - Utc::now()).num_seconds()
+    }
+
+    /// Executes a [`NadeoRequest`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `AuthType` of the request doesn't match `UbiServices`.
+    pub(crate) async fn execute(
+        &mut self,
+        request: NadeoRequest,
+        meta_data: &MetaData,
+        client: &Client,
+    ) -> Result<Response> {
+        assert_eq!(request.auth_type, AuthType::UbiServices);
+
+        self.refresh(meta_data, client).await?;
+        let token = format!("Ubi_v1 t={}", self.ticket);
+
+        let api_request = client.request(request.method, request.url);
+
+        let mut res = api_request
+            .header("Authorization", token.parse::<HeaderValue>().unwrap())
+            .header("Ubi-AppId", UBISOFT_APP_ID.parse::<HeaderValue>().unwrap())
+            .header(
+                "Ubi-SessionId",
+                self.session_id.parse::<HeaderValue>().unwrap(),
+            )
+            .header(
+                "User-Agent",
+                meta_data.user_agent.parse::<HeaderValue>().unwrap(),
+            )
+            .headers(request.headers);
+        if let Some(body) = request.body {
+            res = body.apply(res)?;
+        }
+
+        let res = res.send().await?.error_for_status()?;
+
+        Ok(res)
+    }
+}
+
+/// Returns `true` if the error is caused by the server rejecting the credentials.
+fn is_rejected(err: &reqwest::Error) -> bool {
+    err.status()
+        .is_some_and(|status| status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN)
+}
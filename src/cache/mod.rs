@@ -0,0 +1,529 @@
+//! Caching of API responses.
+//!
+//! A [`NadeoClient`] with a cache stores successful `GET` responses for the TTL configured in the [`CachePolicy`]
+//! and answers identical requests from the cache until they expire.
+//! Use [`LruCache`] for an in-memory cache or implement [`ResponseCache`] for an external store.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use nadeo_api::NadeoClient;
+//! # use nadeo_api::cache::{CachePolicy, LruCache};
+//! # use std::time::Duration;
+//! # async fn run() -> nadeo_api::Result<()> {
+//! let policy = CachePolicy::new()
+//!     .with_rule("prod.trackmania.core.nadeo.online", "/maps/*", Duration::from_secs(60 * 60))
+//!     .with_rule("prod.trackmania.core.nadeo.online", "/zones/", Duration::from_secs(24 * 60 * 60));
+//!
+//! let mut client = NadeoClient::builder()
+//!     .with_normal_auth("email", "password")
+//!     .user_agent("Testing the API / mustermann.max@gmail.com")
+//!     .response_cache(LruCache::new(1000), policy)
+//!     .build()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`NadeoClient`]: crate::NadeoClient
+
+use crate::client::buffered_response::BufferedResponse;
+use crate::request::NadeoRequest;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Method, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Identifies a cached response. Consists of the method, [`AuthType`], URL and body of the request.
+///
+/// [`AuthType`]: crate::auth::AuthType
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct CacheKey(String);
+
+impl CacheKey {
+    pub(crate) fn of(request: &NadeoRequest) -> Self {
+        Self(format!(
+            "{} {} {} {}",
+            request.method,
+            request.auth_type,
+            request.url,
+            request
+                .body
+                .as_ref()
+                .and_then(|body| body.as_text())
+                .unwrap_or_default()
+        ))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// A response stored in a [`ResponseCache`].
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl From<&BufferedResponse> for CachedResponse {
+    fn from(res: &BufferedResponse) -> Self {
+        Self {
+            status: res.status.as_u16(),
+            headers: res
+                .headers
+                .iter()
+                .filter_map(|(name, val)| Some((name.to_string(), val.to_str().ok()?.to_string())))
+                .collect(),
+            body: res.body.to_vec(),
+        }
+    }
+}
+
+impl From<CachedResponse> for BufferedResponse {
+    fn from(res: CachedResponse) -> Self {
+        let mut headers = HeaderMap::new();
+        for (name, val) in res.headers {
+            if let (Ok(name), Ok(val)) = (HeaderName::try_from(name), HeaderValue::try_from(val)) {
+                headers.append(name, val);
+            }
+        }
+
+        Self {
+            status: StatusCode::from_u16(res.status).unwrap_or(StatusCode::OK),
+            headers,
+            body: res.body.into(),
+        }
+    }
+}
+
+/// A store for cached responses.
+pub trait ResponseCache: Debug + Send + Sync {
+    /// Returns the response stored for the key. Expired responses must not be returned.
+    fn get(&self, key: &CacheKey) -> BoxFuture<'_, Option<CachedResponse>>;
+
+    /// Stores a response for the key. The response expires after `ttl`.
+    fn put(&self, key: CacheKey, response: CachedResponse, ttl: Duration) -> BoxFuture<'_, ()>;
+}
+
+#[derive(Debug)]
+struct LruEntry {
+    response: CachedResponse,
+    expires_at: Instant,
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct LruState {
+    entries: HashMap<CacheKey, LruEntry>,
+    /// Keys ordered by their last use.
+    order: BTreeMap<u64, CacheKey>,
+    tick: u64,
+}
+
+impl LruState {
+    fn touch(&mut self, key: &CacheKey) {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some(entry) = self.entries.get_mut(key) {
+            self.order.remove(&entry.last_used);
+            entry.last_used = tick;
+            self.order.insert(tick, key.clone());
+        }
+    }
+
+    fn remove(&mut self, key: &CacheKey) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.order.remove(&entry.last_used);
+        }
+    }
+}
+
+/// An in-memory [`ResponseCache`] which evicts the least recently used response once it is full.
+#[derive(Debug)]
+pub struct LruCache {
+    capacity: usize,
+    state: Mutex<LruState>,
+}
+
+impl LruCache {
+    /// Creates a cache holding up to `capacity` responses.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Returns the number of stored responses, including expired ones which were not evicted yet.
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl ResponseCache for LruCache {
+    fn get(&self, key: &CacheKey) -> BoxFuture<'_, Option<CachedResponse>> {
+        let mut state = self.state.lock().unwrap();
+        let res = match state.entries.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => {
+                let response = entry.response.clone();
+                state.touch(key);
+                Some(response)
+            }
+            Some(_) => {
+                state.remove(key);
+                None
+            }
+            None => None,
+        };
+
+        async move { res }.boxed()
+    }
+
+    fn put(&self, key: CacheKey, response: CachedResponse, ttl: Duration) -> BoxFuture<'_, ()> {
+        let mut state = self.state.lock().unwrap();
+        state.remove(&key);
+        while state.entries.len() >= self.capacity {
+            let Some((_, oldest)) = state.order.pop_first() else {
+                break;
+            };
+            state.entries.remove(&oldest);
+        }
+
+        if self.capacity > 0 {
+            state.entries.insert(
+                key.clone(),
+                LruEntry {
+                    response,
+                    expires_at: Instant::now() + ttl,
+                    last_used: 0,
+                },
+            );
+            state.touch(&key);
+        }
+
+        async {}.boxed()
+    }
+}
+
+/// Defines which responses are cached and for how long.
+#[derive(Debug, Clone, Default)]
+pub struct CachePolicy {
+    rules: Vec<CacheRule>,
+    default_ttl: Option<Duration>,
+}
+
+#[derive(Debug, Clone)]
+struct CacheRule {
+    host: String,
+    path: String,
+    ttl: Duration,
+}
+
+/// Matches `text` against a pattern in which `*` matches any sequence of characters.
+fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts = parts.collect::<Vec<_>>();
+    let Some((last, middle)) = parts.split_last() else {
+        // no `*` in the pattern
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.ends_with(last)
+}
+
+impl CachePolicy {
+    /// Creates a policy which does not cache anything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Caches responses of URLs with the given host and path for `ttl`.
+    /// `*` in the host or path matches any sequence of characters, e.g. `/maps/*`.
+    /// The first matching rule is used.
+    pub fn with_rule(mut self, host: &str, path: &str, ttl: Duration) -> Self {
+        self.rules.push(CacheRule {
+            host: host.to_ascii_lowercase(),
+            path: path.to_string(),
+            ttl,
+        });
+
+        self
+    }
+
+    /// Caches responses which do not match any rule for `ttl`.
+    pub fn with_default_ttl(mut self, ttl: Duration) -> Self {
+        self.default_ttl = Some(ttl);
+
+        self
+    }
+
+    /// Returns how long the response of the request should be cached. Returns `None` if it should not be cached.
+    pub fn ttl(&self, method: &Method, url: &str) -> Option<Duration> {
+        if method != Method::GET {
+            return None;
+        }
+        let url = Url::parse(url).ok()?;
+        let host = url.host_str()?.to_ascii_lowercase();
+
+        self.rules
+            .iter()
+            .find(|rule| {
+                matches_pattern(&rule.host, &host) && matches_pattern(&rule.path, url.path())
+            })
+            .map(|rule| rule.ttl)
+            .or(self.default_ttl)
+    }
+}
+
+/// Hit and miss statistics of a response cache.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct CacheStats {
+    /// Requests answered from the cache.
+    pub hits: u64,
+    /// Cacheable requests which were not in the cache.
+    pub misses: u64,
+    /// Requests which bypassed the cache.
+    pub bypassed: u64,
+}
+
+impl CacheStats {
+    /// Returns the share of cacheable requests answered from the cache.
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+/// The cache of a client. Shared by all clones of the client.
+#[derive(Debug)]
+pub(crate) struct ClientCache {
+    pub(crate) store: Arc<dyn ResponseCache>,
+    pub(crate) policy: CachePolicy,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    bypassed: AtomicU64,
+}
+
+impl ClientCache {
+    pub(crate) fn new(store: Arc<dyn ResponseCache>, policy: CachePolicy) -> Self {
+        Self {
+            store,
+            policy,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            bypassed: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn record_hit(&self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_bypass(&self) {
+        self.bypassed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            bypassed: self.bypassed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthType;
+    use crate::client::test_support::{response, TestServer};
+    use crate::NadeoClient;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    fn key(name: &str) -> CacheKey {
+        CacheKey(name.to_string())
+    }
+
+    fn cached(body: &str) -> CachedResponse {
+        CachedResponse {
+            status: 200,
+            headers: Vec::new(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[tokio::test]
+    async fn lru_cache_evicts_the_least_recently_used_response() {
+        let cache = LruCache::new(2);
+        cache.put(key("a"), cached("a"), TTL).await;
+        cache.put(key("b"), cached("b"), TTL).await;
+        // `a` is used after `b`, so `b` is evicted
+        assert_eq!(cache.get(&key("a")).await, Some(cached("a")));
+
+        cache.put(key("c"), cached("c"), TTL).await;
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&key("b")).await, None);
+        assert_eq!(cache.get(&key("a")).await, Some(cached("a")));
+        assert_eq!(cache.get(&key("c")).await, Some(cached("c")));
+    }
+
+    #[tokio::test]
+    async fn lru_cache_replaces_responses() {
+        let cache = LruCache::new(2);
+        cache.put(key("a"), cached("old"), TTL).await;
+        cache.put(key("a"), cached("new"), TTL).await;
+
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&key("a")).await, Some(cached("new")));
+    }
+
+    #[tokio::test]
+    async fn lru_cache_removes_expired_responses() {
+        let cache = LruCache::new(2);
+        cache.put(key("a"), cached("a"), Duration::ZERO).await;
+        assert_eq!(cache.len(), 1);
+
+        assert_eq!(cache.get(&key("a")).await, None);
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn lru_cache_without_capacity_stores_nothing() {
+        let cache = LruCache::new(0);
+        cache.put(key("a"), cached("a"), TTL).await;
+
+        assert!(cache.is_empty());
+        assert_eq!(cache.get(&key("a")).await, None);
+    }
+
+    #[test]
+    fn matches_wildcard_patterns() {
+        assert!(matches_pattern("/zones/", "/zones/"));
+        assert!(!matches_pattern("/zones/", "/zones/1"));
+        assert!(matches_pattern("/maps/*", "/maps/"));
+        assert!(matches_pattern("/maps/*", "/maps/abc"));
+        assert!(!matches_pattern("/maps/*", "/map"));
+        assert!(matches_pattern("*", ""));
+        assert!(matches_pattern(
+            "*.nadeo.live",
+            "live-services.trackmania.nadeo.live"
+        ));
+        assert!(!matches_pattern("*.nadeo.live", "nadeo.online"));
+        assert!(matches_pattern("/a/*/c/*", "/a/b/c/d"));
+        assert!(!matches_pattern("/a/*/c/*", "/a/b/d"));
+        // the last part must match at the end
+        assert!(matches_pattern("/*/x", "/x/x"));
+        assert!(!matches_pattern("/*/x", "/x/y"));
+    }
+
+    #[test]
+    fn policy_uses_the_first_matching_rule() {
+        let policy = CachePolicy::new()
+            .with_rule("Example.com", "/maps/*", Duration::from_secs(1))
+            .with_rule("example.com", "*", Duration::from_secs(2))
+            .with_default_ttl(Duration::from_secs(3));
+
+        let ttl = |url| policy.ttl(&Method::GET, url);
+        assert_eq!(
+            ttl("https://EXAMPLE.com/maps/1"),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(
+            ttl("https://example.com/zones/"),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(ttl("https://other.com/"), Some(Duration::from_secs(3)));
+        assert_eq!(
+            CachePolicy::new().ttl(&Method::GET, "https://example.com/"),
+            None
+        );
+    }
+
+    #[test]
+    fn policy_only_caches_get_requests() {
+        let policy = CachePolicy::new().with_default_ttl(TTL);
+
+        assert_eq!(policy.ttl(&Method::GET, "https://example.com/"), Some(TTL));
+        for method in [Method::POST, Method::PUT, Method::DELETE, Method::HEAD] {
+            assert_eq!(policy.ttl(&method, "https://example.com/"), None);
+        }
+    }
+
+    #[tokio::test]
+    async fn client_counts_hits_misses_and_bypasses() {
+        let server = TestServer::start(|_| response(200, "text/plain", b"body")).await;
+        let mut client = NadeoClient::for_tests();
+        client.cache = Some(Arc::new(ClientCache::new(
+            Arc::new(LruCache::new(10)),
+            CachePolicy::new().with_rule("127.0.0.1", "/cached", TTL),
+        )));
+        let request = |path: &str| {
+            NadeoRequest::builder()
+                .url(&format!("{}{path}", server.url))
+                .auth_type(AuthType::OAuth)
+                .method(Method::GET)
+        };
+
+        for _ in 0..3 {
+            let res = client
+                .execute(request("/cached").build().unwrap())
+                .await
+                .unwrap();
+            assert_eq!(res.text().await.unwrap(), "body");
+        }
+        client
+            .execute(request("/cached").bypass_cache().build().unwrap())
+            .await
+            .unwrap();
+        // not matched by a rule
+        client
+            .execute(request("/other").build().unwrap())
+            .await
+            .unwrap();
+
+        let stats = client.cache_stats().unwrap();
+        assert_eq!(
+            stats,
+            CacheStats {
+                hits: 2,
+                misses: 1,
+                bypassed: 1,
+            }
+        );
+        assert_eq!(stats.hit_rate(), 2.0 / 3.0);
+    }
+}
@@ -0,0 +1,152 @@
+//! Official campaigns, Weekly Shorts and the Track of the Day.
+
+use crate::auth::AuthType;
+use crate::types::{CampaignId, MapUid, SeasonId};
+use crate::{NadeoClient, NadeoRequest, Result};
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+
+pub mod watcher;
+
+pub use watcher::{CampaignKind, CampaignWatcher, TotdDate, WatchEvent, WatcherState};
+
+const CAMPAIGN_URL: &str = "https://live-services.trackmania.nadeo.live/api/campaign";
+
+/// A map of the playlist of a [`Campaign`].
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CampaignMap {
+    pub position: u32,
+    pub map_uid: MapUid,
+}
+
+/// An official campaign, e.g. a seasonal campaign or a Weekly Shorts week.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Campaign {
+    pub id: CampaignId,
+    pub season_uid: SeasonId,
+    pub name: String,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub start_timestamp: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub end_timestamp: DateTime<Utc>,
+    #[serde(default)]
+    pub playlist: Vec<CampaignMap>,
+}
+
+impl Campaign {
+    /// The maps of the campaign ordered by their position.
+    pub fn map_uids(&self) -> Vec<MapUid> {
+        let mut playlist = self.playlist.iter().collect::<Vec<_>>();
+        playlist.sort_by_key(|map| map.position);
+
+        playlist
+            .into_iter()
+            .map(|map| map.map_uid.clone())
+            .collect()
+    }
+}
+
+/// A page of campaigns.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CampaignList {
+    pub campaign_list: Vec<Campaign>,
+    pub item_count: u32,
+    /// Time at which the list is expected to change next.
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub next_request_timestamp: DateTime<Utc>,
+}
+
+/// A day of the Track of the Day.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TotdDay {
+    pub campaign_id: CampaignId,
+    /// `None` if the map of the day is not published yet.
+    #[serde(deserialize_with = "empty_as_none")]
+    pub map_uid: Option<MapUid>,
+    /// Day of the week starting at 0 for monday.
+    pub day: u32,
+    pub month_day: u32,
+    pub season_uid: SeasonId,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub start_timestamp: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub end_timestamp: DateTime<Utc>,
+}
+
+/// A month of the Track of the Day.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TotdMonth {
+    pub year: i32,
+    pub month: u32,
+    pub last_day: u32,
+    pub days: Vec<TotdDay>,
+}
+
+/// A page of Track of the Day months.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TotdMonthList {
+    pub month_list: Vec<TotdMonth>,
+    pub item_count: u32,
+    /// Time at which the next map is published.
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub next_request_timestamp: DateTime<Utc>,
+}
+
+fn empty_as_none<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<MapUid>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(uid) if !uid.is_empty() => uid.parse().map(Some).map_err(serde::de::Error::custom),
+        _ => Ok(None),
+    }
+}
+
+impl NadeoClient {
+    /// Gets the seasonal campaigns starting with the newest one. Requires [`AuthType::NadeoLiveServices`].
+    pub async fn official_campaigns(&mut self, offset: u32, length: u32) -> Result<CampaignList> {
+        self.campaign_get(&format!(
+            "{CAMPAIGN_URL}/official?offset={offset}&length={length}"
+        ))
+        .await
+    }
+
+    /// Gets the Weekly Shorts campaigns starting with the newest one. Requires [`AuthType::NadeoLiveServices`].
+    pub async fn weekly_shorts_campaigns(
+        &mut self,
+        offset: u32,
+        length: u32,
+    ) -> Result<CampaignList> {
+        self.campaign_get(&format!(
+            "{CAMPAIGN_URL}/weekly-shorts?offset={offset}&length={length}"
+        ))
+        .await
+    }
+
+    /// Gets the months of the Track of the Day starting with the current one. Requires [`AuthType::NadeoLiveServices`].
+    pub async fn totd_months(&mut self, offset: u32, length: u32) -> Result<TotdMonthList> {
+        self.campaign_get(&format!(
+            "{CAMPAIGN_URL}/month?offset={offset}&length={length}"
+        ))
+        .await
+    }
+
+    async fn campaign_get<T: DeserializeOwned>(&mut self, url: &str) -> Result<T> {
+        let request = NadeoRequest::builder()
+            .url(url)
+            .auth_type(AuthType::NadeoLiveServices)
+            .method(Method::GET)
+            .build()?;
+
+        let res = self.execute(request).await?;
+
+        Ok(res.json().await?)
+    }
+}
@@ -0,0 +1,562 @@
+use crate::campaigns::{Campaign, TotdDay, TotdMonth};
+use crate::types::{CampaignId, MapUid};
+use crate::{NadeoClient, Result};
+use chrono::{DateTime, Datelike, Utc};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Number of campaigns requested per poll. Changes of older campaigns are not detected.
+const CAMPAIGN_PAGE_LENGTH: u32 = 2;
+
+/// Kinds of official campaigns.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+pub enum CampaignKind {
+    Seasonal,
+    WeeklyShorts,
+}
+
+/// Date of a Track of the Day.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct TotdDate {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+/// Events of a [`CampaignWatcher`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchEvent {
+    /// A new Track of the Day was published.
+    NewTotd { date: TotdDate, day: TotdDay },
+    NewCampaign {
+        kind: CampaignKind,
+        campaign: Campaign,
+    },
+    /// The maps of a known campaign changed.
+    PlaylistChanged {
+        kind: CampaignKind,
+        campaign: Campaign,
+        previous: Vec<MapUid>,
+    },
+}
+
+/// What a [`CampaignWatcher`] has already seen. Persist it to resume watching without repeating events.
+///
+/// Sources which were not polled yet only record the current state on their first poll without emitting events.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub struct WatcherState {
+    /// Whether the Track of the Day was polled. `latest_totd` is still `None` if no day was published at that time.
+    #[serde(default)]
+    pub totd_polled: bool,
+    #[serde(default)]
+    pub latest_totd: Option<TotdDate>,
+    #[serde(default)]
+    pub seasonal: Option<BTreeMap<CampaignId, Vec<MapUid>>>,
+    #[serde(default)]
+    pub weekly_shorts: Option<BTreeMap<CampaignId, Vec<MapUid>>>,
+}
+
+impl WatcherState {
+    fn campaigns(&mut self, kind: CampaignKind) -> &mut Option<BTreeMap<CampaignId, Vec<MapUid>>> {
+        match kind {
+            CampaignKind::Seasonal => &mut self.seasonal,
+            CampaignKind::WeeklyShorts => &mut self.weekly_shorts,
+        }
+    }
+
+    fn apply(&mut self, event: &WatchEvent) {
+        match event {
+            WatchEvent::NewTotd { date, .. } => {
+                self.totd_polled = true;
+                self.latest_totd = Some(*date);
+            }
+            WatchEvent::NewCampaign { kind, campaign }
+            | WatchEvent::PlaylistChanged { kind, campaign, .. } => {
+                self.campaigns(*kind)
+                    .get_or_insert_with(BTreeMap::new)
+                    .insert(campaign.id, campaign.map_uids());
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Source {
+    Totd,
+    Campaigns(CampaignKind),
+}
+
+/// Watches the Track of the Day and the official campaigns.
+///
+/// Polls are scheduled with the `nextRequestTimestamp` of the responses, bounded by the minimum and maximum interval.
+/// The state is updated when an event is returned, so a persisted [`WatcherState`] never skips events.
+///
+/// # Examples
+///
+/// ```rust
+/// # use nadeo_api::NadeoClient;
+/// # use nadeo_api::campaigns::{CampaignWatcher, WatchEvent, WatcherState};
+/// # use futures::StreamExt;
+/// # async fn run(client: NadeoClient, state: WatcherState) {
+/// let mut events = Box::pin(CampaignWatcher::new(client).with_state(state).into_stream());
+///
+/// while let Some(event) = events.next().await {
+///     match event {
+///         Ok((WatchEvent::NewTotd { day, .. }, state)) => {
+///             println!("new TOTD: {:?}", day.map_uid);
+///             // persist `state`
+///         }
+///         Ok(_) => {}
+///         Err(e) => eprintln!("{e}"),
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct CampaignWatcher {
+    client: NadeoClient,
+    state: WatcherState,
+    sources: Vec<(Source, Instant)>,
+    min_interval: Duration,
+    max_interval: Duration,
+    pending: VecDeque<WatchEvent>,
+}
+
+impl CampaignWatcher {
+    /// Creates a watcher for the Track of the Day, the seasonal campaigns and Weekly Shorts.
+    pub fn new(client: NadeoClient) -> Self {
+        let now = Instant::now();
+
+        Self {
+            client,
+            state: WatcherState::default(),
+            sources: vec![
+                (Source::Totd, now),
+                (Source::Campaigns(CampaignKind::Seasonal), now),
+                (Source::Campaigns(CampaignKind::WeeklyShorts), now),
+            ],
+            min_interval: Duration::from_secs(60),
+            max_interval: Duration::from_secs(60 * 60),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Resumes from a previously persisted state.
+    pub fn with_state(mut self, state: WatcherState) -> Self {
+        self.state = state;
+
+        self
+    }
+
+    pub fn watch_totd(self, watch: bool) -> Self {
+        self.watch(Source::Totd, watch)
+    }
+
+    pub fn watch_campaigns(self, kind: CampaignKind, watch: bool) -> Self {
+        self.watch(Source::Campaigns(kind), watch)
+    }
+
+    /// Minimum time between two polls of a source. Defaults to 1 minute.
+    pub fn min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = interval;
+
+        self
+    }
+
+    /// Maximum time between two polls of a source. Changes which are not announced by the API, e.g. edited playlists,
+    /// are found after at most this time. Defaults to 1 hour.
+    pub fn max_interval(mut self, interval: Duration) -> Self {
+        self.max_interval = interval;
+
+        self
+    }
+
+    fn watch(mut self, source: Source, watch: bool) -> Self {
+        self.sources.retain(|(s, _)| *s != source);
+        if watch {
+            self.sources.push((source, Instant::now()));
+        }
+
+        self
+    }
+
+    /// The state including every returned event.
+    pub fn state(&self) -> &WatcherState {
+        &self.state
+    }
+
+    /// Waits for the next event. Never returns if every source is disabled.
+    ///
+    /// A failed poll is retried after the minimum interval.
+    pub async fn next_event(&mut self) -> Result<WatchEvent> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                self.state.apply(&event);
+                return Ok(event);
+            }
+
+            let Some(index) = (0..self.sources.len()).min_by_key(|i| self.sources[*i].1) else {
+                return std::future::pending().await;
+            };
+            let (source, at) = self.sources[index];
+            tokio::time::sleep_until(at).await;
+
+            let polled = match source {
+                Source::Totd => self.poll_totd().await,
+                Source::Campaigns(kind) => self.poll_campaigns(kind).await,
+            };
+            let next = match polled {
+                Ok(next_request) => (next_request - Utc::now())
+                    .to_std()
+                    .unwrap_or_default()
+                    .clamp(self.min_interval, self.max_interval.max(self.min_interval)),
+                Err(e) => {
+                    self.sources[index].1 = Instant::now() + self.min_interval;
+                    return Err(e);
+                }
+            };
+            self.sources[index].1 = Instant::now() + next;
+        }
+    }
+
+    /// Turns the watcher into an endless stream of events and the state after each event.
+    pub fn into_stream(self) -> impl Stream<Item = Result<(WatchEvent, WatcherState)>> {
+        futures::stream::unfold(self, |mut watcher| async move {
+            let item = watcher
+                .next_event()
+                .await
+                .map(|event| (event, watcher.state.clone()));
+
+            Some((item, watcher))
+        })
+    }
+
+    async fn poll_totd(&mut self) -> Result<DateTime<Utc>> {
+        let now = Utc::now();
+        let length = totd_months_to_request(self.state.latest_totd, now);
+        let months = self.client.totd_months(0, length).await?;
+
+        let events = totd_events(&mut self.state, months.month_list, now);
+        self.pending.extend(events);
+
+        Ok(months.next_request_timestamp)
+    }
+
+    async fn poll_campaigns(&mut self, kind: CampaignKind) -> Result<DateTime<Utc>> {
+        let list = match kind {
+            CampaignKind::Seasonal => {
+                self.client
+                    .official_campaigns(0, CAMPAIGN_PAGE_LENGTH)
+                    .await?
+            }
+            CampaignKind::WeeklyShorts => {
+                self.client
+                    .weekly_shorts_campaigns(0, CAMPAIGN_PAGE_LENGTH)
+                    .await?
+            }
+        };
+
+        let events = campaign_events(&mut self.state, kind, list.campaign_list);
+        self.pending.extend(events);
+
+        Ok(list.next_request_timestamp)
+    }
+}
+
+/// Number of months to request starting with the current one. The previous month is included if the latest known
+/// Track of the Day is from an earlier month, so the last days of the previous month are not missed at the turn of the month.
+fn totd_months_to_request(latest: Option<TotdDate>, now: DateTime<Utc>) -> u32 {
+    match latest {
+        Some(latest) if (latest.year, latest.month) < (now.year(), now.month()) => 2,
+        _ => 1,
+    }
+}
+
+/// Returns the Tracks of the Day published after the latest known one, oldest first.
+/// On the first poll only the latest published day is recorded in the state.
+fn totd_events(
+    state: &mut WatcherState,
+    months: Vec<TotdMonth>,
+    now: DateTime<Utc>,
+) -> Vec<WatchEvent> {
+    let mut published = months
+        .into_iter()
+        .flat_map(|month| {
+            let (year, month_number) = (month.year, month.month);
+            month.days.into_iter().map(move |day| {
+                let date = TotdDate {
+                    year,
+                    month: month_number,
+                    day: day.month_day,
+                };
+                (date, day)
+            })
+        })
+        .filter(|(_, day)| day.map_uid.is_some() && day.start_timestamp <= now)
+        .collect::<Vec<_>>();
+    published.sort_by_key(|(date, _)| *date);
+
+    if !state.totd_polled && state.latest_totd.is_none() {
+        state.totd_polled = true;
+        state.latest_totd = published.last().map(|(date, _)| *date);
+        return Vec::new();
+    }
+
+    published
+        .into_iter()
+        .filter(|(date, _)| state.latest_totd.is_none_or(|latest| *date > latest))
+        .map(|(date, day)| WatchEvent::NewTotd { date, day })
+        .collect()
+}
+
+/// Returns the new and changed campaigns, oldest first. `campaigns` are ordered newest first like in the responses.
+/// On the first poll the campaigns are only recorded in the state.
+fn campaign_events(
+    state: &mut WatcherState,
+    kind: CampaignKind,
+    campaigns: Vec<Campaign>,
+) -> Vec<WatchEvent> {
+    let known = state.campaigns(kind);
+    let Some(known) = known else {
+        *known = Some(
+            campaigns
+                .iter()
+                .map(|campaign| (campaign.id, campaign.map_uids()))
+                .collect(),
+        );
+        return Vec::new();
+    };
+
+    // oldest first, so events are in the order of publication
+    campaigns
+        .into_iter()
+        .rev()
+        .filter_map(|campaign| match known.get(&campaign.id) {
+            None => Some(WatchEvent::NewCampaign { kind, campaign }),
+            Some(previous) if *previous != campaign.map_uids() => {
+                Some(WatchEvent::PlaylistChanged {
+                    kind,
+                    previous: previous.clone(),
+                    campaign,
+                })
+            }
+            Some(_) => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::campaigns::CampaignMap;
+    use crate::types::SeasonId;
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    fn time(month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, month, day, 17, 0, 0).unwrap()
+    }
+
+    fn date(month: u32, day: u32) -> TotdDate {
+        TotdDate {
+            year: 2024,
+            month,
+            day,
+        }
+    }
+
+    fn totd_month(month: u32, days: u32) -> TotdMonth {
+        TotdMonth {
+            year: 2024,
+            month,
+            last_day: days,
+            days: (1..=days)
+                .map(|day| TotdDay {
+                    campaign_id: CampaignId::new(month as u64),
+                    map_uid: Some(format!("map_{month}_{day}").parse().unwrap()),
+                    day: 0,
+                    month_day: day,
+                    season_uid: SeasonId::new(Uuid::nil()),
+                    start_timestamp: time(month, day),
+                    end_timestamp: time(month, day) + chrono::Duration::days(1),
+                })
+                .collect(),
+        }
+    }
+
+    fn campaign(id: u64, maps: &[&str]) -> Campaign {
+        Campaign {
+            id: CampaignId::new(id),
+            season_uid: SeasonId::new(Uuid::nil()),
+            name: format!("Campaign {id}"),
+            start_timestamp: time(1, 1),
+            end_timestamp: time(4, 1),
+            playlist: maps
+                .iter()
+                .enumerate()
+                .map(|(position, uid)| CampaignMap {
+                    position: position as u32,
+                    map_uid: uid.parse().unwrap(),
+                })
+                .collect(),
+        }
+    }
+
+    fn new_totd_dates(events: &[WatchEvent]) -> Vec<TotdDate> {
+        events
+            .iter()
+            .map(|event| match event {
+                WatchEvent::NewTotd { date, .. } => *date,
+                other => panic!("unexpected event {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn apply_records_events() {
+        let mut state = WatcherState::default();
+
+        let day = totd_month(3, 1).days.remove(0);
+        state.apply(&WatchEvent::NewTotd {
+            date: date(3, 1),
+            day,
+        });
+        assert_eq!(state.latest_totd, Some(date(3, 1)));
+
+        state.apply(&WatchEvent::NewCampaign {
+            kind: CampaignKind::Seasonal,
+            campaign: campaign(1, &["a", "b"]),
+        });
+        state.apply(&WatchEvent::PlaylistChanged {
+            kind: CampaignKind::Seasonal,
+            campaign: campaign(1, &["a", "c"]),
+            previous: vec!["a".parse().unwrap(), "b".parse().unwrap()],
+        });
+        let seasonal = state.seasonal.unwrap();
+        assert_eq!(seasonal.len(), 1);
+        assert_eq!(
+            seasonal[&CampaignId::new(1)],
+            vec!["a".parse().unwrap(), "c".parse().unwrap()]
+        );
+        assert_eq!(state.weekly_shorts, None);
+    }
+
+    #[test]
+    fn first_totd_poll_only_records_the_latest_day() {
+        let mut state = WatcherState::default();
+
+        let events = totd_events(&mut state, vec![totd_month(3, 31)], time(3, 10));
+
+        assert!(events.is_empty());
+        assert!(state.totd_polled);
+        assert_eq!(state.latest_totd, Some(date(3, 10)));
+    }
+
+    #[test]
+    fn totds_after_an_empty_first_poll_are_new() {
+        let mut state = WatcherState::default();
+
+        // nothing is published before 17:00 on the first of the month
+        let events = totd_events(
+            &mut state,
+            vec![totd_month(3, 31)],
+            time(3, 1) - chrono::Duration::hours(1),
+        );
+        assert!(events.is_empty());
+        assert!(state.totd_polled);
+        assert_eq!(state.latest_totd, None);
+
+        let events = totd_events(&mut state, vec![totd_month(3, 31)], time(3, 1));
+        assert_eq!(new_totd_dates(&events), vec![date(3, 1)]);
+    }
+
+    #[test]
+    fn returns_new_totds_oldest_first() {
+        let mut state = WatcherState {
+            latest_totd: Some(date(2, 28)),
+            ..Default::default()
+        };
+        let mut month = totd_month(3, 31);
+        month.days[1].map_uid = None;
+
+        // the current month comes first in the response
+        let events = totd_events(
+            &mut state,
+            vec![month, totd_month(2, 29)],
+            time(3, 3) + chrono::Duration::minutes(1),
+        );
+
+        assert_eq!(
+            new_totd_dates(&events),
+            vec![date(2, 29), date(3, 1), date(3, 3)]
+        );
+        // the state is only updated when the events are returned
+        assert_eq!(state.latest_totd, Some(date(2, 28)));
+    }
+
+    #[test]
+    fn requests_the_previous_month_after_the_turn_of_the_month() {
+        assert_eq!(totd_months_to_request(None, time(3, 1)), 1);
+        assert_eq!(totd_months_to_request(Some(date(3, 1)), time(3, 2)), 1);
+        assert_eq!(totd_months_to_request(Some(date(2, 28)), time(3, 1)), 2);
+        let previous_year = TotdDate {
+            year: 2023,
+            month: 12,
+            day: 31,
+        };
+        assert_eq!(totd_months_to_request(Some(previous_year), time(1, 1)), 2);
+    }
+
+    #[test]
+    fn first_campaign_poll_only_records_the_campaigns() {
+        let mut state = WatcherState::default();
+
+        let events = campaign_events(
+            &mut state,
+            CampaignKind::WeeklyShorts,
+            vec![campaign(2, &["c"]), campaign(1, &["a", "b"])],
+        );
+
+        assert!(events.is_empty());
+        assert_eq!(state.seasonal, None);
+        assert_eq!(state.weekly_shorts.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn returns_new_and_changed_campaigns_oldest_first() {
+        let mut state = WatcherState::default();
+        campaign_events(
+            &mut state,
+            CampaignKind::Seasonal,
+            vec![campaign(2, &["c"]), campaign(1, &["a", "b"])],
+        );
+
+        let events = campaign_events(
+            &mut state,
+            CampaignKind::Seasonal,
+            vec![
+                campaign(3, &["d"]),
+                campaign(2, &["c", "e"]),
+                campaign(1, &["a", "b"]),
+            ],
+        );
+
+        assert_eq!(
+            events,
+            vec![
+                WatchEvent::PlaylistChanged {
+                    kind: CampaignKind::Seasonal,
+                    campaign: campaign(2, &["c", "e"]),
+                    previous: vec!["c".parse().unwrap()],
+                },
+                WatchEvent::NewCampaign {
+                    kind: CampaignKind::Seasonal,
+                    campaign: campaign(3, &["d"]),
+                },
+            ]
+        );
+    }
+}
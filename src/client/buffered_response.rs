@@ -0,0 +1,36 @@
+use crate::Result;
+use bytes::Bytes;
+use reqwest::header::HeaderMap;
+use reqwest::{Response, StatusCode};
+
+/// A [`Response`] which was read into memory, so it can be handed out multiple times.
+#[derive(Debug, Clone)]
+pub(crate) struct BufferedResponse {
+    pub(crate) status: StatusCode,
+    pub(crate) headers: HeaderMap,
+    pub(crate) body: Bytes,
+}
+
+impl BufferedResponse {
+    /// Reads the whole body of the response.
+    pub(crate) async fn read(res: Response) -> Result<Self> {
+        let status = res.status();
+        let headers = res.headers().clone();
+        let body = res.bytes().await?;
+
+        Ok(Self {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    /// Converts the buffered response back into a [`Response`].
+    pub(crate) fn to_response(&self) -> Response {
+        let mut res = http::Response::new(self.body.clone());
+        *res.status_mut() = self.status;
+        *res.headers_mut() = self.headers.clone();
+
+        Response::from(res)
+    }
+}
@@ -1,30 +1,82 @@
-use crate::auth::o_auth::OAuthInfo;
-use crate::auth::{AuthInfo, AuthType};
+use crate::auth::circuit_breaker::CircuitBreakerConfig;
+use crate::auth::credentials::{
+    ConfigFileCredentials, CredentialProvider, Credentials, EnvCredentials, OAuthCredentials,
+    ServerCredentials, UbisoftCredentials,
+};
+use crate::cache::{CachePolicy, ClientCache, ResponseCache};
+use crate::client::single_flight::SingleFlight;
 use crate::request::metadata::MetaData;
 use crate::Result;
 use crate::{auth, Error, NadeoClient};
-use futures::future::join3;
+use futures::future::join;
 use reqwest::Client;
+use std::path::Path;
+use std::sync::Arc;
 use thiserror::Error;
 
-type EMail = String;
-type Username = String;
-type Password = String;
-type Identifier = String;
-type Secret = String;
-
 #[derive(Debug, Clone, Default)]
 pub struct NadeoClientBuilder {
-    normal_auth: Option<(EMail, Password)>,
-    server_auth: Option<(Username, Password)>,
-    o_auth: Option<(Identifier, Secret)>,
+    credentials: Credentials,
+    credential_provider: Option<Arc<dyn CredentialProvider>>,
     user_agent: Option<String>,
+    circuit_breaker: CircuitBreakerConfig,
+    coalesce_requests: bool,
+    cache: Option<(Arc<dyn ResponseCache>, CachePolicy)>,
 }
 
 impl NadeoClientBuilder {
+    /// Creates a builder which reads credentials and the UserAgent from environment variables.
+    /// See [`EnvCredentials`] for the names of the variables.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use nadeo_api::NadeoClient;
+    /// # use nadeo_api::client::client_builder::NadeoClientBuilder;
+    /// # async fn run() -> nadeo_api::Result<()> {
+    /// // NADEO_EMAIL=... NADEO_PASSWORD=... NADEO_USER_AGENT=...
+    /// let client = NadeoClientBuilder::from_env().build().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_env() -> Self {
+        Self::default().with_credential_provider(EnvCredentials::default())
+    }
+
+    /// Creates a builder which reads credentials and the UserAgent from a TOML file.
+    /// See [`ConfigFileCredentials`] for the format of the file.
+    pub fn from_config(path: impl AsRef<Path>) -> Self {
+        Self::default().with_credential_provider(ConfigFileCredentials::new(path))
+    }
+
+    /// Adds a [`CredentialProvider`] which is asked for credentials when building the client and whenever a full re-login is required.
+    /// Credentials added with `with_normal_auth`, `with_server_auth` or `with_oauth` take precedence over the ones of the provider.
+    pub fn with_credential_provider<P>(mut self, provider: P) -> Self
+    where
+        P: CredentialProvider + 'static,
+    {
+        self.credential_provider = Some(Arc::new(provider));
+
+        self
+    }
+
     /// Adds credentials for using [`AuthType::NadeoServices`] and [`AuthType::NadeoLiveServices`].
     pub fn with_normal_auth(mut self, email: &str, password: &str) -> Self {
-        self.normal_auth = Some((email.to_string(), password.to_string()));
+        self.credentials.normal_auth = Some(UbisoftCredentials {
+            email: email.to_string(),
+            password: password.to_string(),
+        });
+
+        self
+    }
+
+    /// Adds a Ubisoft remember-me ticket for using [`AuthType::NadeoServices`] and [`AuthType::NadeoLiveServices`].
+    /// The ticket is preferred over [`NadeoClientBuilder::with_normal_auth`], which is only used if the ticket is rejected.
+    /// This way the password does not need to be stored once a ticket was obtained.
+    ///
+    /// The ticket is rotated on every login. The current ticket can be retrieved with [`NadeoClient::remember_me_ticket`].
+    pub fn with_remember_me_ticket(mut self, ticket: &str) -> Self {
+        self.credentials.remember_me_ticket = Some(ticket.to_string());
 
         self
     }
@@ -32,14 +84,20 @@ impl NadeoClientBuilder {
     /// Adds credentials for using [`AuthType::NadeoServices`] and [`AuthType::NadeoLiveServices`] using a server account.
     /// [`NadeoClientBuilder`] will prefer [`NadeoClientBuilder::with_normal_auth`] if `with_normal_auth` and `with_server_auth` are added.
     pub fn with_server_auth(mut self, username: &str, password: &str) -> Self {
-        self.server_auth = Some((username.to_string(), password.to_string()));
+        self.credentials.server_auth = Some(ServerCredentials {
+            username: username.to_string(),
+            password: password.to_string(),
+        });
 
         self
     }
 
     /// Adds credentials for using [`AuthType::OAuth`].
     pub fn with_oauth(mut self, identifier: &str, secret: &str) -> Self {
-        self.o_auth = Some((identifier.to_string(), secret.to_string()));
+        self.credentials.o_auth = Some(OAuthCredentials {
+            identifier: identifier.to_string(),
+            secret: secret.to_string(),
+        });
 
         self
     }
@@ -67,99 +125,95 @@ impl NadeoClientBuilder {
         self
     }
 
+    /// Configures the circuit breaker which stops logging in after repeated failures to protect the accounts from being locked.
+    /// See [`CircuitBreakerConfig`] for the defaults.
+    pub fn login_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = config;
+
+        self
+    }
+
+    /// Enables request coalescing. Identical idempotent requests (same method, URL, body and [`AuthType`]) which are executed
+    /// at the same time by the client or its clones share a single request and response. Disabled by default.
+    pub fn coalesce_requests(mut self, enabled: bool) -> Self {
+        self.coalesce_requests = enabled;
+
+        self
+    }
+
+    /// Adds a response cache. Successful `GET` responses are cached for the TTL defined by the [`CachePolicy`].
+    /// The cache is shared by all clones of the client. See [`crate::cache`].
+    pub fn response_cache<C>(mut self, cache: C, policy: CachePolicy) -> Self
+    where
+        C: ResponseCache + 'static,
+    {
+        self.cache = Some((Arc::new(cache), policy));
+
+        self
+    }
+
+    /// Returns `true` if a UserAgent was added.
+    pub(crate) fn has_user_agent(&self) -> bool {
+        self.user_agent.is_some()
+    }
+
     /// Trys to build a [`NadeoClient`].
     pub async fn build(self) -> Result<NadeoClient> {
-        if self.o_auth.is_none() && self.normal_auth.is_none() && self.server_auth.is_none() {
+        let explicit_credentials = self.credentials;
+        let mut credentials = explicit_credentials.clone();
+        let mut user_agent = self.user_agent;
+        if let Some(ref provider) = self.credential_provider {
+            credentials = credentials.or(provider.credentials().await?);
+            if user_agent.is_none() {
+                user_agent = provider.user_agent().await;
+            }
+        }
+
+        if credentials.is_empty() {
             return Err(Error::from(NadeoClientBuilderError::MissingCredentials));
         }
-        if self.user_agent.is_none() {
+        if user_agent.is_none() {
             return Err(Error::from(NadeoClientBuilderError::MissingUserAgent));
         }
 
         let meta_data = MetaData {
-            user_agent: self.user_agent.unwrap(),
+            user_agent: user_agent.unwrap(),
         };
 
         let client = Client::new();
 
-        // Ubisoft auth ticket
-        let mut ticket = String::new();
-        if let Some(ref auth) = self.normal_auth {
-            ticket = auth::get_ubi_auth_ticket(&auth.0, &auth.1, &meta_data, &client).await?;
-        }
-
-        // NadeoServices
-        let normal_auth_future = async {
-            if self.normal_auth.is_some() {
-                Some(AuthInfo::new(AuthType::NadeoServices, &ticket, &meta_data, &client).await)
-            } else if let Some((ref username, ref password)) = self.server_auth {
-                Some(
-                    AuthInfo::new_server(
-                        AuthType::NadeoServices,
-                        &meta_data,
-                        username,
-                        password,
-                        &client,
-                    )
-                    .await,
-                )
-            } else {
-                None
-            }
-        };
-        // NadeoLiveServices
-        let live_auth_future = async {
-            if self.normal_auth.is_some() {
-                Some(AuthInfo::new(AuthType::NadeoLiveServices, &ticket, &meta_data, &client).await)
-            } else if let Some((ref username, ref password)) = self.server_auth {
-                Some(
-                    AuthInfo::new_server(
-                        AuthType::NadeoLiveServices,
-                        &meta_data,
-                        username,
-                        password,
-                        &client,
-                    )
-                    .await,
-                )
-            } else {
-                None
-            }
-        };
-        // OAuth
-        let oauth_future = async {
-            if let Some(auth) = self.o_auth {
-                Some(OAuthInfo::new(&auth.0, &auth.1, &client).await)
-            } else {
-                None
-            }
-        };
         // execute requests
-        let (normal_auth_res, live_auth_res, oauth_res) =
-            join3(normal_auth_future, live_auth_future, oauth_future).await;
+        let (nadeo_res, oauth_res) = join(
+            auth::nadeo_login(&credentials, &meta_data, &client, &self.circuit_breaker),
+            auth::oauth_login(&credentials, &client),
+        )
+        .await;
 
-        let mut normal_auth = None;
-        let mut live_auth = None;
-        let mut o_auth = None;
+        let o_auth = oauth_res?;
 
-        // extract results
-        if let Some(auth) = normal_auth_res {
-            normal_auth = Some(auth?);
-        }
-        if let Some(auth) = live_auth_res {
-            live_auth = Some(auth?);
-        }
-        if let Some(auth) = oauth_res {
-            o_auth = Some(auth?);
-        }
-
-        Ok(NadeoClient {
+        let mut client = NadeoClient {
             client,
-            normal_auth,
-            live_auth,
+            normal_auth: None,
+            live_auth: None,
             o_auth,
+            ubi_session: None,
             meta_data,
-        })
+            credentials,
+            explicit_credentials,
+            credential_provider: self.credential_provider,
+            circuit_breaker: self.circuit_breaker,
+            single_flight: self
+                .coalesce_requests
+                .then(|| Arc::new(SingleFlight::default())),
+            cache: self
+                .cache
+                .map(|(store, policy)| Arc::new(ClientCache::new(store, policy))),
+        };
+        if let Some(login) = nadeo_res? {
+            client.set_nadeo_login(login).await?;
+        }
+
+        Ok(client)
     }
 }
 
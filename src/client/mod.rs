@@ -1,16 +1,32 @@
+use crate::auth::circuit_breaker::CircuitBreakerConfig;
+use crate::auth::credentials::{CredentialProvider, Credentials, Redacted};
 use crate::auth::o_auth::OAuthInfo;
+use crate::auth::ubi::UbiSession;
 
-use crate::auth::{AuthInfo, AuthType};
-use crate::request::NadeoRequest;
+use crate::auth::{self, AuthInfo, AuthType, NadeoLogin};
+use crate::cache::{CacheKey, CacheStats, CachedResponse, ClientCache};
+use crate::client::buffered_response::BufferedResponse;
+use crate::client::single_flight::{RequestKey, SingleFlight};
+use crate::request::{NadeoRequest, RequestBody};
+use crate::types::AccountId;
 use crate::{Error, Result};
 
-use reqwest::{Client, Response};
+use reqwest::{Client, Method, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
 use crate::client::client_builder::NadeoClientBuilder;
 use crate::request::metadata::MetaData;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
 use thiserror::Error;
 
+pub(crate) mod buffered_response;
 pub mod client_builder;
+pub(crate) mod pagination;
+pub(crate) mod single_flight;
+#[cfg(test)]
+pub(crate) mod test_support;
 
 pub(crate) const NADEO_AUTH_URL: &str =
     "https://prod.trackmania.core.nadeo.online/v2/authentication/token/ubiservices";
@@ -38,13 +54,40 @@ pub(crate) const EXPIRATION_TIME_BUFFER: i64 = 60;
 /// ```
 ///
 /// [`NadeoRequest`]: NadeoRequest
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct NadeoClient {
     pub(crate) client: Client,
     pub(crate) normal_auth: Option<AuthInfo>,
     pub(crate) live_auth: Option<AuthInfo>,
     pub(crate) o_auth: Option<OAuthInfo>,
+    pub(crate) ubi_session: Option<UbiSession>,
     pub(crate) meta_data: MetaData,
+    /// The credentials of the last login.
+    pub(crate) credentials: Credentials,
+    /// Credentials added on the builder. They take precedence over the ones of the credential provider.
+    pub(crate) explicit_credentials: Credentials,
+    pub(crate) credential_provider: Option<Arc<dyn CredentialProvider>>,
+    pub(crate) circuit_breaker: CircuitBreakerConfig,
+    /// Shared by all clones of the client. `None` if request coalescing is disabled.
+    pub(crate) single_flight: Option<Arc<SingleFlight>>,
+    /// Shared by all clones of the client. `None` if the client has no response cache.
+    pub(crate) cache: Option<Arc<ClientCache>>,
+}
+
+/// Only shows which tokens are present, the tokens and credentials are secrets.
+impl Debug for NadeoClient {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NadeoClient")
+            .field("normal_auth", &self.normal_auth.as_ref().map(|_| Redacted))
+            .field("live_auth", &self.live_auth.as_ref().map(|_| Redacted))
+            .field("o_auth", &self.o_auth.as_ref().map(|_| Redacted))
+            .field("ubi_session", &self.ubi_session.as_ref().map(|_| Redacted))
+            .field("meta_data", &self.meta_data)
+            .field("credentials", &self.credentials)
+            .field("explicit_credentials", &self.explicit_credentials)
+            .field("circuit_breaker", &self.circuit_breaker)
+            .finish_non_exhaustive()
+    }
 }
 
 impl NadeoClient {
@@ -88,28 +131,294 @@ impl NadeoClient {
     /// [`NadeoRequest`]: NadeoRequest
     /// [`NadeoClient`]: NadeoClient
     pub async fn execute(&mut self, request: NadeoRequest) -> Result<Response> {
-        match request.auth_type {
-            AuthType::NadeoServices => {
-                if let Some(auth) = &mut self.normal_auth {
-                    auth.execute(request, &self.meta_data, &self.client).await
-                } else {
-                    Err(Error::from(ClientError::MissingNadeoAuth))
-                }
+        let Some(cache) = self.cache.clone() else {
+            return self.execute_uncached(request).await;
+        };
+        if request.bypass_cache {
+            cache.record_bypass();
+            return self.execute_uncached(request).await;
+        }
+        let multipart = matches!(request.body, Some(RequestBody::Multipart(_)));
+        let Some(ttl) = cache
+            .policy
+            .ttl(&request.method, &request.url)
+            .filter(|_| !multipart)
+        else {
+            return self.execute_uncached(request).await;
+        };
+
+        let key = CacheKey::of(&request);
+        if let Some(res) = cache.store.get(&key).await {
+            cache.record_hit();
+            return Ok(BufferedResponse::from(res).to_response());
+        }
+        cache.record_miss();
+
+        let res = BufferedResponse::read(self.execute_uncached(request).await?).await?;
+        cache.store.put(key, CachedResponse::from(&res), ttl).await;
+
+        Ok(res.to_response())
+    }
+
+    /// Returns the hit and miss statistics of the response cache. Returns `None` if the client has no cache.
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(|cache| cache.stats())
+    }
+
+    /// Executes a [`NadeoRequest`] without using the response cache.
+    async fn execute_uncached(&mut self, request: NadeoRequest) -> Result<Response> {
+        if let Some(single_flight) = self
+            .single_flight
+            .clone()
+            .filter(|_| !request.bypass_coalescing)
+        {
+            if let Some(key) = RequestKey::of(&request) {
+                return single_flight
+                    .execute(key, self.execute_uncoalesced(request))
+                    .await;
             }
-            AuthType::NadeoLiveServices => {
-                if let Some(auth) = &mut self.live_auth {
-                    auth.execute(request, &self.meta_data, &self.client).await
+        }
+
+        self.execute_uncoalesced(request).await
+    }
+
+    /// Executes a [`NadeoRequest`] without sharing its response with identical requests.
+    async fn execute_uncoalesced(&mut self, request: NadeoRequest) -> Result<Response> {
+        match request.auth_type {
+            AuthType::NadeoServices | AuthType::NadeoLiveServices => {
+                self.ensure_nadeo_auth(request.auth_type).await?;
+
+                let auth = if request.auth_type == AuthType::NadeoServices {
+                    self.normal_auth.as_mut()
                 } else {
-                    Err(Error::from(ClientError::MissingNadeoAuth))
+                    self.live_auth.as_mut()
+                };
+                match auth {
+                    Some(auth) => auth.execute(request, &self.meta_data, &self.client).await,
+                    None => Err(Error::from(ClientError::MissingNadeoAuth)),
                 }
             }
             AuthType::OAuth => {
+                self.ensure_oauth().await?;
+
                 if let Some(auth) = &mut self.o_auth {
                     auth.execute(request, &self.meta_data, &self.client).await
                 } else {
                     Err(Error::from(ClientError::MissingOAuth))
                 }
             }
+            AuthType::UbiServices => {
+                self.ensure_ubi_session().await?;
+
+                if let Some(session) = &mut self.ubi_session {
+                    session
+                        .execute(request, &self.meta_data, &self.client)
+                        .await
+                } else {
+                    Err(Error::from(ClientError::MissingUbiAuth))
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if the client has credentials for the given [`AuthType`].
+    pub fn has_auth(&self, auth_type: AuthType) -> bool {
+        match auth_type {
+            AuthType::NadeoServices => self.normal_auth.is_some(),
+            AuthType::NadeoLiveServices => self.live_auth.is_some(),
+            AuthType::OAuth => self.o_auth.is_some(),
+            AuthType::UbiServices => self.ubi_session.is_some(),
+        }
+    }
+
+    /// Returns the *accountID* of the Ubisoft or server account used for [`AuthType::NadeoServices`] and [`AuthType::NadeoLiveServices`].
+    pub fn account_id(&self) -> Option<AccountId> {
+        self.normal_auth
+            .as_ref()
+            .or(self.live_auth.as_ref())
+            .and_then(|auth| auth.access_token.account_id().ok())
+    }
+
+    /// Sends `body` as JSON in a POST request and deserializes the response. An empty response is deserialized from `null`.
+    pub(crate) async fn post_json<T: DeserializeOwned>(
+        &mut self,
+        url: &str,
+        auth_type: AuthType,
+        body: &impl Serialize,
+    ) -> Result<T> {
+        let body = serde_json::to_string(body)
+            .map_err(|e| Error::from(ClientError::InvalidRequestBody(e.to_string())))?;
+        let request = NadeoRequest::builder()
+            .url(url)
+            .auth_type(auth_type)
+            .method(Method::POST)
+            .add_header("Content-Type", "application/json")
+            .body(&body)
+            .build()?;
+
+        let res = self.execute(request).await?;
+        let bytes = res.bytes().await?;
+        // some endpoints respond without a body
+        let json = match bytes.is_empty() {
+            true => Ok(serde_json::Value::Null),
+            false => serde_json::from_slice(&bytes),
+        };
+
+        json.and_then(serde_json::from_value)
+            .map_err(|e| Error::from(ClientError::InvalidResponse(e.to_string())))
+    }
+
+    /// Refreshes the tokens of the given [`AuthType`] if required. If the refresh fails a full re-login is attempted.
+    ///
+    /// Clones of the client don't share their tokens, so this is called before cloning the client for concurrent requests.
+    /// Otherwise every clone refreshes the tokens on its own and the refreshed tokens are lost with the clone.
+    pub(crate) async fn ensure_auth(&mut self, auth_type: AuthType) -> Result<()> {
+        match auth_type {
+            AuthType::NadeoServices | AuthType::NadeoLiveServices => {
+                self.ensure_nadeo_auth(auth_type).await
+            }
+            AuthType::OAuth => self.ensure_oauth().await,
+            AuthType::UbiServices => self.ensure_ubi_session().await,
+        }
+    }
+
+    /// Returns the credentials used for a full re-login. If a [`CredentialProvider`] was added, fresh credentials are fetched from it
+    /// and merged with the credentials added on the builder.
+    async fn current_credentials(&mut self) -> Result<Credentials> {
+        if let Some(ref provider) = self.credential_provider {
+            // the ticket of the client is rotated on every login, so it is newer than the one of the provider
+            let current_ticket = Credentials {
+                remember_me_ticket: self.credentials.remember_me_ticket.clone(),
+                ..Default::default()
+            };
+            let credentials = current_ticket
+                .or(self.explicit_credentials.clone())
+                .or(provider.credentials().await?);
+            if !credentials.is_empty() {
+                self.credentials = credentials;
+            }
+        }
+
+        Ok(self.credentials.clone())
+    }
+
+    /// Refreshes the Nadeo tokens if required. If the refresh fails a full re-login is attempted.
+    async fn ensure_nadeo_auth(&mut self, auth_type: AuthType) -> Result<()> {
+        let auth = match auth_type {
+            AuthType::NadeoServices => self.normal_auth.as_mut(),
+            _ => self.live_auth.as_mut(),
+        };
+        let Some(auth) = auth else {
+            return Err(Error::from(ClientError::MissingNadeoAuth));
+        };
+        if auth.refresh(&self.meta_data, &self.client).await.is_ok() {
+            return Ok(());
+        }
+
+        let credentials = self.current_credentials().await?;
+        let login = auth::nadeo_login(
+            &credentials,
+            &self.meta_data,
+            &self.client,
+            &self.circuit_breaker,
+        )
+        .await?
+        .ok_or(ClientError::MissingNadeoAuth)?;
+
+        self.set_nadeo_login(login).await
+    }
+
+    /// Stores the tokens of a login and persists the rotated remember-me ticket.
+    pub(crate) async fn set_nadeo_login(&mut self, login: NadeoLogin) -> Result<()> {
+        self.normal_auth = Some(login.normal_auth);
+        self.live_auth = Some(login.live_auth);
+
+        match login.ubi_session {
+            Some(session) => self.set_ubi_session(session).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Stores a Ubisoft session and persists its rotated remember-me ticket.
+    async fn set_ubi_session(&mut self, session: UbiSession) -> Result<()> {
+        if let Some(ref ticket) = session.remember_me_ticket {
+            if let Some(ref provider) = self.credential_provider {
+                provider.persist_remember_me_ticket(ticket).await?;
+            }
+            self.credentials.remember_me_ticket = Some(ticket.clone());
+        }
+        self.ubi_session = Some(session);
+
+        Ok(())
+    }
+
+    /// Renews the Ubisoft session before the ticket expires. If the renewal fails a new session is created.
+    async fn ensure_ubi_session(&mut self) -> Result<()> {
+        let Some(session) = &mut self.ubi_session else {
+            return Err(Error::from(ClientError::MissingUbiAuth));
+        };
+        if session.refresh(&self.meta_data, &self.client).await.is_ok() {
+            return Ok(());
+        }
+
+        let credentials = self.current_credentials().await?;
+        let session = UbiSession::login(
+            &credentials,
+            &self.meta_data,
+            &self.client,
+            &self.circuit_breaker,
+        )
+        .await?
+        .ok_or(ClientError::MissingUbiAuth)?;
+
+        self.set_ubi_session(session).await
+    }
+
+    /// Returns the current Ubisoft remember-me ticket. The ticket is rotated on every login with a Ubisoft account.
+    /// It can be passed to [`NadeoClientBuilder::with_remember_me_ticket`] to log in without a password.
+    pub fn remember_me_ticket(&self) -> Option<&str> {
+        self.credentials.remember_me_ticket.as_deref()
+    }
+
+    /// Refreshes the OAuth token if required. If the refresh fails the credentials are fetched again and another login is attempted.
+    async fn ensure_oauth(&mut self) -> Result<()> {
+        let Some(auth) = &mut self.o_auth else {
+            return Err(Error::from(ClientError::MissingOAuth));
+        };
+        if auth.refresh(&self.client).await.is_ok() {
+            return Ok(());
+        }
+
+        let credentials = self.current_credentials().await?;
+        self.o_auth = Some(
+            auth::oauth_login(&credentials, &self.client)
+                .await?
+                .ok_or(ClientError::MissingOAuth)?,
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl NadeoClient {
+    /// A client with an [`AuthType::OAuth`] token for requests to local servers in tests.
+    pub(crate) fn for_tests() -> Self {
+        Self {
+            client: Client::new(),
+            normal_auth: None,
+            live_auth: None,
+            o_auth: Some(OAuthInfo::for_tests("access-token")),
+            ubi_session: None,
+            meta_data: MetaData {
+                user_agent: "nadeo-api tests".to_string(),
+            },
+            credentials: Credentials::default(),
+            explicit_credentials: Credentials::default(),
+            credential_provider: None,
+            circuit_breaker: CircuitBreakerConfig::default(),
+            single_flight: None,
+            cache: None,
         }
     }
 }
@@ -120,4 +429,55 @@ pub enum ClientError {
     MissingNadeoAuth,
     #[error("Client does not have OAuth credentials")]
     MissingOAuth,
+    #[error("Client does not have a Ubisoft account for UbiServices")]
+    MissingUbiAuth,
+    /// Returned to callers whose request was coalesced with an identical request which failed.
+    /// The original error can't be cloned, so only its status code, if there was one, and its message are passed on.
+    /// Failed logins are passed on as the original [`AuthError`].
+    ///
+    /// [`AuthError`]: crate::auth::circuit_breaker::AuthError
+    #[error("An identical request executed at the same time failed: {message}")]
+    CoalescedRequestFailed {
+        status: Option<StatusCode>,
+        message: String,
+    },
+    #[error("the request body could not be serialized: {0}")]
+    InvalidRequestBody(String),
+    #[error("the response has an unexpected format: {0}")]
+    InvalidResponse(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::credentials::{OAuthCredentials, UbisoftCredentials};
+
+    #[test]
+    fn debug_output_redacts_secrets() {
+        let mut client = NadeoClient::for_tests();
+        client.o_auth = Some(OAuthInfo::for_tests("token-secret"));
+        client.credentials = Credentials {
+            normal_auth: Some(UbisoftCredentials {
+                email: "mail@example.com".to_string(),
+                password: "ubi-password".to_string(),
+            }),
+            o_auth: Some(OAuthCredentials {
+                identifier: "app".to_string(),
+                secret: "oauth-secret".to_string(),
+            }),
+            remember_me_ticket: Some("ticket-secret".to_string()),
+            ..Default::default()
+        };
+
+        let debug = format!("{client:?}");
+        for secret in [
+            "token-secret",
+            "ubi-password",
+            "oauth-secret",
+            "ticket-secret",
+        ] {
+            assert!(!debug.contains(secret), "{debug}");
+        }
+        assert!(debug.contains("mail@example.com"));
+    }
 }
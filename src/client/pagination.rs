@@ -0,0 +1,116 @@
+use crate::{Error, NadeoClient, NadeoRequest, Result};
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use serde::de::DeserializeOwned;
+
+/// Streams the items of an endpoint which is paginated with `offset` and `length`.
+///
+/// `request` builds the request for a page from its offset and length and `items` extracts the items of a response.
+/// Pages are requested lazily until a page has less than `page_size` items.
+///
+/// # Panics
+///
+/// Panics if `page_size` is `0`.
+pub(crate) fn paginate<'a, P, T>(
+    client: &'a mut NadeoClient,
+    page_size: u32,
+    request: impl Fn(u32, u32) -> Result<NadeoRequest> + Send + 'a,
+    items: fn(P) -> Vec<T>,
+) -> BoxStream<'a, Result<T>>
+where
+    P: DeserializeOwned + Send + 'a,
+    T: Send + 'a,
+{
+    assert!(page_size > 0, "page_size must not be 0");
+
+    stream::try_unfold(
+        (client, request, Some(0)),
+        move |(client, request, offset)| async move {
+            let Some(offset) = offset else {
+                return Ok::<_, Error>(None);
+            };
+
+            let res = client.execute(request(offset, page_size)?).await?;
+            let page = items(res.json::<P>().await?);
+            let next = match page.len() < page_size as usize {
+                true => None,
+                false => Some(offset + page_size),
+            };
+
+            Ok(Some((page, (client, request, next))))
+        },
+    )
+    .map_ok(|page| stream::iter(page.into_iter().map(Ok)))
+    .try_flatten()
+    .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthType;
+    use crate::client::test_support::{response, TestServer};
+    use reqwest::Method;
+
+    /// Serves the numbers `0..total` paginated with `offset` and `length`.
+    async fn server(total: u32) -> TestServer {
+        TestServer::start(move |req| {
+            let query = |name: &str| -> u32 {
+                req.head
+                    .split(&format!("{name}="))
+                    .nth(1)
+                    .and_then(|rest| rest.split(['&', ' ']).next())
+                    .and_then(|val| val.parse().ok())
+                    .unwrap()
+            };
+            let (offset, length) = (query("offset"), query("length"));
+            let page = (offset..total.min(offset + length)).collect::<Vec<_>>();
+            response(200, "application/json", &serde_json::to_vec(&page).unwrap())
+        })
+        .await
+    }
+
+    async fn collect(server: &TestServer, page_size: u32) -> Vec<u32> {
+        let mut client = NadeoClient::for_tests();
+        let url = server.url.clone();
+        paginate(
+            &mut client,
+            page_size,
+            move |offset, length| {
+                NadeoRequest::builder()
+                    .url(&format!("{url}/?offset={offset}&length={length}"))
+                    .method(Method::GET)
+                    .auth_type(AuthType::OAuth)
+                    .build()
+            },
+            |page: Vec<u32>| page,
+        )
+        .try_collect()
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn stops_after_a_short_page() {
+        let server = server(5).await;
+
+        assert_eq!(collect(&server, 2).await, vec![0, 1, 2, 3, 4]);
+        assert_eq!(server.request_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn stops_after_an_empty_page() {
+        // the last full page can't be told apart from a page followed by more items
+        let server = server(4).await;
+
+        assert_eq!(collect(&server, 2).await, vec![0, 1, 2, 3]);
+        assert_eq!(server.request_count(), 3);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "page_size must not be 0")]
+    async fn rejects_empty_pages() {
+        let server = server(1).await;
+
+        collect(&server, 0).await;
+    }
+}
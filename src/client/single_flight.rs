@@ -0,0 +1,266 @@
+use crate::auth::circuit_breaker::AuthError;
+use crate::auth::AuthType;
+use crate::client::buffered_response::BufferedResponse;
+use crate::client::ClientError;
+use crate::request::NadeoRequest;
+use crate::{Error, Result};
+use futures::channel::oneshot;
+use reqwest::{Method, Response, StatusCode};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+/// Identifies identical requests.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub(crate) struct RequestKey {
+    method: Method,
+    url: String,
+    body: Option<String>,
+    auth_type: AuthType,
+}
+
+impl RequestKey {
+    /// Returns the key of the request or `None` if the request is not idempotent or has a multipart body.
+    pub(crate) fn of(request: &NadeoRequest) -> Option<Self> {
+        if request.method != Method::GET && request.method != Method::HEAD {
+            return None;
+        }
+        let body = match request.body {
+            Some(ref body) => Some(body.as_text()?.to_string()),
+            None => None,
+        };
+
+        Some(Self {
+            method: request.method.clone(),
+            url: request.url.clone(),
+            body,
+            auth_type: request.auth_type,
+        })
+    }
+}
+
+/// The error of a failed request, passed on to the callers waiting for it.
+#[derive(Debug, Clone)]
+enum SharedError {
+    /// Logging in failed. Callers get the same [`AuthError`].
+    Auth(AuthError),
+    Request {
+        status: Option<StatusCode>,
+        message: String,
+    },
+}
+
+impl SharedError {
+    fn of(err: &Error) -> Self {
+        match err {
+            Error::Auth(e) => Self::Auth(e.clone()),
+            Error::NadeoApi(e) => Self::Request {
+                status: e.status(),
+                message: e.to_string(),
+            },
+            Error::Client(ClientError::CoalescedRequestFailed { status, message }) => {
+                Self::Request {
+                    status: *status,
+                    message: message.clone(),
+                }
+            }
+            e => Self::Request {
+                status: None,
+                message: e.to_string(),
+            },
+        }
+    }
+}
+
+impl From<SharedError> for Error {
+    fn from(err: SharedError) -> Self {
+        match err {
+            SharedError::Auth(e) => Error::from(e),
+            SharedError::Request { status, message } => {
+                Error::from(ClientError::CoalescedRequestFailed { status, message })
+            }
+        }
+    }
+}
+
+type Waiter = oneshot::Sender<std::result::Result<BufferedResponse, SharedError>>;
+
+/// Shares the response of an in-flight request with all callers making the same request at the same time.
+#[derive(Debug, Default)]
+pub(crate) struct SingleFlight {
+    in_flight: Mutex<HashMap<RequestKey, Vec<Waiter>>>,
+}
+
+/// Removes the in-flight entry if the leading request is dropped before it completes.
+/// The waiters are dropped as well and execute the request themselves.
+struct LeaderGuard<'a> {
+    single_flight: &'a SingleFlight,
+    key: Option<RequestKey>,
+}
+
+impl Drop for LeaderGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.single_flight.in_flight.lock().unwrap().remove(&key);
+        }
+    }
+}
+
+impl SingleFlight {
+    /// Executes `request` unless an identical request is already in flight, in which case its response is awaited instead.
+    pub(crate) async fn execute<F>(
+        self: &Arc<Self>,
+        key: RequestKey,
+        request: F,
+    ) -> Result<Response>
+    where
+        F: Future<Output = Result<Response>>,
+    {
+        let receiver = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get_mut(&key) {
+                Some(waiters) => {
+                    let (sender, receiver) = oneshot::channel();
+                    waiters.push(sender);
+                    Some(receiver)
+                }
+                None => {
+                    in_flight.insert(key.clone(), Vec::new());
+                    None
+                }
+            }
+        };
+
+        if let Some(receiver) = receiver {
+            return match receiver.await {
+                Ok(Ok(res)) => Ok(res.to_response()),
+                Ok(Err(e)) => Err(Error::from(e)),
+                // the leading request was dropped
+                Err(_) => request.await,
+            };
+        }
+
+        let mut guard = LeaderGuard {
+            single_flight: self,
+            key: Some(key),
+        };
+        let res = match request.await {
+            Ok(res) => BufferedResponse::read(res).await,
+            Err(e) => Err(e),
+        };
+
+        let key = guard.key.take().unwrap();
+        let waiters = self
+            .in_flight
+            .lock()
+            .unwrap()
+            .remove(&key)
+            .unwrap_or_default();
+        let shared = res.as_ref().cloned().map_err(SharedError::of);
+        for waiter in waiters {
+            let _ = waiter.send(shared.clone());
+        }
+
+        res.map(|res| res.to_response())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_support::{response, TestServer};
+    use crate::NadeoClient;
+    use std::time::Duration;
+
+    fn key() -> RequestKey {
+        RequestKey {
+            method: Method::GET,
+            url: "https://example.com".to_string(),
+            body: None,
+            auth_type: AuthType::NadeoServices,
+        }
+    }
+
+    #[tokio::test]
+    async fn waiters_get_the_auth_error_of_the_leader() {
+        let single_flight = Arc::new(SingleFlight::default());
+        let (tx, rx) = oneshot::channel::<()>();
+
+        let leader = single_flight.execute(key(), async {
+            rx.await.unwrap();
+            Err(Error::from(AuthError::InvalidCredentials))
+        });
+        let waiter =
+            single_flight.execute(key(), async { unreachable!("the request is coalesced") });
+        let release = async {
+            tokio::task::yield_now().await;
+            tx.send(()).unwrap();
+        };
+        let (leader, waiter, _) = tokio::join!(leader, waiter, release);
+
+        assert!(matches!(
+            leader,
+            Err(Error::Auth(AuthError::InvalidCredentials))
+        ));
+        assert!(matches!(
+            waiter,
+            Err(Error::Auth(AuthError::InvalidCredentials))
+        ));
+    }
+
+    #[tokio::test]
+    async fn identical_requests_share_one_response() {
+        let server = TestServer::with_delay(Duration::from_millis(100), |_| {
+            response(200, "text/plain", b"shared")
+        })
+        .await;
+        let mut client = NadeoClient::for_tests();
+        client.single_flight = Some(Arc::new(SingleFlight::default()));
+        let request = NadeoRequest::builder()
+            .url(&server.url)
+            .method(Method::GET)
+            .auth_type(AuthType::OAuth)
+            .build()
+            .unwrap();
+
+        let (mut first, mut second) = (client.clone(), client.clone());
+        let (first, second) = tokio::join!(
+            async {
+                first
+                    .execute(request.clone())
+                    .await?
+                    .text()
+                    .await
+                    .map_err(Error::from)
+            },
+            async {
+                second
+                    .execute(request.clone())
+                    .await?
+                    .text()
+                    .await
+                    .map_err(Error::from)
+            },
+        );
+
+        assert_eq!(first.unwrap(), "shared");
+        assert_eq!(second.unwrap(), "shared");
+        assert_eq!(server.request_count(), 1);
+    }
+
+    #[test]
+    fn shared_errors_keep_the_status() {
+        let err = Error::from(ClientError::CoalescedRequestFailed {
+            status: Some(StatusCode::UNAUTHORIZED),
+            message: "401 Unauthorized".to_string(),
+        });
+
+        assert!(matches!(
+            Error::from(SharedError::of(&err)),
+            Error::Client(ClientError::CoalescedRequestFailed {
+                status: Some(StatusCode::UNAUTHORIZED),
+                ..
+            })
+        ));
+    }
+}
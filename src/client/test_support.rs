@@ -0,0 +1,180 @@
+//! A minimal HTTP server and temporary paths used by the tests.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// A request received by a [`TestServer`].
+#[derive(Debug, Clone)]
+pub(crate) struct ReceivedRequest {
+    /// Request line and headers.
+    pub(crate) head: String,
+    pub(crate) body: Vec<u8>,
+}
+
+impl ReceivedRequest {
+    pub(crate) fn body_text(&self) -> String {
+        String::from_utf8_lossy(&self.body).to_string()
+    }
+}
+
+/// Answers every request with the raw response returned by the handler and closes the connection.
+pub(crate) struct TestServer {
+    pub(crate) url: String,
+    requests: mpsc::UnboundedReceiver<ReceivedRequest>,
+    count: Arc<AtomicUsize>,
+    task: JoinHandle<()>,
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl TestServer {
+    pub(crate) async fn start<F>(respond: F) -> Self
+    where
+        F: Fn(&ReceivedRequest) -> Vec<u8> + Send + Sync + 'static,
+    {
+        Self::with_delay(Duration::ZERO, respond).await
+    }
+
+    /// Waits for `delay` before every response.
+    pub(crate) async fn with_delay<F>(delay: Duration, respond: F) -> Self
+    where
+        F: Fn(&ReceivedRequest) -> Vec<u8> + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, requests) = mpsc::unbounded_channel();
+        let respond = Arc::new(respond);
+        let count = Arc::new(AtomicUsize::new(0));
+
+        let task = tokio::spawn({
+            let count = Arc::clone(&count);
+            async move {
+                while let Ok((mut stream, _)) = listener.accept().await {
+                    let (sender, count, respond) =
+                        (sender.clone(), Arc::clone(&count), Arc::clone(&respond));
+                    tokio::spawn(async move {
+                        let Some(request) = read_request(&mut stream).await else {
+                            return;
+                        };
+                        count.fetch_add(1, Ordering::SeqCst);
+                        let response = respond(&request);
+                        let _ = sender.send(request);
+                        tokio::time::sleep(delay).await;
+                        let _ = stream.write_all(&response).await;
+                        let _ = stream.shutdown().await;
+                    });
+                }
+            }
+        });
+
+        Self {
+            url,
+            requests,
+            count,
+            task,
+        }
+    }
+
+    /// Number of requests received so far.
+    pub(crate) fn request_count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    pub(crate) async fn next_request(&mut self) -> ReceivedRequest {
+        self.requests.recv().await.expect("the server is running")
+    }
+}
+
+async fn read_request(stream: &mut TcpStream) -> Option<ReceivedRequest> {
+    let mut data = Vec::new();
+    let mut buf = [0; 4096];
+    let head_end = loop {
+        if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        let n = stream.read(&mut buf).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        data.extend_from_slice(&buf[..n]);
+    };
+    let head = String::from_utf8_lossy(&data[..head_end]).to_string();
+    let header = |name: &str| {
+        head.lines().find_map(|line| {
+            let (key, val) = line.split_once(':')?;
+            key.eq_ignore_ascii_case(name)
+                .then(|| val.trim().to_string())
+        })
+    };
+
+    let mut body = data.split_off(head_end);
+    if let Some(len) = header("content-length").and_then(|len| len.parse::<usize>().ok()) {
+        while body.len() < len {
+            let n = stream.read(&mut buf).await.ok()?;
+            if n == 0 {
+                break;
+            }
+            body.extend_from_slice(&buf[..n]);
+        }
+    } else if header("transfer-encoding").is_some_and(|val| val.eq_ignore_ascii_case("chunked")) {
+        while !body.ends_with(b"0\r\n\r\n") {
+            let n = stream.read(&mut buf).await.ok()?;
+            if n == 0 {
+                break;
+            }
+            body.extend_from_slice(&buf[..n]);
+        }
+        body = dechunk(&body);
+    }
+
+    Some(ReceivedRequest { head, body })
+}
+
+fn dechunk(mut data: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    while let Some(pos) = data.windows(2).position(|w| w == b"\r\n") {
+        let len = usize::from_str_radix(&String::from_utf8_lossy(&data[..pos]), 16).unwrap_or(0);
+        if len == 0 {
+            break;
+        }
+        body.extend_from_slice(&data[pos + 2..pos + 2 + len]);
+        data = &data[pos + 2 + len + 2..];
+    }
+
+    body
+}
+
+/// Builds a response with a `Content-Length` header.
+pub(crate) fn response(status: u16, content_type: &str, body: &[u8]) -> Vec<u8> {
+    let mut response = format!(
+        "HTTP/1.1 {status} Status\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )
+    .into_bytes();
+    response.extend_from_slice(body);
+
+    response
+}
+
+/// Builds a response without a `Content-Length` header, so the body ends when the connection is closed.
+pub(crate) fn streamed_response(body: &[u8]) -> Vec<u8> {
+    let mut response = b"HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n".to_vec();
+    response.extend_from_slice(body);
+
+    response
+}
+
+/// A path in the temporary directory which is unique for the test process.
+pub(crate) fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("nadeo-api-{}-{name}", std::process::id()))
+}
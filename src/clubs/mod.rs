@@ -0,0 +1,511 @@
+use crate::auth::AuthType;
+use crate::text;
+use crate::types::{AccountId, ActivityId, CampaignId, ClubId, MapUid, RoomId};
+use crate::{Error, NadeoClient, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashSet;
+use thiserror::Error;
+
+pub mod room;
+
+pub use room::{ClubRoom, RoomConfig, ScriptSetting, SettingValue};
+
+const CLUB_URL: &str = "https://live-services.trackmania.nadeo.live/api/token/club";
+
+/// Maximum length of the name of an activity including formatting codes.
+pub const MAX_NAME_LENGTH: usize = 64;
+/// Maximum number of maps in a club campaign.
+pub const MAX_CAMPAIGN_MAPS: usize = 25;
+
+/// Types of club activities.
+#[derive(strum::Display, Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum ActivityType {
+    Campaign,
+    Room,
+    News,
+    Ranking,
+    SkinUpload,
+    ItemUpload,
+    MapUpload,
+    Folder,
+    /// An activity type which is not known to this crate.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Roles of club members.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+pub enum ClubRole {
+    Admin,
+    #[serde(rename = "Content_Creator")]
+    ContentCreator,
+    Member,
+}
+
+/// An activity of a club.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClubActivity {
+    pub id: ActivityId,
+    pub name: String,
+    pub activity_type: ActivityType,
+    /// ID of the campaign or room of the activity.
+    #[serde(default)]
+    pub external_id: u64,
+    #[serde(default)]
+    pub position: u32,
+    #[serde(default)]
+    pub active: bool,
+    #[serde(default)]
+    pub public: bool,
+}
+
+impl ClubActivity {
+    /// Returns the ID of the campaign if the activity is a campaign.
+    pub fn campaign_id(&self) -> Option<CampaignId> {
+        (self.activity_type == ActivityType::Campaign).then(|| CampaignId::new(self.external_id))
+    }
+
+    /// Returns the ID of the room if the activity is a room.
+    pub fn room_id(&self) -> Option<RoomId> {
+        (self.activity_type == ActivityType::Room).then(|| RoomId::new(self.external_id))
+    }
+}
+
+/// Changes to an activity. Fields which are not set are left unchanged.
+#[derive(Debug, Clone, Default, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityEdit {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    active: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    public: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    featured: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    position: Option<u32>,
+}
+
+impl ActivityEdit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+
+        self
+    }
+
+    pub fn active(mut self, active: bool) -> Self {
+        self.active = Some(active);
+
+        self
+    }
+
+    pub fn public(mut self, public: bool) -> Self {
+        self.public = Some(public);
+
+        self
+    }
+
+    pub fn featured(mut self, featured: bool) -> Self {
+        self.featured = Some(featured);
+
+        self
+    }
+
+    /// Moves the activity to the position. Positions start at `0`.
+    pub fn position(mut self, position: u32) -> Self {
+        self.position = Some(position);
+
+        self
+    }
+
+    /// Checks the changes. Called before the changes are sent.
+    pub fn validate(&self) -> std::result::Result<(), ClubError> {
+        if let Some(ref name) = self.name {
+            validate_name(name)?;
+        }
+        if *self == Self::default() {
+            return Err(ClubError::NoChanges);
+        }
+
+        Ok(())
+    }
+}
+
+/// Configuration of a club campaign. Used for creating and editing campaigns.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CampaignConfig {
+    pub name: String,
+    /// The maps of the campaign in order.
+    pub playlist: Vec<MapUid>,
+}
+
+impl CampaignConfig {
+    pub fn new(name: &str, playlist: Vec<MapUid>) -> Self {
+        Self {
+            name: name.to_string(),
+            playlist,
+        }
+    }
+
+    /// Checks the configuration. Called before the campaign is sent.
+    pub fn validate(&self) -> std::result::Result<(), ClubError> {
+        validate_name(&self.name)?;
+        if self.playlist.is_empty() {
+            return Err(ClubError::NoMaps);
+        }
+        if self.playlist.len() > MAX_CAMPAIGN_MAPS {
+            return Err(ClubError::TooManyMaps {
+                max: MAX_CAMPAIGN_MAPS,
+            });
+        }
+        let mut seen = HashSet::new();
+        if let Some(map_uid) = self.playlist.iter().find(|map_uid| !seen.insert(*map_uid)) {
+            return Err(ClubError::DuplicateMap(map_uid.clone()));
+        }
+
+        Ok(())
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let playlist = self
+            .playlist
+            .iter()
+            .enumerate()
+            .map(|(position, map_uid)| json!({ "position": position, "mapUid": map_uid }))
+            .collect::<Vec<_>>();
+
+        json!(
+            {
+                "name": self.name,
+                "playlist": playlist
+            }
+        )
+    }
+}
+
+/// A map of the playlist of a [`ClubCampaign`].
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistMap {
+    pub position: u32,
+    pub map_uid: MapUid,
+}
+
+/// A club campaign.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClubCampaign {
+    pub campaign_id: CampaignId,
+    pub activity_id: ActivityId,
+    pub name: String,
+    #[serde(default)]
+    pub playlist: Vec<PlaylistMap>,
+}
+
+/// Checks the name of an activity.
+pub(crate) fn validate_name(name: &str) -> std::result::Result<(), ClubError> {
+    if text::strip(name).trim().is_empty() {
+        return Err(ClubError::EmptyName);
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(ClubError::NameTooLong {
+            max: MAX_NAME_LENGTH,
+        });
+    }
+
+    Ok(())
+}
+
+impl NadeoClient {
+    /// Creates an activity, e.g. a folder or news. Campaigns and rooms are created with [`NadeoClient::create_club_campaign`] and [`NadeoClient::create_club_room`].
+    /// Requires [`AuthType::NadeoLiveServices`].
+    pub async fn create_club_activity(
+        &mut self,
+        club_id: ClubId,
+        name: &str,
+        activity_type: ActivityType,
+    ) -> Result<ClubActivity> {
+        validate_name(name)?;
+        let body = json!(
+            {
+                "name": name,
+                "activityType": activity_type
+            }
+        );
+
+        self.post_json(
+            &format!("{CLUB_URL}/{club_id}/activity/create"),
+            AuthType::NadeoLiveServices,
+            &body,
+        )
+        .await
+    }
+
+    /// Edits an activity. Requires [`AuthType::NadeoLiveServices`].
+    pub async fn edit_club_activity(
+        &mut self,
+        club_id: ClubId,
+        activity_id: ActivityId,
+        edit: &ActivityEdit,
+    ) -> Result<ClubActivity> {
+        edit.validate()?;
+
+        self.post_json(
+            &format!("{CLUB_URL}/{club_id}/activity/{activity_id}/edit"),
+            AuthType::NadeoLiveServices,
+            edit,
+        )
+        .await
+    }
+
+    /// Deletes an activity including its campaign or room. Requires [`AuthType::NadeoLiveServices`].
+    pub async fn delete_club_activity(
+        &mut self,
+        club_id: ClubId,
+        activity_id: ActivityId,
+    ) -> Result<()> {
+        self.post_json::<serde_json::Value>(
+            &format!("{CLUB_URL}/{club_id}/activity/{activity_id}/delete"),
+            AuthType::NadeoLiveServices,
+            &json!({}),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Moves the activities to the first positions in the given order by editing their positions one after another.
+    /// Where activities which are not given end up is decided by the API.
+    /// Requires [`AuthType::NadeoLiveServices`].
+    ///
+    /// This is not atomic. If an edit fails, the activities before it have already been moved.
+    pub async fn reorder_club_activities(
+        &mut self,
+        club_id: ClubId,
+        order: &[ActivityId],
+    ) -> Result<()> {
+        let mut seen = HashSet::new();
+        if let Some(activity_id) = order.iter().find(|id| !seen.insert(**id)) {
+            return Err(Error::from(ClubError::DuplicateActivity(*activity_id)));
+        }
+
+        for (position, activity_id) in order.iter().enumerate() {
+            let edit = ActivityEdit::new().position(position as u32);
+            self.edit_club_activity(club_id, *activity_id, &edit)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Creates a room. Requires [`AuthType::NadeoLiveServices`].
+    pub async fn create_club_room(
+        &mut self,
+        club_id: ClubId,
+        room: &RoomConfig,
+    ) -> Result<ClubRoom> {
+        room.validate()?;
+
+        self.post_json(
+            &format!("{CLUB_URL}/{club_id}/room/create"),
+            AuthType::NadeoLiveServices,
+            room,
+        )
+        .await
+    }
+
+    /// Replaces the configuration of a room. Requires [`AuthType::NadeoLiveServices`].
+    pub async fn edit_club_room(
+        &mut self,
+        club_id: ClubId,
+        room_id: RoomId,
+        room: &RoomConfig,
+    ) -> Result<ClubRoom> {
+        room.validate()?;
+
+        self.post_json(
+            &format!("{CLUB_URL}/{club_id}/room/{room_id}/edit"),
+            AuthType::NadeoLiveServices,
+            room,
+        )
+        .await
+    }
+
+    /// Creates a campaign. Requires [`AuthType::NadeoLiveServices`].
+    pub async fn create_club_campaign(
+        &mut self,
+        club_id: ClubId,
+        campaign: &CampaignConfig,
+    ) -> Result<ClubCampaign> {
+        campaign.validate()?;
+
+        self.post_json(
+            &format!("{CLUB_URL}/{club_id}/campaign/create"),
+            AuthType::NadeoLiveServices,
+            &campaign.to_json(),
+        )
+        .await
+    }
+
+    /// Replaces the name and playlist of a campaign. Requires [`AuthType::NadeoLiveServices`].
+    pub async fn edit_club_campaign(
+        &mut self,
+        club_id: ClubId,
+        campaign_id: CampaignId,
+        campaign: &CampaignConfig,
+    ) -> Result<ClubCampaign> {
+        campaign.validate()?;
+
+        self.post_json(
+            &format!("{CLUB_URL}/{club_id}/campaign/{campaign_id}/edit"),
+            AuthType::NadeoLiveServices,
+            &campaign.to_json(),
+        )
+        .await
+    }
+
+    /// Changes the role of a club member. Requires [`AuthType::NadeoLiveServices`].
+    pub async fn set_club_member_role(
+        &mut self,
+        club_id: ClubId,
+        account_id: AccountId,
+        role: ClubRole,
+    ) -> Result<()> {
+        self.post_json::<serde_json::Value>(
+            &format!("{CLUB_URL}/{club_id}/member/{account_id}/edit"),
+            AuthType::NadeoLiveServices,
+            &json!({ "role": role }),
+        )
+        .await
+        .map(|_| ())
+    }
+}
+
+/// Errors of club operations.
+#[derive(Error, Debug)]
+pub enum ClubError {
+    #[error("the name is empty")]
+    EmptyName,
+    #[error("the name is longer than {max} characters")]
+    NameTooLong { max: usize },
+    #[error("no maps were given")]
+    NoMaps,
+    #[error("more than {max} maps were given")]
+    TooManyMaps { max: usize },
+    #[error("map {0} was given more than once")]
+    DuplicateMap(MapUid),
+    #[error("activity {0} was given more than once")]
+    DuplicateActivity(ActivityId),
+    #[error("the player limit must be between 1 and {max}")]
+    InvalidPlayerLimit { max: u32 },
+    #[error("no mode script was given")]
+    MissingScript,
+    #[error("{0:?} is not a valid script setting, settings start with S_")]
+    InvalidSetting(String),
+    #[error("no changes were given")]
+    NoChanges,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maps(count: usize) -> Vec<MapUid> {
+        (0..count)
+            .map(|i| format!("map{i}").parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn validates_names() {
+        assert!(validate_name("Training").is_ok());
+        assert!(validate_name(&"a".repeat(MAX_NAME_LENGTH)).is_ok());
+        assert!(matches!(validate_name(""), Err(ClubError::EmptyName)));
+        assert!(matches!(validate_name("  "), Err(ClubError::EmptyName)));
+        // only formatting codes
+        assert!(matches!(validate_name("$o$f00"), Err(ClubError::EmptyName)));
+        assert!(matches!(
+            validate_name(&"a".repeat(MAX_NAME_LENGTH + 1)),
+            Err(ClubError::NameTooLong {
+                max: MAX_NAME_LENGTH
+            })
+        ));
+        // the length counts characters, not bytes
+        assert!(validate_name(&"é".repeat(MAX_NAME_LENGTH)).is_ok());
+    }
+
+    #[test]
+    fn validates_activity_edits() {
+        assert!(ActivityEdit::new().active(false).validate().is_ok());
+        assert!(matches!(
+            ActivityEdit::new().validate(),
+            Err(ClubError::NoChanges)
+        ));
+        assert!(matches!(
+            ActivityEdit::new().public(true).name("").validate(),
+            Err(ClubError::EmptyName)
+        ));
+    }
+
+    #[test]
+    fn activity_edit_only_serializes_changes() {
+        let edit = ActivityEdit::new().name("News").position(2);
+
+        assert_eq!(
+            serde_json::to_value(edit).unwrap(),
+            json!({ "name": "News", "position": 2 })
+        );
+    }
+
+    #[test]
+    fn validates_campaign_configs() {
+        assert!(CampaignConfig::new("Campaign", maps(MAX_CAMPAIGN_MAPS))
+            .validate()
+            .is_ok());
+        assert!(matches!(
+            CampaignConfig::new("", maps(1)).validate(),
+            Err(ClubError::EmptyName)
+        ));
+        assert!(matches!(
+            CampaignConfig::new("Campaign", Vec::new()).validate(),
+            Err(ClubError::NoMaps)
+        ));
+        assert!(matches!(
+            CampaignConfig::new("Campaign", maps(MAX_CAMPAIGN_MAPS + 1)).validate(),
+            Err(ClubError::TooManyMaps {
+                max: MAX_CAMPAIGN_MAPS
+            })
+        ));
+
+        let mut playlist = maps(3);
+        playlist.push(playlist[1].clone());
+        assert!(matches!(
+            CampaignConfig::new("Campaign", playlist).validate(),
+            Err(ClubError::DuplicateMap(map_uid)) if map_uid.to_string() == "map1"
+        ));
+    }
+
+    #[test]
+    fn campaign_config_json_has_positions() {
+        let config = CampaignConfig::new("Campaign", maps(2));
+
+        assert_eq!(
+            config.to_json(),
+            json!({
+                "name": "Campaign",
+                "playlist": [
+                    { "position": 0, "mapUid": "map0" },
+                    { "position": 1, "mapUid": "map1" },
+                ]
+            })
+        );
+    }
+}
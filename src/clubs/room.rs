@@ -0,0 +1,342 @@
+use crate::clubs::{validate_name, ClubError};
+use crate::types::{ActivityId, ClubId, MapUid, RoomId};
+use serde::{Deserialize, Serialize, Serializer};
+
+/// Maximum number of players per server of a room.
+pub const MAX_PLAYERS_PER_SERVER: u32 = 100;
+
+/// Value of a mode script setting.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingValue {
+    Integer(i64),
+    Boolean(bool),
+    Text(String),
+    Real(f64),
+}
+
+impl SettingValue {
+    fn type_name(&self) -> &'static str {
+        match self {
+            SettingValue::Integer(_) => "integer",
+            SettingValue::Boolean(_) => "boolean",
+            SettingValue::Text(_) => "text",
+            SettingValue::Real(_) => "real",
+        }
+    }
+}
+
+impl From<i64> for SettingValue {
+    fn from(value: i64) -> Self {
+        SettingValue::Integer(value)
+    }
+}
+
+impl From<i32> for SettingValue {
+    fn from(value: i32) -> Self {
+        SettingValue::Integer(value as i64)
+    }
+}
+
+impl From<bool> for SettingValue {
+    fn from(value: bool) -> Self {
+        SettingValue::Boolean(value)
+    }
+}
+
+impl From<&str> for SettingValue {
+    fn from(value: &str) -> Self {
+        SettingValue::Text(value.to_string())
+    }
+}
+
+impl From<f64> for SettingValue {
+    fn from(value: f64) -> Self {
+        SettingValue::Real(value)
+    }
+}
+
+/// A mode script setting of a room, e.g. `S_TimeLimit`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptSetting {
+    pub key: String,
+    pub value: SettingValue,
+}
+
+impl Serialize for ScriptSetting {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Setting<'a> {
+            key: &'a str,
+            value: String,
+            #[serde(rename = "type")]
+            setting_type: &'a str,
+        }
+
+        let value = match self.value {
+            SettingValue::Integer(i) => i.to_string(),
+            SettingValue::Boolean(b) => b.to_string(),
+            SettingValue::Text(ref s) => s.clone(),
+            SettingValue::Real(r) => r.to_string(),
+        };
+        Setting {
+            key: &self.key,
+            value,
+            setting_type: self.value.type_name(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Configuration of a club room. Used for creating and editing rooms.
+///
+/// # Examples
+///
+/// ```rust
+/// # use nadeo_api::clubs::RoomConfig;
+/// # fn run() -> nadeo_api::Result<()> {
+/// let room = RoomConfig::new("Training", "TrackMania/TM_TimeAttack_Online.Script.txt")
+///     .max_players(32)
+///     .setting("S_TimeLimit", 300)
+///     .map("olsKnq_qAghcVAnEkoeUnVHFZei".parse()?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomConfig {
+    name: String,
+    region: String,
+    #[serde(rename = "maxPlayersPerServer")]
+    max_players: u32,
+    script: String,
+    settings: Vec<ScriptSetting>,
+    maps: Vec<MapUid>,
+    #[serde(serialize_with = "as_int")]
+    scalable: bool,
+    #[serde(serialize_with = "as_int")]
+    password: bool,
+}
+
+fn as_int<S: Serializer>(val: &bool, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u8(*val as u8)
+}
+
+impl RoomConfig {
+    /// Creates a room in the `eu-west` region with 32 players per server.
+    pub fn new(name: &str, script: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            region: "eu-west".to_string(),
+            max_players: 32,
+            script: script.to_string(),
+            settings: Vec::new(),
+            maps: Vec::new(),
+            scalable: true,
+            password: false,
+        }
+    }
+
+    /// Sets the region of the servers, e.g. `eu-west` or `ca-central`.
+    pub fn region(mut self, region: &str) -> Self {
+        self.region = region.to_string();
+
+        self
+    }
+
+    pub fn max_players(mut self, max_players: u32) -> Self {
+        self.max_players = max_players;
+
+        self
+    }
+
+    /// Adds a mode script setting. A previous value of the same key is replaced.
+    pub fn setting(mut self, key: &str, value: impl Into<SettingValue>) -> Self {
+        self.settings.retain(|setting| setting.key != key);
+        self.settings.push(ScriptSetting {
+            key: key.to_string(),
+            value: value.into(),
+        });
+
+        self
+    }
+
+    /// Adds a map to the map list of the room.
+    pub fn map(mut self, map_uid: MapUid) -> Self {
+        self.maps.push(map_uid);
+
+        self
+    }
+
+    /// Replaces the map list of the room.
+    pub fn maps(mut self, map_uids: Vec<MapUid>) -> Self {
+        self.maps = map_uids;
+
+        self
+    }
+
+    /// Whether more servers are started if the room is full. Enabled by default.
+    pub fn scalable(mut self, scalable: bool) -> Self {
+        self.scalable = scalable;
+
+        self
+    }
+
+    /// Whether a password is required for joining.
+    pub fn password(mut self, password: bool) -> Self {
+        self.password = password;
+
+        self
+    }
+
+    /// Checks the configuration. Called before the room is sent.
+    pub fn validate(&self) -> Result<(), ClubError> {
+        validate_name(&self.name)?;
+        if self.max_players == 0 || self.max_players > MAX_PLAYERS_PER_SERVER {
+            return Err(ClubError::InvalidPlayerLimit {
+                max: MAX_PLAYERS_PER_SERVER,
+            });
+        }
+        if self.script.is_empty() {
+            return Err(ClubError::MissingScript);
+        }
+        if self.maps.is_empty() {
+            return Err(ClubError::NoMaps);
+        }
+        if let Some(setting) = self
+            .settings
+            .iter()
+            .find(|setting| !setting.key.starts_with("S_"))
+        {
+            return Err(ClubError::InvalidSetting(setting.key.clone()));
+        }
+
+        Ok(())
+    }
+}
+
+/// A club room.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClubRoom {
+    pub id: RoomId,
+    pub club_id: ClubId,
+    pub activity_id: ActivityId,
+    pub name: String,
+    #[serde(default)]
+    pub region: String,
+    #[serde(default)]
+    pub max_players: u32,
+    #[serde(default)]
+    pub script: String,
+    #[serde(default)]
+    pub maps: Vec<MapUid>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SCRIPT: &str = "TrackMania/TM_TimeAttack_Online.Script.txt";
+
+    fn room() -> RoomConfig {
+        RoomConfig::new("Training", SCRIPT).map("map".parse().unwrap())
+    }
+
+    #[test]
+    fn validates_room_configs() {
+        assert!(room().validate().is_ok());
+        assert!(room().max_players(1).validate().is_ok());
+        assert!(room()
+            .max_players(MAX_PLAYERS_PER_SERVER)
+            .validate()
+            .is_ok());
+
+        for max_players in [0, MAX_PLAYERS_PER_SERVER + 1] {
+            assert!(matches!(
+                room().max_players(max_players).validate(),
+                Err(ClubError::InvalidPlayerLimit {
+                    max: MAX_PLAYERS_PER_SERVER
+                })
+            ));
+        }
+        assert!(matches!(
+            RoomConfig::new("", SCRIPT)
+                .map("map".parse().unwrap())
+                .validate(),
+            Err(ClubError::EmptyName)
+        ));
+        assert!(matches!(
+            RoomConfig::new("Training", "")
+                .map("map".parse().unwrap())
+                .validate(),
+            Err(ClubError::MissingScript)
+        ));
+        assert!(matches!(
+            RoomConfig::new("Training", SCRIPT).validate(),
+            Err(ClubError::NoMaps)
+        ));
+        assert!(matches!(
+            room().setting("TimeLimit", 300).validate(),
+            Err(ClubError::InvalidSetting(key)) if key == "TimeLimit"
+        ));
+    }
+
+    #[test]
+    fn settings_replace_previous_values() {
+        let config = room()
+            .setting("S_TimeLimit", 300)
+            .setting("S_TimeLimit", 600);
+
+        assert_eq!(
+            config.settings,
+            vec![ScriptSetting {
+                key: "S_TimeLimit".to_string(),
+                value: SettingValue::Integer(600),
+            }]
+        );
+    }
+
+    #[test]
+    fn serializes_settings_with_their_type() {
+        let settings = [
+            ("S_TimeLimit", SettingValue::from(300), "300", "integer"),
+            ("S_WarmUp", SettingValue::from(true), "true", "boolean"),
+            ("S_Name", SettingValue::from("Cup"), "Cup", "text"),
+            ("S_Ratio", SettingValue::from(0.5), "0.5", "real"),
+        ];
+
+        for (key, value, text, setting_type) in settings {
+            let setting = ScriptSetting {
+                key: key.to_string(),
+                value,
+            };
+            assert_eq!(
+                serde_json::to_value(&setting).unwrap(),
+                json!({ "key": key, "value": text, "type": setting_type })
+            );
+        }
+    }
+
+    #[test]
+    fn serializes_room_configs() {
+        let config = room()
+            .max_players(16)
+            .password(true)
+            .setting("S_TimeLimit", 300);
+
+        assert_eq!(
+            serde_json::to_value(&config).unwrap(),
+            json!({
+                "name": "Training",
+                "region": "eu-west",
+                "maxPlayersPerServer": 16,
+                "script": SCRIPT,
+                "settings": [{ "key": "S_TimeLimit", "value": "300", "type": "integer" }],
+                "maps": ["map"],
+                "scalable": 1,
+                "password": 1,
+            })
+        );
+    }
+}
@@ -0,0 +1,548 @@
+//! Competitions of the meet services, e.g. cups and the Cup of the Day.
+
+use crate::auth::AuthType;
+use crate::client::pagination::paginate;
+use crate::types::{AccountId, CompetitionId, MatchId, RoundId};
+use crate::{Error, NadeoClient, NadeoRequest, Result};
+use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+pub(crate) const MEET_URL: &str = "https://meet.trackmania.nadeo.club/api";
+/// Number of items requested per page of paginated endpoints.
+pub(crate) const PAGE_SIZE: u32 = 100;
+
+/// Whether players compete alone or in teams.
+#[derive(strum::Display, Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum ParticipantType {
+    Player,
+    Team,
+    /// A participant type which is not known to this crate.
+    #[serde(other)]
+    Unknown,
+}
+
+/// A competition.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Competition {
+    pub id: CompetitionId,
+    /// ID of the competition in the live services, e.g. `LID-COMP-xxxxxxxxxxxxxxx`.
+    pub live_id: String,
+    pub name: String,
+    pub participant_type: ParticipantType,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default, with = "chrono::serde::ts_seconds_option")]
+    pub registration_start: Option<DateTime<Utc>>,
+    #[serde(default, with = "chrono::serde::ts_seconds_option")]
+    pub registration_end: Option<DateTime<Utc>>,
+    #[serde(default, with = "chrono::serde::ts_seconds_option")]
+    pub start_date: Option<DateTime<Utc>>,
+    #[serde(default, with = "chrono::serde::ts_seconds_option")]
+    pub end_date: Option<DateTime<Utc>>,
+    /// Number of registered participants.
+    #[serde(default)]
+    pub nb_players: u32,
+    #[serde(default)]
+    pub leaderboard_id: u64,
+    #[serde(default)]
+    pub region: Option<String>,
+}
+
+/// A round of a competition, e.g. the qualification or the knockout.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Round {
+    pub id: RoundId,
+    pub position: u32,
+    pub name: String,
+    #[serde(default)]
+    pub nb_matches: u32,
+    #[serde(default, with = "chrono::serde::ts_seconds_option")]
+    pub start_date: Option<DateTime<Utc>>,
+    #[serde(default, with = "chrono::serde::ts_seconds_option")]
+    pub end_date: Option<DateTime<Utc>>,
+    /// Status of the round, e.g. `NOT_STARTED` or `COMPLETED`.
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub leaderboard_compute_type: String,
+}
+
+/// A match of a competition round.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Match {
+    pub id: MatchId,
+    pub name: String,
+    #[serde(default)]
+    pub position: u32,
+    /// ID of the match in the live services, e.g. `LID-MTCH-xxxxxxxxxxxxxxx`.
+    #[serde(default)]
+    pub club_match_live_id: String,
+    #[serde(default)]
+    pub is_completed: bool,
+}
+
+/// Result of a participant in a match.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchResult {
+    /// Account ID of a player or name of a team.
+    pub participant: String,
+    /// `None` if the participant is not ranked yet.
+    #[serde(default)]
+    pub rank: Option<u32>,
+    #[serde(default)]
+    pub score: Option<i64>,
+    #[serde(default)]
+    pub zone: Option<String>,
+}
+
+impl MatchResult {
+    /// The account of the participant. `None` for teams.
+    pub fn account_id(&self) -> Option<AccountId> {
+        self.participant.parse().ok()
+    }
+}
+
+/// An entry of the leaderboard of a competition.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardEntry {
+    /// Account ID of a player or name of a team.
+    pub participant: String,
+    pub rank: u32,
+    #[serde(default)]
+    pub score: i64,
+    #[serde(default)]
+    pub zone: Option<String>,
+}
+
+impl LeaderboardEntry {
+    /// The account of the participant. `None` for teams.
+    pub fn account_id(&self) -> Option<AccountId> {
+        self.participant.parse().ok()
+    }
+}
+
+/// A registered participant of a competition.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Participant {
+    /// Account ID of a player or name of a team.
+    pub participant: String,
+    #[serde(default)]
+    pub zone: Option<String>,
+}
+
+impl Participant {
+    /// The account of the participant. `None` for teams.
+    pub fn account_id(&self) -> Option<AccountId> {
+        self.participant.parse().ok()
+    }
+}
+
+#[derive(Deserialize)]
+struct MatchPage {
+    matches: Vec<Match>,
+}
+
+#[derive(Deserialize)]
+struct ResultPage {
+    results: Vec<MatchResult>,
+}
+
+/// Configuration for creating a competition.
+///
+/// Fields which are not covered by the builder, e.g. the `spotStructure` of the rounds, can be set with
+/// [`CompetitionConfig::field`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use nadeo_api::competitions::CompetitionConfig;
+/// # use chrono::{Duration, Utc};
+/// let start = Utc::now() + Duration::days(1);
+/// let competition = CompetitionConfig::new("Weekly Cup", start, start + Duration::hours(2))
+///     .description("Every sunday")
+///     .registration(Utc::now(), start);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompetitionConfig {
+    name: String,
+    participant_type: ParticipantType,
+    description: Option<String>,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+    registration: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl CompetitionConfig {
+    /// Creates a competition for single players.
+    pub fn new(name: &str, start_date: DateTime<Utc>, end_date: DateTime<Utc>) -> Self {
+        Self {
+            name: name.to_string(),
+            participant_type: ParticipantType::Player,
+            description: None,
+            start_date,
+            end_date,
+            registration: None,
+            extra: serde_json::Map::new(),
+        }
+    }
+
+    pub fn participant_type(mut self, participant_type: ParticipantType) -> Self {
+        self.participant_type = participant_type;
+
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+
+        self
+    }
+
+    /// Sets the time frame in which players can register.
+    pub fn registration(mut self, start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        self.registration = Some((start, end));
+
+        self
+    }
+
+    /// Sets an additional field of the request body.
+    pub fn field(mut self, key: &str, value: serde_json::Value) -> Self {
+        self.extra.insert(key.to_string(), value);
+
+        self
+    }
+
+    /// Checks the configuration. Called before the competition is created.
+    pub fn validate(&self) -> std::result::Result<(), CompetitionError> {
+        if self.name.trim().is_empty() {
+            return Err(CompetitionError::EmptyName);
+        }
+        if self.participant_type == ParticipantType::Unknown {
+            return Err(CompetitionError::UnknownParticipantType);
+        }
+        if self.end_date <= self.start_date {
+            return Err(CompetitionError::InvalidSchedule(
+                "the competition ends before it starts",
+            ));
+        }
+        if let Some((start, end)) = self.registration {
+            if end <= start {
+                return Err(CompetitionError::InvalidSchedule(
+                    "the registration ends before it starts",
+                ));
+            }
+            if end > self.start_date {
+                return Err(CompetitionError::InvalidSchedule(
+                    "the registration ends after the competition starts",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The request body for creating the competition.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut body = self.extra.clone();
+        body.insert("name".to_string(), json!(self.name));
+        body.insert("participantType".to_string(), json!(self.participant_type));
+        body.insert("startDate".to_string(), json!(self.start_date.timestamp()));
+        body.insert("endDate".to_string(), json!(self.end_date.timestamp()));
+        if let Some(description) = &self.description {
+            body.insert("description".to_string(), json!(description));
+        }
+        if let Some((start, end)) = self.registration {
+            body.insert("registrationStart".to_string(), json!(start.timestamp()));
+            body.insert("registrationEnd".to_string(), json!(end.timestamp()));
+        }
+
+        serde_json::Value::Object(body)
+    }
+}
+
+impl NadeoClient {
+    /// Gets a competition. Requires [`AuthType::NadeoLiveServices`].
+    pub async fn competition(&mut self, competition_id: CompetitionId) -> Result<Competition> {
+        self.meet_get(&format!("{MEET_URL}/competitions/{competition_id}"))
+            .await
+    }
+
+    /// Gets the rounds of a competition ordered by their position. Requires [`AuthType::NadeoLiveServices`].
+    pub async fn competition_rounds(
+        &mut self,
+        competition_id: CompetitionId,
+    ) -> Result<Vec<Round>> {
+        let mut rounds: Vec<Round> = self
+            .meet_get(&format!("{MEET_URL}/competitions/{competition_id}/rounds"))
+            .await?;
+        rounds.sort_by_key(|round| round.position);
+
+        Ok(rounds)
+    }
+
+    /// Streams the matches of a round. Pages are requested while the stream is polled.
+    /// Requires [`AuthType::NadeoLiveServices`].
+    pub fn round_matches(&mut self, round_id: RoundId) -> BoxStream<'_, Result<Match>> {
+        paginate(
+            self,
+            PAGE_SIZE,
+            move |offset, length| {
+                meet_request(&format!(
+                    "{MEET_URL}/rounds/{round_id}/matches?length={length}&offset={offset}"
+                ))
+            },
+            |page: MatchPage| page.matches,
+        )
+    }
+
+    /// Streams the results of a match. Pages are requested while the stream is polled.
+    /// Requires [`AuthType::NadeoLiveServices`].
+    pub fn match_results(&mut self, match_id: MatchId) -> BoxStream<'_, Result<MatchResult>> {
+        paginate(
+            self,
+            PAGE_SIZE,
+            move |offset, length| {
+                meet_request(&format!(
+                    "{MEET_URL}/matches/{match_id}/results?length={length}&offset={offset}"
+                ))
+            },
+            |page: ResultPage| page.results,
+        )
+    }
+
+    /// Streams the leaderboard of a competition. Pages are requested while the stream is polled.
+    /// Requires [`AuthType::NadeoLiveServices`].
+    pub fn competition_leaderboard(
+        &mut self,
+        competition_id: CompetitionId,
+    ) -> BoxStream<'_, Result<LeaderboardEntry>> {
+        paginate(
+            self,
+            PAGE_SIZE,
+            move |offset, length| {
+                meet_request(&format!(
+                    "{MEET_URL}/competitions/{competition_id}/leaderboard?length={length}&offset={offset}"
+                ))
+            },
+            |page: Vec<LeaderboardEntry>| page,
+        )
+    }
+
+    /// Streams the registered participants of a competition. Pages are requested while the stream is polled.
+    /// Requires [`AuthType::NadeoLiveServices`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use nadeo_api::NadeoClient;
+    /// # use futures::TryStreamExt;
+    /// # async fn run(client: &mut NadeoClient) -> nadeo_api::Result<()> {
+    /// let participants: Vec<_> = client
+    ///     .competition_participants(12345.into())
+    ///     .try_collect()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn competition_participants(
+        &mut self,
+        competition_id: CompetitionId,
+    ) -> BoxStream<'_, Result<Participant>> {
+        paginate(
+            self,
+            PAGE_SIZE,
+            move |offset, length| {
+                meet_request(&format!(
+                    "{MEET_URL}/competitions/{competition_id}/participants?length={length}&offset={offset}"
+                ))
+            },
+            |page: Vec<Participant>| page,
+        )
+    }
+
+    /// Creates a competition. Requires [`AuthType::NadeoLiveServices`] of an organizer account.
+    pub async fn create_competition(&mut self, config: &CompetitionConfig) -> Result<Competition> {
+        config.validate()?;
+
+        self.post_json(
+            &format!("{MEET_URL}/competitions/web/create"),
+            AuthType::NadeoLiveServices,
+            &config.to_json(),
+        )
+        .await
+    }
+
+    /// Registers players for a competition. Requires [`AuthType::NadeoLiveServices`] of an organizer account.
+    pub async fn add_competition_participants(
+        &mut self,
+        competition_id: CompetitionId,
+        account_ids: &[AccountId],
+    ) -> Result<()> {
+        if account_ids.is_empty() {
+            return Err(Error::from(CompetitionError::NoParticipants));
+        }
+
+        self.post_json::<serde_json::Value>(
+            &format!("{MEET_URL}/competitions/{competition_id}/participants/add"),
+            AuthType::NadeoLiveServices,
+            &json!({ "participants": account_ids }),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Unregisters players from a competition. Requires [`AuthType::NadeoLiveServices`] of an organizer account.
+    pub async fn remove_competition_participants(
+        &mut self,
+        competition_id: CompetitionId,
+        account_ids: &[AccountId],
+    ) -> Result<()> {
+        if account_ids.is_empty() {
+            return Err(Error::from(CompetitionError::NoParticipants));
+        }
+
+        self.post_json::<serde_json::Value>(
+            &format!("{MEET_URL}/competitions/{competition_id}/participants/remove"),
+            AuthType::NadeoLiveServices,
+            &json!({ "participants": account_ids }),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    pub(crate) async fn meet_get<T: DeserializeOwned>(&mut self, url: &str) -> Result<T> {
+        let res = self.execute(meet_request(url)?).await?;
+
+        Ok(res.json().await?)
+    }
+}
+
+pub(crate) fn meet_request(url: &str) -> Result<NadeoRequest> {
+    NadeoRequest::builder()
+        .url(url)
+        .auth_type(AuthType::NadeoLiveServices)
+        .method(Method::GET)
+        .build()
+}
+
+/// Errors of competition operations.
+#[derive(Error, Debug)]
+pub enum CompetitionError {
+    #[error("the name is empty")]
+    EmptyName,
+    #[error("the participant type is unknown")]
+    UnknownParticipantType,
+    #[error("invalid schedule: {0}")]
+    InvalidSchedule(&'static str),
+    #[error("no participants were given")]
+    NoParticipants,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 10, 18, 0, 0).unwrap()
+    }
+
+    fn config() -> CompetitionConfig {
+        CompetitionConfig::new("Weekly Cup", start(), start() + Duration::hours(2))
+    }
+
+    fn invalid_schedule(config: CompetitionConfig) -> &'static str {
+        match config.validate() {
+            Err(CompetitionError::InvalidSchedule(reason)) => reason,
+            other => panic!("unexpected result {other:?}"),
+        }
+    }
+
+    #[test]
+    fn validates_configs() {
+        assert!(config().validate().is_ok());
+        assert!(config()
+            .registration(start() - Duration::days(1), start())
+            .validate()
+            .is_ok());
+        assert!(matches!(
+            CompetitionConfig::new(" ", start(), start() + Duration::hours(2)).validate(),
+            Err(CompetitionError::EmptyName)
+        ));
+        assert!(matches!(
+            config()
+                .participant_type(ParticipantType::Unknown)
+                .validate(),
+            Err(CompetitionError::UnknownParticipantType)
+        ));
+    }
+
+    #[test]
+    fn validates_the_schedule() {
+        assert_eq!(
+            invalid_schedule(CompetitionConfig::new("Cup", start(), start())),
+            "the competition ends before it starts"
+        );
+        assert_eq!(
+            invalid_schedule(
+                config().registration(start() - Duration::hours(1), start() - Duration::hours(2))
+            ),
+            "the registration ends before it starts"
+        );
+        assert_eq!(
+            invalid_schedule(
+                config().registration(start() - Duration::hours(1), start() + Duration::minutes(1))
+            ),
+            "the registration ends after the competition starts"
+        );
+    }
+
+    #[test]
+    fn json_contains_the_fields() {
+        let config = config()
+            .participant_type(ParticipantType::Team)
+            .description("Every sunday")
+            .registration(start() - Duration::days(1), start())
+            .field("region", json!("eu-west"))
+            // fields of the builder are not overwritten
+            .field("name", json!("Other"));
+
+        assert_eq!(
+            config.to_json(),
+            json!({
+                "name": "Weekly Cup",
+                "participantType": "TEAM",
+                "description": "Every sunday",
+                "startDate": start().timestamp(),
+                "endDate": start().timestamp() + 2 * 3600,
+                "registrationStart": start().timestamp() - 24 * 3600,
+                "registrationEnd": start().timestamp(),
+                "region": "eu-west",
+            })
+        );
+    }
+
+    #[test]
+    fn json_leaves_out_unset_fields() {
+        let json = config().to_json();
+
+        assert_eq!(json["participantType"], "PLAYER");
+        for field in ["description", "registrationStart", "registrationEnd"] {
+            assert!(json.get(field).is_none(), "{field}");
+        }
+    }
+}
@@ -0,0 +1,446 @@
+//! Downloading map and ghost files.
+//!
+//! Files are streamed to any [`AsyncWrite`] or to disk without loading them into memory.
+//! URLs of known hosts (see [`AudienceTable`]) are requested with the matching [`AuthType`], other URLs without authentication.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use nadeo_api::NadeoClient;
+//! # use nadeo_api::download::DownloadOptions;
+//! # use nadeo_api::maps::MapInfo;
+//! # async fn run(mut client: NadeoClient, map: MapInfo) -> nadeo_api::Result<()> {
+//! let options = DownloadOptions::default()
+//!     .max_size(20 * 1024 * 1024)
+//!     .on_progress(|progress| println!("{} bytes", progress.downloaded));
+//!
+//! let path = client.download_map(&map, "maps", &options).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`AudienceTable`]: crate::request::audience::AudienceTable
+//! [`AuthType`]: crate::auth::AuthType
+
+use crate::maps::MapInfo;
+use crate::records::MapRecord;
+use crate::request::audience::default_table;
+use crate::{Error, NadeoClient, NadeoRequest, Result};
+use futures::stream::{self, StreamExt};
+use reqwest::header::HeaderValue;
+use reqwest::{Method, Response};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Progress of a download.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Progress {
+    /// Downloaded bytes.
+    pub downloaded: u64,
+    /// Size of the file if it is known.
+    pub total: Option<u64>,
+}
+
+type ProgressCallback = Arc<dyn Fn(Progress) + Send + Sync>;
+
+/// Options for downloading files.
+#[derive(Clone, Default)]
+pub struct DownloadOptions {
+    max_size: Option<u64>,
+    sha256: Option<String>,
+    progress: Option<ProgressCallback>,
+}
+
+impl Debug for DownloadOptions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DownloadOptions")
+            .field("max_size", &self.max_size)
+            .field("sha256", &self.sha256)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DownloadOptions {
+    /// Aborts the download if the file is larger than `bytes`.
+    pub fn max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+
+        self
+    }
+
+    /// Verifies the SHA-256 checksum (hex encoded) of the downloaded file.
+    pub fn sha256(mut self, checksum: &str) -> Self {
+        self.sha256 = Some(checksum.to_ascii_lowercase());
+
+        self
+    }
+
+    /// Calls `callback` after every received chunk.
+    pub fn on_progress<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(Progress) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(callback));
+
+        self
+    }
+}
+
+/// Summary of a completed download.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Download {
+    /// Size of the file in bytes.
+    pub size: u64,
+    /// Hex encoded SHA-256 checksum of the file.
+    pub sha256: String,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+impl NadeoClient {
+    /// Sends a `GET` request for a file. Known hosts are requested with authentication.
+    async fn get_file(&mut self, url: &str) -> Result<Response> {
+        if default_table().get(url).is_some() {
+            let request = NadeoRequest::builder()
+                .url(url)
+                .method(Method::GET)
+                .bypass_cache()
+                .bypass_coalescing()
+                .build()?;
+
+            return self.execute(request).await;
+        }
+
+        let res = self
+            .client
+            .get(url)
+            .header(
+                "User-Agent",
+                self.meta_data.user_agent.parse::<HeaderValue>().unwrap(),
+            )
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(res)
+    }
+
+    /// Streams the file at `url` into `writer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if the request fails, the file exceeds the size limit or the checksum does not match.
+    /// The writer may have received a part of the file in that case.
+    pub async fn download<W>(
+        &mut self,
+        url: &str,
+        writer: &mut W,
+        options: &DownloadOptions,
+    ) -> Result<Download>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut res = self.get_file(url).await?;
+
+        let total = res.content_length();
+        if let (Some(total), Some(limit)) = (total, options.max_size) {
+            if total > limit {
+                return Err(Error::from(DownloadError::TooLarge { limit }));
+            }
+        }
+
+        let mut hasher = Sha256::new();
+        let mut downloaded = 0u64;
+        while let Some(chunk) = res.chunk().await? {
+            downloaded += chunk.len() as u64;
+            if options.max_size.is_some_and(|limit| downloaded > limit) {
+                return Err(Error::from(DownloadError::TooLarge {
+                    limit: options.max_size.unwrap(),
+                }));
+            }
+
+            hasher.update(&chunk);
+            writer
+                .write_all(&chunk)
+                .await
+                .map_err(DownloadError::from)?;
+
+            if let Some(ref progress) = options.progress {
+                progress(Progress { downloaded, total });
+            }
+        }
+        writer.flush().await.map_err(DownloadError::from)?;
+
+        let sha256 = to_hex(&hasher.finalize());
+        if let Some(ref expected) = options.sha256 {
+            if *expected != sha256 {
+                return Err(Error::from(DownloadError::ChecksumMismatch {
+                    expected: expected.clone(),
+                    actual: sha256,
+                }));
+            }
+        }
+
+        Ok(Download {
+            size: downloaded,
+            sha256,
+        })
+    }
+
+    /// Downloads the file at `url` to `path`.
+    /// The file is written to a temporary file next to `path` first, which is renamed once the download succeeded.
+    pub async fn download_to_file(
+        &mut self,
+        url: &str,
+        path: impl AsRef<Path>,
+        options: &DownloadOptions,
+    ) -> Result<Download> {
+        let path = path.as_ref();
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".part");
+
+        let mut file = tokio::fs::File::create(&tmp)
+            .await
+            .map_err(DownloadError::from)?;
+        let res = self.download(url, &mut file, options).await;
+        drop(file);
+
+        match res {
+            Ok(download) => {
+                tokio::fs::rename(&tmp, path)
+                    .await
+                    .map_err(DownloadError::from)?;
+                Ok(download)
+            }
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&tmp).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Downloads the `.Map.Gbx` file of a map into `dir`. The file is named `{mapUID}.Map.Gbx`.
+    /// Returns the path of the file.
+    pub async fn download_map(
+        &mut self,
+        map: &MapInfo,
+        dir: impl AsRef<Path>,
+        options: &DownloadOptions,
+    ) -> Result<PathBuf> {
+        let path = dir.as_ref().join(format!("{}.Map.Gbx", map.map_uid));
+        self.download_to_file(&map.file_url, &path, options).await?;
+
+        Ok(path)
+    }
+
+    /// Downloads the `.Ghost.Gbx` file of a record into `dir`. The file is named `{mapRecordId}.Ghost.Gbx`.
+    /// Returns the path of the file.
+    pub async fn download_ghost(
+        &mut self,
+        record: &MapRecord,
+        dir: impl AsRef<Path>,
+        options: &DownloadOptions,
+    ) -> Result<PathBuf> {
+        let path = dir
+            .as_ref()
+            .join(format!("{}.Ghost.Gbx", record.map_record_id));
+        self.download_to_file(&record.url, &path, options).await?;
+
+        Ok(path)
+    }
+
+    /// Downloads the `.Map.Gbx` files of multiple maps, e.g. of a campaign, into `dir`.
+    /// Up to `concurrency` files are downloaded at the same time.
+    /// Returns the path or the error for every map in the order of `maps`.
+    ///
+    /// The tokens required by the file URLs are refreshed before the downloads start, so the refreshed tokens are kept
+    /// by this client. If that fails the error is returned for every map requiring the tokens.
+    pub async fn download_maps(
+        &mut self,
+        maps: &[MapInfo],
+        dir: impl AsRef<Path>,
+        concurrency: usize,
+        options: &DownloadOptions,
+    ) -> Vec<Result<PathBuf>> {
+        let dir = dir.as_ref();
+
+        let auth_types = maps
+            .iter()
+            .filter_map(|map| default_table().get(&map.file_url))
+            .collect::<HashSet<_>>();
+        let mut auth_errors = HashMap::new();
+        for auth_type in auth_types {
+            let res = self.ensure_auth(auth_type).await;
+            auth_errors.insert(auth_type, res.err().map(|e| e.to_string()));
+        }
+
+        stream::iter(maps)
+            .map(|map| {
+                let mut client = self.clone();
+                let auth_error = default_table()
+                    .get(&map.file_url)
+                    .and_then(|auth_type| auth_errors[&auth_type].clone());
+                async move {
+                    match auth_error {
+                        Some(e) => Err(Error::from(DownloadError::Auth(e))),
+                        None => client.download_map(map, dir, options).await,
+                    }
+                }
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+}
+
+/// Errors while downloading files.
+#[derive(Error, Debug)]
+pub enum DownloadError {
+    #[error("the file is larger than {limit} bytes")]
+    TooLarge { limit: u64 },
+    #[error("checksum mismatch, expected {expected} but got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+    #[error("the file could not be written: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the tokens for the download could not be refreshed: {0}")]
+    Auth(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_support::{response, streamed_response, temp_path, TestServer};
+    use std::sync::Mutex;
+
+    const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    async fn server(streamed: bool) -> TestServer {
+        TestServer::start(move |_| match streamed {
+            true => streamed_response(b"hello"),
+            false => response(200, "application/octet-stream", b"hello"),
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn downloads_into_the_writer() {
+        let server = server(true).await;
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let options = DownloadOptions::default()
+            .sha256(&HELLO_SHA256.to_uppercase())
+            .on_progress({
+                let progress = Arc::clone(&progress);
+                move |p| progress.lock().unwrap().push(p)
+            });
+
+        let mut data = Vec::new();
+        let download = NadeoClient::for_tests()
+            .download(&server.url, &mut data, &options)
+            .await
+            .unwrap();
+
+        assert_eq!(data, b"hello");
+        assert_eq!(
+            download,
+            Download {
+                size: 5,
+                sha256: HELLO_SHA256.to_string(),
+            }
+        );
+        assert_eq!(
+            progress.lock().unwrap().last(),
+            Some(&Progress {
+                downloaded: 5,
+                total: None,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_too_large_content_length() {
+        let server = server(false).await;
+        let options = DownloadOptions::default().max_size(4);
+
+        let mut data = Vec::new();
+        let res = NadeoClient::for_tests()
+            .download(&server.url, &mut data, &options)
+            .await;
+
+        assert!(matches!(
+            res,
+            Err(Error::Download(DownloadError::TooLarge { limit: 4 }))
+        ));
+        // the body is not read
+        assert!(data.is_empty());
+    }
+
+    #[tokio::test]
+    async fn rejects_too_large_streamed_body() {
+        let server = server(true).await;
+        let options = DownloadOptions::default().max_size(4);
+
+        let res = NadeoClient::for_tests()
+            .download(&server.url, &mut Vec::new(), &options)
+            .await;
+
+        assert!(matches!(
+            res,
+            Err(Error::Download(DownloadError::TooLarge { limit: 4 }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_checksum_mismatch() {
+        let server = server(false).await;
+        let options = DownloadOptions::default().sha256("00");
+
+        let res = NadeoClient::for_tests()
+            .download(&server.url, &mut Vec::new(), &options)
+            .await;
+
+        match res {
+            Err(Error::Download(DownloadError::ChecksumMismatch { expected, actual })) => {
+                assert_eq!(expected, "00");
+                assert_eq!(actual, HELLO_SHA256);
+            }
+            other => panic!("unexpected result {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn renames_the_file_after_the_download() {
+        let server = server(false).await;
+        let path = temp_path("download.Map.Gbx");
+
+        NadeoClient::for_tests()
+            .download_to_file(&server.url, &path, &DownloadOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), b"hello");
+        assert!(!temp_path("download.Map.Gbx.part").exists());
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn removes_the_partial_file_after_a_failure() {
+        let server = server(true).await;
+        let path = temp_path("failed.Map.Gbx");
+        let options = DownloadOptions::default().max_size(4);
+
+        let res = NadeoClient::for_tests()
+            .download_to_file(&server.url, &path, &options)
+            .await;
+
+        assert!(res.is_err());
+        assert!(!path.exists());
+        assert!(!temp_path("failed.Map.Gbx.part").exists());
+    }
+}
@@ -10,4 +10,16 @@ pub enum Error {
     ClientBuilderError(#[from] crate::client::client_builder::NadeoClientBuilderError),
     Token(#[from] crate::auth::token::ParseTokenError),
     Request(#[from] crate::request::request_builder::RequestBuilderError),
+    Credentials(#[from] crate::auth::credentials::CredentialError),
+    Auth(#[from] crate::auth::circuit_breaker::AuthError),
+    Pool(#[from] crate::pool::PoolError),
+    Id(#[from] crate::types::id::IdError),
+    Loader(#[from] crate::loader::LoaderError),
+    Download(#[from] crate::download::DownloadError),
+    Export(#[from] crate::export::ExportError),
+    Club(#[from] crate::clubs::ClubError),
+    Competition(#[from] crate::competitions::CompetitionError),
+    Gbx(#[from] crate::gbx::GbxError),
+    GbxRemote(#[from] crate::gbx_remote::GbxRemoteError),
+    Upload(#[from] crate::maps::UploadError),
 }
@@ -0,0 +1,494 @@
+//! Exports the leaderboards of campaigns to CSV or JSON Lines files.
+
+use crate::auth::AuthType;
+use crate::campaigns::Campaign;
+use crate::clubs::ClubCampaign;
+use crate::leaderboards::{MAX_LEADERBOARD_LENGTH, MAX_LEADERBOARD_RECORDS};
+use crate::loader::{load_all, DisplayNames};
+use crate::types::{AccountId, MapUid, RaceTime, ZoneId};
+use crate::{Error, NadeoClient, Result};
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+/// Formats of exported files.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub enum ExportFormat {
+    /// Comma separated values with a header row.
+    #[default]
+    Csv,
+    /// One JSON object per line.
+    JsonLines,
+}
+
+/// A row of an exported file.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct ExportRecord {
+    pub map_uid: MapUid,
+    /// Position in the world leaderboard starting at 1.
+    pub position: u32,
+    pub account_id: AccountId,
+    /// `None` if names are not resolved or the account has no name.
+    pub display_name: Option<String>,
+    /// Time in milliseconds.
+    pub time: RaceTime,
+    pub zone_id: ZoneId,
+    pub zone_name: String,
+}
+
+const CSV_HEADER: [&str; 7] = [
+    "map_uid",
+    "position",
+    "account_id",
+    "display_name",
+    "time",
+    "zone_id",
+    "zone_name",
+];
+
+/// The maps and options of an export.
+///
+/// # Examples
+///
+/// ```rust
+/// # use nadeo_api::NadeoClient;
+/// # use nadeo_api::export::{ExportFormat, RecordExport};
+/// # async fn run(client: &mut NadeoClient) -> nadeo_api::Result<()> {
+/// let campaign = client.official_campaigns(0, 1).await?.campaign_list.remove(0);
+/// let export = RecordExport::from_campaign(&campaign).format(ExportFormat::JsonLines);
+///
+/// // continues where it stopped if it was interrupted
+/// let summary = client.export_records(&export, "records.jsonl").await?;
+/// println!("exported {} records", summary.records);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RecordExport {
+    maps: Vec<MapUid>,
+    format: ExportFormat,
+    resolve_names: bool,
+    max_records_per_map: Option<u32>,
+}
+
+impl RecordExport {
+    /// Exports the given maps as CSV with display names.
+    pub fn new(maps: Vec<MapUid>) -> Self {
+        Self {
+            maps,
+            format: ExportFormat::default(),
+            resolve_names: true,
+            max_records_per_map: None,
+        }
+    }
+
+    /// Exports the maps of a seasonal or Weekly Shorts campaign.
+    pub fn from_campaign(campaign: &Campaign) -> Self {
+        Self::new(campaign.map_uids())
+    }
+
+    pub fn from_club_campaign(campaign: &ClubCampaign) -> Self {
+        let mut playlist = campaign.playlist.iter().collect::<Vec<_>>();
+        playlist.sort_by_key(|map| map.position);
+
+        Self::new(
+            playlist
+                .into_iter()
+                .map(|map| map.map_uid.clone())
+                .collect(),
+        )
+    }
+
+    pub fn format(mut self, format: ExportFormat) -> Self {
+        self.format = format;
+
+        self
+    }
+
+    /// Whether display names are requested. Requires [`AuthType::OAuth`]. Enabled by default.
+    ///
+    /// [`AuthType::OAuth`]: crate::auth::AuthType::OAuth
+    pub fn resolve_names(mut self, resolve_names: bool) -> Self {
+        self.resolve_names = resolve_names;
+
+        self
+    }
+
+    /// Only exports the best records of every map. By default the whole leaderboard is exported, which is limited
+    /// to the best [`MAX_LEADERBOARD_RECORDS`] records by the API.
+    pub fn max_records_per_map(mut self, max_records: u32) -> Self {
+        self.max_records_per_map = Some(max_records);
+
+        self
+    }
+}
+
+/// Result of a finished export.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ExportSummary {
+    pub maps: usize,
+    pub records: u64,
+}
+
+/// The progress of an export, saved next to the file after every page.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct Progress {
+    maps: Vec<MapUid>,
+    format: ExportFormat,
+    resolve_names: bool,
+    max_records_per_map: Option<u32>,
+    map_index: usize,
+    offset: u32,
+    /// Length of the file after the last completed page. Anything after it is discarded when resuming.
+    bytes: u64,
+    records: u64,
+}
+
+impl Progress {
+    /// Whether the progress belongs to an export with the same maps and options.
+    fn matches(&self, export: &RecordExport) -> bool {
+        self.maps == export.maps
+            && self.format == export.format
+            && self.resolve_names == export.resolve_names
+            && self.max_records_per_map == export.max_records_per_map
+    }
+
+    async fn save(&self, path: &Path) -> std::result::Result<(), ExportError> {
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let data = serde_json::to_vec(self).expect("progress can be serialized");
+        tokio::fs::write(&tmp, data).await?;
+        tokio::fs::rename(&tmp, path).await?;
+
+        Ok(())
+    }
+}
+
+fn progress_path(path: &Path) -> PathBuf {
+    let mut progress = OsString::from(path.as_os_str());
+    progress.push(".progress");
+
+    PathBuf::from(progress)
+}
+
+impl NadeoClient {
+    /// Exports the world leaderboards of the maps to `path`.
+    ///
+    /// The leaderboards are requested page by page with [`AuthType::NadeoLiveServices`]. After every page the progress
+    /// is saved to `<path>.progress`, so an interrupted export continues where it stopped when it is started again
+    /// with the same maps and options. If the file was removed in the meantime, the export starts again.
+    /// The progress file is removed when the export is finished.
+    /// At most the best [`MAX_LEADERBOARD_RECORDS`] records of a map are available.
+    ///
+    /// [`AuthType::NadeoLiveServices`]: crate::auth::AuthType::NadeoLiveServices
+    pub async fn export_records(
+        &mut self,
+        export: &RecordExport,
+        path: impl AsRef<Path>,
+    ) -> Result<ExportSummary> {
+        let path = path.as_ref();
+        let progress_path = progress_path(path);
+
+        let saved = match tokio::fs::read(&progress_path).await {
+            Ok(data) => Some(
+                serde_json::from_slice::<Progress>(&data)
+                    .map_err(|e| ExportError::InvalidProgress(e.to_string()))?,
+            ),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(Error::from(ExportError::from(e))),
+        };
+
+        let resumed = match saved {
+            Some(progress) => {
+                if !progress.matches(export) {
+                    return Err(Error::from(ExportError::ProgressMismatch));
+                }
+                match resume_file(path, progress.bytes).await {
+                    Ok(file) => Some((file, progress)),
+                    // the file was removed, so the export starts again
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                    Err(e) => return Err(Error::from(ExportError::from(e))),
+                }
+            }
+            None => None,
+        };
+
+        let (mut file, mut progress) = match resumed {
+            Some(resumed) => resumed,
+            None => {
+                let mut file = tokio::fs::File::create(path)
+                    .await
+                    .map_err(ExportError::from)?;
+                let header = match export.format {
+                    ExportFormat::Csv => csv_rows(&[], true),
+                    ExportFormat::JsonLines => Vec::new(),
+                };
+                file.write_all(&header).await.map_err(ExportError::from)?;
+                file.flush().await.map_err(ExportError::from)?;
+
+                let progress = Progress {
+                    maps: export.maps.clone(),
+                    format: export.format,
+                    resolve_names: export.resolve_names,
+                    max_records_per_map: export.max_records_per_map,
+                    map_index: 0,
+                    offset: 0,
+                    bytes: header.len() as u64,
+                    records: 0,
+                };
+                progress.save(&progress_path).await?;
+
+                (file, progress)
+            }
+        };
+
+        while let Some(map_uid) = export.maps.get(progress.map_index) {
+            let limit = export
+                .max_records_per_map
+                .unwrap_or(MAX_LEADERBOARD_RECORDS)
+                .min(MAX_LEADERBOARD_RECORDS);
+            let length = MAX_LEADERBOARD_LENGTH.min(limit.saturating_sub(progress.offset));
+            let top = match length {
+                0 => Vec::new(),
+                _ => self
+                    .map_leaderboard(map_uid, progress.offset, length, true)
+                    .await?
+                    .tops
+                    .into_iter()
+                    .next()
+                    .map(|world| world.top)
+                    .unwrap_or_default(),
+            };
+
+            let names = match export.resolve_names && !top.is_empty() {
+                true => {
+                    let account_ids = top
+                        .iter()
+                        .map(|record| record.account_id)
+                        .collect::<Vec<_>>();
+                    // the names are requested with clones of the client, which don't keep refreshed tokens
+                    self.ensure_auth(AuthType::OAuth).await?;
+                    load_all(self, &DisplayNames, &account_ids).await?
+                }
+                false => Default::default(),
+            };
+            let rows = top
+                .into_iter()
+                .map(|record| ExportRecord {
+                    map_uid: map_uid.clone(),
+                    position: record.position,
+                    display_name: names.get(&record.account_id).cloned(),
+                    account_id: record.account_id,
+                    time: record.score,
+                    zone_id: record.zone_id,
+                    zone_name: record.zone_name,
+                })
+                .collect::<Vec<_>>();
+
+            let data = match export.format {
+                ExportFormat::Csv => csv_rows(&rows, false),
+                ExportFormat::JsonLines => json_lines(&rows),
+            };
+            file.write_all(&data).await.map_err(ExportError::from)?;
+            file.flush().await.map_err(ExportError::from)?;
+
+            progress.bytes += data.len() as u64;
+            progress.records += rows.len() as u64;
+            match rows.len() < length as usize || length == 0 {
+                true => {
+                    progress.map_index += 1;
+                    progress.offset = 0;
+                }
+                false => progress.offset += length,
+            }
+            progress.save(&progress_path).await?;
+        }
+
+        tokio::fs::remove_file(&progress_path)
+            .await
+            .map_err(ExportError::from)?;
+
+        Ok(ExportSummary {
+            maps: export.maps.len(),
+            records: progress.records,
+        })
+    }
+}
+
+/// Opens the file of an interrupted export and discards anything after the last completed page.
+async fn resume_file(path: &Path, bytes: u64) -> std::io::Result<tokio::fs::File> {
+    let mut file = tokio::fs::OpenOptions::new().write(true).open(path).await?;
+    file.set_len(bytes).await?;
+    file.seek(std::io::SeekFrom::End(0)).await?;
+
+    Ok(file)
+}
+
+fn csv_rows(rows: &[ExportRecord], header: bool) -> Vec<u8> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    if header {
+        writer
+            .write_record(CSV_HEADER)
+            .expect("writing to memory can't fail");
+    }
+    for row in rows {
+        writer.serialize(row).expect("rows can be serialized");
+    }
+
+    writer.into_inner().expect("writing to memory can't fail")
+}
+
+fn json_lines(rows: &[ExportRecord]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for row in rows {
+        serde_json::to_writer(&mut data, row).expect("rows can be serialized");
+        data.push(b'\n');
+    }
+
+    data
+}
+
+/// Errors of record exports.
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error("the progress file belongs to an export with different maps or options")]
+    ProgressMismatch,
+    #[error("the progress file is invalid: {0}")]
+    InvalidProgress(String),
+    #[error("the file could not be written: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_support::temp_path;
+    use uuid::Uuid;
+
+    fn record(display_name: Option<&str>) -> ExportRecord {
+        ExportRecord {
+            map_uid: "map".parse().unwrap(),
+            position: 1,
+            account_id: AccountId::new(Uuid::from_u128(1)),
+            display_name: display_name.map(str::to_string),
+            time: RaceTime::from_millis(45_123),
+            zone_id: ZoneId::new(Uuid::from_u128(2)),
+            zone_name: "France".to_string(),
+        }
+    }
+
+    fn progress(export: &RecordExport) -> Progress {
+        Progress {
+            maps: export.maps.clone(),
+            format: export.format,
+            resolve_names: export.resolve_names,
+            max_records_per_map: export.max_records_per_map,
+            map_index: export.maps.len(),
+            offset: 0,
+            bytes: 0,
+            records: 0,
+        }
+    }
+
+    #[test]
+    fn csv_has_a_header_and_empty_names() {
+        let data = csv_rows(&[record(Some("Player")), record(None)], true);
+
+        assert_eq!(
+            String::from_utf8(data).unwrap(),
+            "map_uid,position,account_id,display_name,time,zone_id,zone_name\n\
+             map,1,00000000-0000-0000-0000-000000000001,Player,45123,00000000-0000-0000-0000-000000000002,France\n\
+             map,1,00000000-0000-0000-0000-000000000001,,45123,00000000-0000-0000-0000-000000000002,France\n"
+        );
+        assert!(!csv_rows(&[record(None)], false).starts_with(b"map_uid"));
+    }
+
+    #[test]
+    fn json_lines_has_one_object_per_line() {
+        let data = String::from_utf8(json_lines(&[record(Some("Player")), record(None)])).unwrap();
+
+        let lines = data.split_terminator('\n').collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(data.ends_with('\n'));
+        assert_eq!(
+            serde_json::from_str::<ExportRecord>(lines[0]).unwrap(),
+            record(Some("Player"))
+        );
+        assert_eq!(
+            serde_json::from_str::<ExportRecord>(lines[1]).unwrap(),
+            record(None)
+        );
+    }
+
+    #[test]
+    fn progress_only_matches_the_same_options() {
+        let export = RecordExport::new(vec!["a".parse().unwrap()]);
+        let progress = progress(&export);
+        assert!(progress.matches(&export));
+
+        let changed = [
+            RecordExport::new(vec!["b".parse().unwrap()]),
+            export.clone().format(ExportFormat::JsonLines),
+            export.clone().resolve_names(false),
+            export.clone().max_records_per_map(100),
+        ];
+        for export in &changed {
+            assert!(!progress.matches(export), "{export:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn resuming_discards_data_after_the_last_page() {
+        let path = temp_path("resume.csv");
+        let export = RecordExport::new(vec!["a".parse().unwrap()]);
+        let mut progress = progress(&export);
+        progress.bytes = 6;
+        progress.records = 3;
+        progress.save(&progress_path(&path)).await.unwrap();
+        tokio::fs::write(&path, b"header,partial page")
+            .await
+            .unwrap();
+
+        // every map is done, so nothing is requested
+        let summary = NadeoClient::for_tests()
+            .export_records(&export, &path)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            summary,
+            ExportSummary {
+                maps: 1,
+                records: 3
+            }
+        );
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), b"header");
+        assert!(!progress_path(&path).exists());
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn missing_file_starts_again() {
+        let path = temp_path("missing.csv");
+        let export = RecordExport::new(Vec::new());
+        let mut progress = progress(&export);
+        progress.bytes = 100;
+        progress.records = 10;
+        progress.save(&progress_path(&path)).await.unwrap();
+
+        let summary = NadeoClient::for_tests()
+            .export_records(&export, &path)
+            .await
+            .unwrap();
+
+        assert_eq!(summary.records, 0);
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), csv_rows(&[], true));
+        assert!(!progress_path(&path).exists());
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+}
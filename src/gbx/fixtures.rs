@@ -0,0 +1,101 @@
+//! Builders for small Gbx files used by the tests.
+
+/// Writes little-endian values like they are stored in Gbx files.
+#[derive(Default)]
+pub(crate) struct Writer {
+    pub(crate) data: Vec<u8>,
+    lookback_started: bool,
+}
+
+impl Writer {
+    pub(crate) fn u8(&mut self, val: u8) -> &mut Self {
+        self.data.push(val);
+        self
+    }
+
+    pub(crate) fn u32(&mut self, val: u32) -> &mut Self {
+        self.data.extend_from_slice(&val.to_le_bytes());
+        self
+    }
+
+    pub(crate) fn i32(&mut self, val: i32) -> &mut Self {
+        self.data.extend_from_slice(&val.to_le_bytes());
+        self
+    }
+
+    pub(crate) fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.data.extend_from_slice(bytes);
+        self
+    }
+
+    pub(crate) fn string(&mut self, string: &str) -> &mut Self {
+        self.u32(string.len() as u32).bytes(string.as_bytes())
+    }
+
+    /// Writes a new lookback string, or an empty one if `string` is empty.
+    pub(crate) fn lookback_string(&mut self, string: &str) -> &mut Self {
+        if !self.lookback_started {
+            self.lookback_started = true;
+            self.u32(3);
+        }
+        match string.is_empty() {
+            true => self.u32(u32::MAX),
+            false => self.u32(0x4000_0000).string(string),
+        }
+    }
+
+    pub(crate) fn finish(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.data)
+    }
+}
+
+/// Builds a Gbx file with the given header chunks and body. `body_compressed` only sets the flag in the header.
+pub(crate) fn gbx(
+    class_id: u32,
+    body_compressed: bool,
+    chunks: &[(u32, Vec<u8>)],
+    body: &[u8],
+) -> Vec<u8> {
+    let mut user_data = Writer::default();
+    user_data.u32(chunks.len() as u32);
+    for (id, data) in chunks {
+        user_data.u32(*id).u32(data.len() as u32);
+    }
+    for (_, data) in chunks {
+        user_data.bytes(data);
+    }
+    let user_data = user_data.finish();
+
+    let compression = if body_compressed { b'C' } else { b'U' };
+    Writer::default()
+        .bytes(b"GBX")
+        .bytes(&6u16.to_le_bytes())
+        .bytes(&[b'B', b'U', compression, b'R'])
+        .u32(class_id)
+        .u32(user_data.len() as u32)
+        .bytes(&user_data)
+        // node count, external nodes
+        .u32(1)
+        .u32(0)
+        .bytes(body)
+        .finish()
+}
+
+/// Encodes `data` as a single LZO1X literal run followed by the end marker.
+pub(crate) fn lzo_literals(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    if data.len() <= 238 {
+        out.push(17 + data.len() as u8);
+    } else {
+        // 18 literals plus the length encoded as zero bytes adding 255 each and a final non-zero byte
+        let extra = data.len() - 18;
+        let zeros = (extra - 1) / 255;
+        out.push(0);
+        out.extend(std::iter::repeat_n(0, zeros));
+        out.push((extra - zeros * 255) as u8);
+    }
+    out.extend_from_slice(data);
+    out.extend_from_slice(&[0x11, 0, 0]);
+
+    out
+}
@@ -0,0 +1,334 @@
+use crate::gbx::reader::Reader;
+use crate::gbx::{lzo, GbxError, Header};
+use crate::records::{MapRecord, RecordScore};
+use crate::types::RaceTime;
+use std::path::Path;
+
+const GHOST_CLASS_ID: u32 = 0x0309_2000;
+const CHUNK_RACE_TIME: u32 = 0x0309_2005;
+const CHUNK_RESPAWNS: u32 = 0x0309_2008;
+const CHUNK_STUNT_SCORE: u32 = 0x0309_200A;
+const CHUNK_CHECKPOINTS: u32 = 0x0309_200B;
+/// Marks the end of the chunks of a node.
+const END_OF_NODE: u32 = 0xFACA_DE01;
+/// Marks skippable chunks, `PIKS` in little-endian.
+const SKIPPABLE: u32 = 0x534B_4950;
+
+/// A checkpoint of a [`Ghost`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Checkpoint {
+    /// Time since the start. `None` if the checkpoint was not reached.
+    pub time: Option<RaceTime>,
+    pub stunt_score: i32,
+}
+
+/// A field of a [`Ghost`] which does not match the [`MapRecord`] of the API.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum GhostField {
+    Time,
+    RespawnCount,
+    /// The time of the last checkpoint is not the finish time.
+    Checkpoints,
+}
+
+/// Information read from a `.Ghost.Gbx` file, e.g. of a [`MapRecord`].
+///
+/// Only the chunks at the start of the body which can be skipped are read, up to and including the checkpoints.
+/// The input data of the run is not parsed.
+///
+/// # Examples
+///
+/// ```rust
+/// # use nadeo_api::gbx::Ghost;
+/// # use nadeo_api::records::MapRecord;
+/// # async fn run(record: MapRecord) -> nadeo_api::Result<()> {
+/// let ghost = Ghost::from_file(format!("{}.Ghost.Gbx", record.map_record_id)).await?;
+/// for split in ghost.splits() {
+///     println!("{split}");
+/// }
+/// assert!(ghost.mismatches(&record).is_empty());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Ghost {
+    pub race_time: Option<RaceTime>,
+    pub respawn_count: Option<u32>,
+    pub stunt_score: Option<i32>,
+    /// Every checkpoint including the finish.
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+impl Ghost {
+    /// Parses a `.Ghost.Gbx` file.
+    pub fn parse(data: &[u8]) -> Result<Self, GbxError> {
+        let header = Header::parse(data)?;
+        if header.class_id != GHOST_CLASS_ID {
+            return Err(GbxError::UnexpectedClass {
+                expected: GHOST_CLASS_ID,
+                actual: header.class_id,
+            });
+        }
+
+        let mut r = Reader::new(header.rest);
+        // node count
+        r.skip(4)?;
+        if r.u32()? != 0 {
+            return Err(GbxError::ExternalNodes);
+        }
+
+        let mut ghost = Self {
+            race_time: None,
+            respawn_count: None,
+            stunt_score: None,
+            checkpoints: Vec::new(),
+        };
+        if header.body_compressed {
+            let size = r.u32()? as usize;
+            let compressed_size = r.u32()? as usize;
+            let body = lzo::decompress(r.bytes(compressed_size)?, size)?;
+            ghost.read_body(&mut Reader::new(&body))?;
+        } else {
+            ghost.read_body(&mut r)?;
+        }
+
+        Ok(ghost)
+    }
+
+    /// Reads and parses a `.Ghost.Gbx` file.
+    pub async fn from_file(path: impl AsRef<Path>) -> Result<Self, GbxError> {
+        Self::parse(&tokio::fs::read(path).await?)
+    }
+
+    /// Returns the finish time. Falls back to the time of the last checkpoint.
+    pub fn finish_time(&self) -> Option<RaceTime> {
+        self.race_time
+            .or_else(|| self.checkpoints.last().and_then(|cp| cp.time))
+    }
+
+    /// Returns the time between consecutive checkpoints. Stops at the first checkpoint which was not reached.
+    pub fn splits(&self) -> Vec<RaceTime> {
+        let mut last = RaceTime::ZERO;
+        self.checkpoints
+            .iter()
+            .map_while(|cp| cp.time)
+            .map(|time| {
+                let split = time - last;
+                last = time;
+                split
+            })
+            .collect()
+    }
+
+    /// Returns the score of the ghost as it is stored for records. `None` if the ghost did not finish.
+    pub fn record_score(&self) -> Option<RecordScore> {
+        Some(RecordScore {
+            time: self.finish_time()?,
+            score: self.stunt_score.unwrap_or_default() as i64,
+            respawn_count: self.respawn_count.unwrap_or_default(),
+        })
+    }
+
+    /// Compares the ghost with the [`MapRecord`] of the API and returns every field which does not match.
+    pub fn mismatches(&self, record: &MapRecord) -> Vec<GhostField> {
+        let mut mismatches = Vec::new();
+        if self.finish_time() != Some(record.record_score.time) {
+            mismatches.push(GhostField::Time);
+        }
+        if self
+            .respawn_count
+            .is_some_and(|count| count != record.record_score.respawn_count)
+        {
+            mismatches.push(GhostField::RespawnCount);
+        }
+        if self
+            .checkpoints
+            .last()
+            .is_some_and(|cp| cp.time != Some(record.record_score.time))
+        {
+            mismatches.push(GhostField::Checkpoints);
+        }
+
+        mismatches
+    }
+
+    /// Reads the chunks of the body until the first chunk which can not be skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GbxError::UnskippableChunk`] if such a chunk comes before the checkpoints.
+    fn read_body(&mut self, r: &mut Reader) -> Result<(), GbxError> {
+        let mut checkpoints_read = false;
+        loop {
+            let id = r.u32()?;
+            if id == END_OF_NODE {
+                return Ok(());
+            }
+            if r.u32()? != SKIPPABLE {
+                return match checkpoints_read {
+                    true => Ok(()),
+                    false => Err(GbxError::UnskippableChunk(id)),
+                };
+            }
+            let size = r.u32()? as usize;
+            let mut chunk = Reader::new(r.bytes(size)?);
+
+            match id {
+                CHUNK_RACE_TIME => self.race_time = chunk.time()?,
+                CHUNK_RESPAWNS => self.respawn_count = Some(chunk.u32()?),
+                CHUNK_STUNT_SCORE => self.stunt_score = Some(chunk.i32()?),
+                CHUNK_CHECKPOINTS => {
+                    let count = chunk.u32()?;
+                    if size != 4 + count as usize * 8 {
+                        return Err(GbxError::InvalidChunk(id));
+                    }
+                    self.checkpoints = (0..count)
+                        .map(|_| {
+                            Ok(Checkpoint {
+                                time: chunk.time()?,
+                                stunt_score: chunk.i32()?,
+                            })
+                        })
+                        .collect::<Result<_, GbxError>>()?;
+                    checkpoints_read = true;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gbx::fixtures::{gbx, lzo_literals, Writer};
+
+    fn chunk(w: &mut Writer, id: u32, data: &[u8]) {
+        w.u32(id).u32(SKIPPABLE).u32(data.len() as u32).bytes(data);
+    }
+
+    fn body() -> Vec<u8> {
+        let mut w = Writer::default();
+        chunk(&mut w, CHUNK_RACE_TIME, &45_123i32.to_le_bytes());
+        chunk(&mut w, CHUNK_RESPAWNS, &2u32.to_le_bytes());
+        chunk(&mut w, CHUNK_STUNT_SCORE, &0i32.to_le_bytes());
+        let checkpoints = Writer::default()
+            .u32(3)
+            .i32(12_000)
+            .i32(0)
+            .i32(30_500)
+            .i32(0)
+            .i32(45_123)
+            .i32(0)
+            .finish();
+        chunk(&mut w, CHUNK_CHECKPOINTS, &checkpoints);
+        // the input data which is not skippable
+        w.u32(0x0309_2019).u32(1);
+
+        w.finish()
+    }
+
+    fn compressed(body: &[u8]) -> Vec<u8> {
+        let data = lzo_literals(body);
+        Writer::default()
+            .u32(body.len() as u32)
+            .u32(data.len() as u32)
+            .bytes(&data)
+            .finish()
+    }
+
+    #[test]
+    fn parses_compressed_ghost() {
+        let ghost = Ghost::parse(&gbx(GHOST_CLASS_ID, true, &[], &compressed(&body()))).unwrap();
+
+        assert_eq!(ghost.race_time, Some(RaceTime::from_millis(45_123)));
+        assert_eq!(ghost.respawn_count, Some(2));
+        assert_eq!(ghost.stunt_score, Some(0));
+        assert_eq!(
+            ghost.splits(),
+            vec![
+                RaceTime::from_millis(12_000),
+                RaceTime::from_millis(18_500),
+                RaceTime::from_millis(14_623),
+            ]
+        );
+        assert_eq!(
+            ghost.record_score(),
+            Some(RecordScore {
+                time: RaceTime::from_millis(45_123),
+                score: 0,
+                respawn_count: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn parses_uncompressed_ghost() {
+        let compressed = Ghost::parse(&gbx(GHOST_CLASS_ID, true, &[], &compressed(&body())));
+        let uncompressed = Ghost::parse(&gbx(GHOST_CLASS_ID, false, &[], &body()));
+
+        assert_eq!(compressed.unwrap(), uncompressed.unwrap());
+    }
+
+    #[test]
+    fn unreached_checkpoints_end_the_splits() {
+        let mut w = Writer::default();
+        let checkpoints = Writer::default()
+            .u32(2)
+            .i32(12_000)
+            .i32(0)
+            .i32(-1)
+            .i32(0)
+            .finish();
+        chunk(&mut w, CHUNK_CHECKPOINTS, &checkpoints);
+        w.u32(END_OF_NODE);
+
+        let ghost = Ghost::parse(&gbx(GHOST_CLASS_ID, false, &[], &w.finish())).unwrap();
+        assert_eq!(ghost.race_time, None);
+        assert_eq!(ghost.checkpoints[1].time, None);
+        assert_eq!(ghost.splits(), vec![RaceTime::from_millis(12_000)]);
+        assert_eq!(ghost.finish_time(), None);
+    }
+
+    #[test]
+    fn rejects_unskippable_chunk_before_checkpoints() {
+        let mut w = Writer::default();
+        chunk(&mut w, CHUNK_RACE_TIME, &45_123i32.to_le_bytes());
+        w.u32(0x0309_2019).u32(1);
+
+        assert!(matches!(
+            Ghost::parse(&gbx(GHOST_CLASS_ID, false, &[], &w.finish())),
+            Err(GbxError::UnskippableChunk(0x0309_2019))
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_checkpoint_chunk() {
+        let mut w = Writer::default();
+        chunk(
+            &mut w,
+            CHUNK_CHECKPOINTS,
+            &Writer::default().u32(2).i32(0).finish(),
+        );
+
+        assert!(matches!(
+            Ghost::parse(&gbx(GHOST_CLASS_ID, false, &[], &w.finish())),
+            Err(GbxError::InvalidChunk(CHUNK_CHECKPOINTS))
+        ));
+    }
+
+    #[test]
+    fn rejects_oversized_body() {
+        let data = lzo_literals(&body());
+        let body = Writer::default()
+            .u32(u32::MAX)
+            .u32(data.len() as u32)
+            .bytes(&data)
+            .finish();
+
+        assert!(matches!(
+            Ghost::parse(&gbx(GHOST_CLASS_ID, true, &[], &body)),
+            Err(GbxError::BodyTooLarge(size)) if size == u32::MAX as usize
+        ));
+    }
+}
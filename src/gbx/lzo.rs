@@ -0,0 +1,239 @@
+//! Decompression of LZO1X which is used for the body of Gbx files.
+
+use crate::gbx::GbxError;
+
+/// Distance offset of 3 byte matches following a literal run.
+const M2_MAX_OFFSET: usize = 0x0800;
+/// Upper limit for the decompressed size. Protects against allocating huge buffers for invalid files.
+const MAX_DECOMPRESSED_SIZE: usize = 64 * 1024 * 1024;
+
+struct Input<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Input<'_> {
+    fn byte(&mut self) -> Result<usize, GbxError> {
+        let byte = *self
+            .data
+            .get(self.pos)
+            .ok_or(GbxError::InvalidCompression)?;
+        self.pos += 1;
+
+        Ok(byte as usize)
+    }
+
+    fn le16(&mut self) -> Result<usize, GbxError> {
+        Ok(self.byte()? | self.byte()? << 8)
+    }
+
+    /// Reads the extension of a length which is encoded as zero bytes, each adding 255, followed by a non-zero byte.
+    fn long_length(&mut self) -> Result<usize, GbxError> {
+        let mut len = 0;
+        while self.data.get(self.pos) == Some(&0) {
+            len += 255;
+            self.pos += 1;
+        }
+
+        Ok(len + self.byte()?)
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&[u8], GbxError> {
+        let end = self.pos + len;
+        let bytes = self
+            .data
+            .get(self.pos..end)
+            .ok_or(GbxError::InvalidCompression)?;
+        self.pos = end;
+
+        Ok(bytes)
+    }
+}
+
+struct Output {
+    data: Vec<u8>,
+    size: usize,
+}
+
+impl Output {
+    fn literals(&mut self, input: &mut Input, len: usize) -> Result<(), GbxError> {
+        if self.data.len() + len > self.size {
+            return Err(GbxError::InvalidCompression);
+        }
+        self.data.extend_from_slice(input.bytes(len)?);
+
+        Ok(())
+    }
+
+    /// Copies `len` bytes starting `distance` bytes before the end. The ranges may overlap.
+    fn copy_match(&mut self, distance: usize, len: usize) -> Result<(), GbxError> {
+        if distance > self.data.len() || self.data.len() + len > self.size {
+            return Err(GbxError::InvalidCompression);
+        }
+        let start = self.data.len() - distance;
+        for i in start..start + len {
+            self.data.push(self.data[i]);
+        }
+
+        Ok(())
+    }
+}
+
+/// Decompresses LZO1X compressed `data` into `size` bytes.
+pub(crate) fn decompress(data: &[u8], size: usize) -> Result<Vec<u8>, GbxError> {
+    if size > MAX_DECOMPRESSED_SIZE {
+        return Err(GbxError::BodyTooLarge(size));
+    }
+    let mut input = Input { data, pos: 0 };
+    let mut out = Output {
+        data: Vec::with_capacity(size),
+        size,
+    };
+
+    // number of literals copied after the last instruction, 4 after a literal run
+    let mut state = 0;
+    if data.first().is_some_and(|first| *first > 17) {
+        let len = input.byte()? - 17;
+        out.literals(&mut input, len)?;
+        state = len.min(4);
+    }
+
+    loop {
+        let t = input.byte()?;
+        let (distance, len, literals) = if t < 16 {
+            match state {
+                0 => {
+                    let len = if t == 0 { 15 + input.long_length()? } else { t };
+                    out.literals(&mut input, len + 3)?;
+                    state = 4;
+                    continue;
+                }
+                1..=3 => (1 + (t >> 2) + (input.byte()? << 2), 2, t & 3),
+                _ => (
+                    1 + M2_MAX_OFFSET + (t >> 2) + (input.byte()? << 2),
+                    3,
+                    t & 3,
+                ),
+            }
+        } else if t >= 64 {
+            (
+                1 + ((t >> 2) & 7) + (input.byte()? << 3),
+                (t >> 5) + 1,
+                t & 3,
+            )
+        } else if t >= 32 {
+            let len = match t & 31 {
+                0 => 31 + input.long_length()?,
+                len => len,
+            };
+            let next = input.le16()?;
+            (1 + (next >> 2), len + 2, next & 3)
+        } else {
+            let len = match t & 7 {
+                0 => 7 + input.long_length()?,
+                len => len,
+            };
+            let next = input.le16()?;
+            let distance = ((t & 8) << 11) + (next >> 2);
+            if distance == 0 {
+                break;
+            }
+            (distance + 0x4000, len + 2, next & 3)
+        };
+
+        out.copy_match(distance, len)?;
+        out.literals(&mut input, literals)?;
+        state = literals;
+    }
+
+    if out.data.len() != size {
+        return Err(GbxError::InvalidCompression);
+    }
+
+    Ok(out.data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gbx::fixtures::lzo_literals;
+
+    #[test]
+    fn literal_run() {
+        let data = [&[17 + 5][..], b"hello", &[0x11, 0, 0]].concat();
+
+        assert_eq!(decompress(&data, 5).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn literal_run_after_instruction() {
+        // 1 + 3 literals
+        let data = [&[0x01][..], b"wxyz", &[0x11, 0, 0]].concat();
+
+        assert_eq!(decompress(&data, 4).unwrap(), b"wxyz");
+    }
+
+    #[test]
+    fn long_literal_run() {
+        let input = (0..600).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+
+        assert_eq!(
+            decompress(&lzo_literals(&input), input.len()).unwrap(),
+            input
+        );
+    }
+
+    #[test]
+    fn overlapping_match() {
+        // "abc", then a match of 9 bytes at distance 3
+        let data = [&[17 + 3][..], b"abc", &[32 | 7, 2 << 2, 0], &[0x11, 0, 0]].concat();
+
+        assert_eq!(decompress(&data, 12).unwrap(), b"abcabcabcabc");
+    }
+
+    #[test]
+    fn short_match_with_trailing_literals() {
+        // "abcd", then a match of 4 bytes at distance 4 followed by 2 literals
+        let data = [
+            &[17 + 4][..],
+            b"abcd",
+            &[0x60 | 3 << 2 | 2, 0],
+            b"xy",
+            &[0x11, 0, 0],
+        ]
+        .concat();
+
+        assert_eq!(decompress(&data, 10).unwrap(), b"abcdabcdxy");
+    }
+
+    #[test]
+    fn rejects_invalid_data() {
+        let valid = [&[17 + 5][..], b"hello", &[0x11, 0, 0]].concat();
+
+        // wrong size
+        assert!(matches!(
+            decompress(&valid, 4),
+            Err(GbxError::InvalidCompression)
+        ));
+        assert!(matches!(
+            decompress(&valid, 6),
+            Err(GbxError::InvalidCompression)
+        ));
+        // missing end marker
+        assert!(matches!(
+            decompress(&valid[..6], 5),
+            Err(GbxError::InvalidCompression)
+        ));
+        // size of a crafted file
+        assert!(matches!(
+            decompress(&valid, u32::MAX as usize),
+            Err(GbxError::BodyTooLarge(_))
+        ));
+        // match before the start of the output
+        let data = [&[17 + 1][..], b"a", &[32 | 7, 2 << 2, 0], &[0x11, 0, 0]].concat();
+        assert!(matches!(
+            decompress(&data, 10),
+            Err(GbxError::InvalidCompression)
+        ));
+    }
+}
@@ -0,0 +1,466 @@
+use crate::gbx::reader::Reader;
+use crate::gbx::{GbxError, Header};
+use crate::maps::MapInfo;
+use crate::types::{AccountId, MedalTimes};
+use std::io::Read;
+use std::path::Path;
+use tokio::io::AsyncRead;
+
+const MAP_CLASS_ID: u32 = 0x0304_3000;
+const CHUNK_DESCRIPTION: u32 = 0x0304_3002;
+const CHUNK_COMMON: u32 = 0x0304_3003;
+const CHUNK_XML: u32 = 0x0304_3005;
+const CHUNK_THUMBNAIL: u32 = 0x0304_3007;
+const CHUNK_AUTHOR: u32 = 0x0304_3008;
+
+/// Metadata read from the header of a `.Map.Gbx` file.
+///
+/// # Examples
+///
+/// Checks a downloaded map against the information from the API.
+/// ```rust
+/// # use nadeo_api::gbx::MapHeader;
+/// # use nadeo_api::maps::MapInfo;
+/// # async fn run(map: MapInfo) -> nadeo_api::Result<()> {
+/// let header = MapHeader::from_file(format!("{}.Map.Gbx", map.map_uid)).await?;
+/// assert!(header.mismatches(&map).is_empty());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MapHeader {
+    /// The *mapUID* of the map.
+    pub uid: String,
+    /// Name of the map including formatting codes.
+    pub name: String,
+    /// Login of the author. Use [`MapHeader::author_account_id`] for the accountID.
+    pub author_login: String,
+    /// Display name of the author. Only present in maps of newer games.
+    pub author_nickname: Option<String>,
+    /// Zone of the author, e.g. `World|Europe|Germany`.
+    pub author_zone: Option<String>,
+    /// The environment, e.g. `Stadium`.
+    pub collection: String,
+    pub map_type: String,
+    pub map_style: String,
+    /// The medal times. `None` if the map is not validated.
+    pub medal_times: Option<MedalTimes>,
+    pub checkpoint_count: Option<u32>,
+    pub lap_count: Option<u32>,
+    pub created_with_simple_editor: bool,
+    pub created_with_gamepad_editor: bool,
+    /// The thumbnail as a JPEG. The image is stored upside down.
+    pub thumbnail: Option<Vec<u8>>,
+    pub comments: Option<String>,
+    /// XML summary of the map used by the community.
+    pub xml: Option<String>,
+}
+
+/// A field of a [`MapHeader`] which does not match the [`MapInfo`] of the API.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum MapField {
+    Uid,
+    Name,
+    Author,
+    MedalTimes,
+    Collection,
+    MapType,
+    MapStyle,
+}
+
+impl MapHeader {
+    /// Parses the header of a `.Map.Gbx` file. The body is not read.
+    pub fn parse(data: &[u8]) -> Result<Self, GbxError> {
+        let header = Header::parse(data)?;
+        if header.class_id != MAP_CLASS_ID {
+            return Err(GbxError::UnexpectedClass {
+                expected: MAP_CLASS_ID,
+                actual: header.class_id,
+            });
+        }
+
+        let mut map = Self {
+            uid: String::new(),
+            name: String::new(),
+            author_login: String::new(),
+            author_nickname: None,
+            author_zone: None,
+            collection: String::new(),
+            map_type: String::new(),
+            map_style: String::new(),
+            medal_times: None,
+            checkpoint_count: None,
+            lap_count: None,
+            created_with_simple_editor: false,
+            created_with_gamepad_editor: false,
+            thumbnail: None,
+            comments: None,
+            xml: None,
+        };
+
+        // every chunk is read with a new reader because the lookback strings are reset per chunk
+        let common = header
+            .chunk(CHUNK_COMMON)
+            .ok_or(GbxError::MissingChunk(CHUNK_COMMON))?;
+        map.read_common(&mut Reader::new(common))?;
+        if let Some(description) = header.chunk(CHUNK_DESCRIPTION) {
+            map.read_description(&mut Reader::new(description))?;
+        }
+        if let Some(xml) = header.chunk(CHUNK_XML) {
+            map.xml = Some(Reader::new(xml).string()?);
+        }
+        if let Some(thumbnail) = header.chunk(CHUNK_THUMBNAIL) {
+            map.read_thumbnail(&mut Reader::new(thumbnail))?;
+        }
+        if let Some(author) = header.chunk(CHUNK_AUTHOR) {
+            map.read_author(&mut Reader::new(author))?;
+        }
+
+        Ok(map)
+    }
+
+    /// Reads and parses the header from `reader`. Only the header is read, the reader is left at the start of the body.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, GbxError> {
+        Self::parse(&Header::read_bytes(reader)?)
+    }
+
+    /// Reads and parses the header from an async `reader`. Only the header is read, the reader is left at the start of the body.
+    pub async fn from_async_reader<R: AsyncRead + Unpin>(reader: R) -> Result<Self, GbxError> {
+        Self::parse(&Header::read_bytes_async(reader).await?)
+    }
+
+    /// Reads and parses the header of a `.Map.Gbx` file.
+    pub async fn from_file(path: impl AsRef<Path>) -> Result<Self, GbxError> {
+        let file = tokio::fs::File::open(path).await?;
+
+        Self::from_async_reader(tokio::io::BufReader::new(file)).await
+    }
+
+    /// Converts the author login into an accountID. Returns `None` for maps of older games.
+    pub fn author_account_id(&self) -> Option<AccountId> {
+        AccountId::from_login(&self.author_login).ok()
+    }
+
+    /// Compares the header with the [`MapInfo`] of the API and returns every field which does not match.
+    /// Fields which are empty in the `MapInfo` are not compared.
+    pub fn mismatches(&self, info: &MapInfo) -> Vec<MapField> {
+        let mut mismatches = Vec::new();
+        if self.uid != info.map_uid.as_str() {
+            mismatches.push(MapField::Uid);
+        }
+        if self.name != info.name {
+            mismatches.push(MapField::Name);
+        }
+        if self.author_account_id() != Some(info.author) {
+            mismatches.push(MapField::Author);
+        }
+        if self.medal_times != Some(info.medal_times()) {
+            mismatches.push(MapField::MedalTimes);
+        }
+        if !info.collection_name.is_empty() && self.collection != info.collection_name {
+            mismatches.push(MapField::Collection);
+        }
+        if !info.map_type.is_empty() && self.map_type != info.map_type {
+            mismatches.push(MapField::MapType);
+        }
+        if !info.map_style.is_empty() && self.map_style != info.map_style {
+            mismatches.push(MapField::MapStyle);
+        }
+
+        mismatches
+    }
+
+    /// Reads chunk `0x03043002` which contains the medal times and the editor.
+    fn read_description(&mut self, r: &mut Reader) -> Result<(), GbxError> {
+        let version = r.u8()?;
+        if version < 3 {
+            // old maps store the ident and name here as well
+            self.uid = r.lookback_string()?;
+            self.collection = r.lookback_string()?;
+            self.author_login = r.lookback_string()?;
+            self.name = r.string()?;
+        }
+        r.skip(4)?;
+        if version < 1 {
+            return Ok(());
+        }
+
+        let bronze = r.time()?;
+        let silver = r.time()?;
+        let gold = r.time()?;
+        let author = r.time()?;
+        if let (Some(bronze), Some(silver), Some(gold), Some(author)) =
+            (bronze, silver, gold, author)
+        {
+            self.medal_times = Some(MedalTimes {
+                author,
+                gold,
+                silver,
+                bronze,
+            });
+        }
+        if version == 2 {
+            r.skip(1)?;
+        }
+        if version < 11 {
+            return Ok(());
+        }
+
+        // cost, is lap race, play mode, unknown, author score
+        r.skip(20)?;
+        let editor = r.u32()?;
+        self.created_with_simple_editor = editor & 1 != 0;
+        self.created_with_gamepad_editor = editor & 4 != 0;
+        if version < 13 {
+            return Ok(());
+        }
+
+        r.skip(4)?;
+        self.checkpoint_count = Some(r.u32()?);
+        self.lap_count = Some(r.u32()?);
+
+        Ok(())
+    }
+
+    /// Reads chunk `0x03043003` which contains the ident, name and map type.
+    fn read_common(&mut self, r: &mut Reader) -> Result<(), GbxError> {
+        let version = r.u8()?;
+        self.uid = r.lookback_string()?;
+        self.collection = r.lookback_string()?;
+        self.author_login = r.lookback_string()?;
+        self.name = r.string()?;
+        // kind
+        r.skip(1)?;
+        if version < 6 {
+            return Ok(());
+        }
+
+        // locked
+        r.skip(4)?;
+        // password
+        r.string()?;
+        // decoration
+        r.lookback_string()?;
+        r.lookback_string()?;
+        r.lookback_string()?;
+        // map origin, map target, unknown
+        r.skip(8 + 8 + 16)?;
+        self.map_type = r.string()?;
+        self.map_style = r.string()?;
+
+        Ok(())
+    }
+
+    /// Reads chunk `0x03043007` which contains the thumbnail and the comments.
+    fn read_thumbnail(&mut self, r: &mut Reader) -> Result<(), GbxError> {
+        let version = r.u32()?;
+        if version == 0 {
+            return Ok(());
+        }
+
+        let size = r.u32()? as usize;
+        expect(r, b"<Thumbnail.jpg>", CHUNK_THUMBNAIL)?;
+        let thumbnail = r.bytes(size)?;
+        expect(r, b"</Thumbnail.jpg>", CHUNK_THUMBNAIL)?;
+        expect(r, b"<Comments>", CHUNK_THUMBNAIL)?;
+        let comments = r.string()?;
+        expect(r, b"</Comments>", CHUNK_THUMBNAIL)?;
+
+        if !thumbnail.is_empty() {
+            self.thumbnail = Some(thumbnail.to_vec());
+        }
+        if !comments.is_empty() {
+            self.comments = Some(comments);
+        }
+
+        Ok(())
+    }
+
+    /// Reads chunk `0x03043008` which contains information about the author.
+    fn read_author(&mut self, r: &mut Reader) -> Result<(), GbxError> {
+        // chunk version, author version
+        r.skip(8)?;
+        let login = r.string()?;
+        let nickname = r.string()?;
+        let zone = r.string()?;
+
+        if !login.is_empty() {
+            self.author_login = login;
+        }
+        self.author_nickname = Some(nickname).filter(|nickname| !nickname.is_empty());
+        self.author_zone = Some(zone).filter(|zone| !zone.is_empty());
+
+        Ok(())
+    }
+}
+
+/// Reads a fixed tag of a chunk.
+fn expect(r: &mut Reader, tag: &[u8], chunk: u32) -> Result<(), GbxError> {
+    if r.bytes(tag.len())? != tag {
+        return Err(GbxError::InvalidChunk(chunk));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gbx::fixtures::{gbx, Writer};
+    use crate::types::RaceTime;
+
+    const THUMBNAIL: &[u8] = &[0xFF, 0xD8, 0xFF, 0xD9];
+
+    fn common_chunk() -> Vec<u8> {
+        Writer::default()
+            .u8(6)
+            .lookback_string("Xy1AbCdEfGhIjKlMnOpQrStUvW")
+            .lookback_string("Stadium")
+            .lookback_string("4SUrQyaQSICY2uJ-F6LFeA")
+            .string("$f00Red $zMap")
+            // kind, locked, password
+            .u8(6)
+            .u32(0)
+            .string("")
+            // decoration
+            .lookback_string("")
+            .lookback_string("")
+            .lookback_string("")
+            .bytes(&[0; 32])
+            .string("TrackMania\\TM_Race")
+            .string("Tech")
+            .finish()
+    }
+
+    fn description_chunk() -> Vec<u8> {
+        Writer::default()
+            .u8(13)
+            .u32(0)
+            .i32(60_000)
+            .i32(49_000)
+            .i32(43_000)
+            .i32(40_000)
+            .bytes(&[0; 20])
+            // simple editor
+            .u32(1)
+            .u32(0)
+            // checkpoints, laps
+            .u32(5)
+            .u32(1)
+            .finish()
+    }
+
+    fn thumbnail_chunk() -> Vec<u8> {
+        Writer::default()
+            .u32(1)
+            .u32(THUMBNAIL.len() as u32)
+            .bytes(b"<Thumbnail.jpg>")
+            .bytes(THUMBNAIL)
+            .bytes(b"</Thumbnail.jpg>")
+            .bytes(b"<Comments>")
+            .string("have fun")
+            .bytes(b"</Comments>")
+            .finish()
+    }
+
+    fn author_chunk() -> Vec<u8> {
+        Writer::default()
+            .u32(0)
+            .u32(0)
+            .string("4SUrQyaQSICY2uJ-F6LFeA")
+            .string("Author")
+            .string("World|Europe|France")
+            .finish()
+    }
+
+    #[test]
+    fn parses_header() {
+        let data = gbx(
+            MAP_CLASS_ID,
+            true,
+            &[
+                (CHUNK_DESCRIPTION, description_chunk()),
+                (CHUNK_COMMON, common_chunk()),
+                (CHUNK_THUMBNAIL, thumbnail_chunk()),
+                (CHUNK_AUTHOR, author_chunk()),
+            ],
+            &[],
+        );
+
+        let header = MapHeader::parse(&data).unwrap();
+        assert_eq!(header.uid, "Xy1AbCdEfGhIjKlMnOpQrStUvW");
+        assert_eq!(header.name, "$f00Red $zMap");
+        assert_eq!(header.author_login, "4SUrQyaQSICY2uJ-F6LFeA");
+        assert_eq!(header.author_nickname.as_deref(), Some("Author"));
+        assert_eq!(header.author_zone.as_deref(), Some("World|Europe|France"));
+        assert_eq!(header.collection, "Stadium");
+        assert_eq!(header.map_type, "TrackMania\\TM_Race");
+        assert_eq!(header.map_style, "Tech");
+        assert_eq!(
+            header.medal_times,
+            Some(MedalTimes {
+                author: RaceTime::from_millis(40_000),
+                gold: RaceTime::from_millis(43_000),
+                silver: RaceTime::from_millis(49_000),
+                bronze: RaceTime::from_millis(60_000),
+            })
+        );
+        assert_eq!(header.checkpoint_count, Some(5));
+        assert_eq!(header.lap_count, Some(1));
+        assert!(header.created_with_simple_editor);
+        assert!(!header.created_with_gamepad_editor);
+        assert_eq!(header.thumbnail.as_deref(), Some(THUMBNAIL));
+        assert_eq!(header.comments.as_deref(), Some("have fun"));
+        assert_eq!(header.xml, None);
+    }
+
+    #[test]
+    fn reads_only_the_header() {
+        let data = gbx(
+            MAP_CLASS_ID,
+            true,
+            &[(CHUNK_COMMON, common_chunk())],
+            b"body",
+        );
+
+        let mut reader = data.as_slice();
+        let header = MapHeader::from_reader(&mut reader).unwrap();
+        assert_eq!(header.uid, "Xy1AbCdEfGhIjKlMnOpQrStUvW");
+        assert_eq!(header.medal_times, None);
+        assert_eq!(header.thumbnail, None);
+        // node count, external nodes and the body are left
+        assert_eq!(reader.len(), 4 + 4 + 4);
+    }
+
+    #[test]
+    fn rejects_other_classes_and_missing_chunks() {
+        let ghost = gbx(0x0309_2000, true, &[(CHUNK_COMMON, common_chunk())], &[]);
+        assert!(matches!(
+            MapHeader::parse(&ghost),
+            Err(GbxError::UnexpectedClass { .. })
+        ));
+
+        let data = gbx(MAP_CLASS_ID, true, &[(CHUNK_AUTHOR, author_chunk())], &[]);
+        assert!(matches!(
+            MapHeader::parse(&data),
+            Err(GbxError::MissingChunk(CHUNK_COMMON))
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_thumbnail_tags() {
+        let mut thumbnail = thumbnail_chunk();
+        thumbnail[9] = b'X';
+        let data = gbx(
+            MAP_CLASS_ID,
+            true,
+            &[(CHUNK_COMMON, common_chunk()), (CHUNK_THUMBNAIL, thumbnail)],
+            &[],
+        );
+
+        assert!(matches!(
+            MapHeader::parse(&data),
+            Err(GbxError::InvalidChunk(CHUNK_THUMBNAIL))
+        ));
+    }
+}
@@ -0,0 +1,166 @@
+//! Parsing of `.Gbx` files.
+
+use crate::gbx::reader::Reader;
+use std::io::Read;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+pub mod ghost;
+pub mod map;
+
+#[cfg(test)]
+pub(crate) mod fixtures;
+pub(crate) mod lzo;
+pub(crate) mod reader;
+
+pub use ghost::{Checkpoint, Ghost, GhostField};
+pub use map::{MapField, MapHeader};
+
+/// Size of the fixed part of the header up to and including the size of the header chunks.
+const HEADER_PREFIX_SIZE: usize = 3 + 2 + 4 + 4 + 4;
+/// Upper limit for the size of the header chunks. Protects against allocating huge buffers for invalid files.
+const MAX_USER_DATA_SIZE: usize = 16 * 1024 * 1024;
+
+/// A header chunk of a Gbx file.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct HeaderChunk<'a> {
+    pub(crate) id: u32,
+    pub(crate) data: &'a [u8],
+}
+
+/// The header of a Gbx file. Contains the class of the main node and its header chunks.
+#[derive(Debug, Clone)]
+pub(crate) struct Header<'a> {
+    pub(crate) class_id: u32,
+    pub(crate) chunks: Vec<HeaderChunk<'a>>,
+    /// Whether the body is compressed with LZO.
+    pub(crate) body_compressed: bool,
+    /// Everything after the header, starting with the node count.
+    pub(crate) rest: &'a [u8],
+}
+
+impl<'a> Header<'a> {
+    /// Parses the header of a Gbx file. Only version 6 which is used by all current games is supported.
+    pub(crate) fn parse(data: &'a [u8]) -> Result<Self, GbxError> {
+        let user_data_size = user_data_size(data)?;
+        let mut r = Reader::new(data);
+        // magic, version, format, compression of the ref table
+        r.skip(3 + 2 + 2)?;
+        let body_compressed = r.u8()? == b'C';
+        r.skip(1)?;
+        let class_id = r.u32()?;
+        r.skip(4)?;
+        let user_data = r.bytes(user_data_size)?;
+        let rest = &data[HEADER_PREFIX_SIZE + user_data_size..];
+
+        let mut r = Reader::new(user_data);
+        let chunk_count = r.u32()?;
+        let mut entries = Vec::new();
+        for _ in 0..chunk_count {
+            let id = r.u32()?;
+            // the highest bit marks heavy chunks
+            let size = r.u32()? & 0x7FFF_FFFF;
+            entries.push((id, size as usize));
+        }
+
+        let mut chunks = Vec::with_capacity(entries.len());
+        for (id, size) in entries {
+            chunks.push(HeaderChunk {
+                id,
+                data: r.bytes(size)?,
+            });
+        }
+
+        Ok(Self {
+            class_id,
+            chunks,
+            body_compressed,
+            rest,
+        })
+    }
+
+    /// Reads the bytes of the header from `reader` without reading the body.
+    pub(crate) fn read_bytes<R: Read>(mut reader: R) -> Result<Vec<u8>, GbxError> {
+        let mut data = vec![0; HEADER_PREFIX_SIZE];
+        reader.read_exact(&mut data)?;
+        let user_data_size = user_data_size(&data)?;
+        data.resize(HEADER_PREFIX_SIZE + user_data_size, 0);
+        reader.read_exact(&mut data[HEADER_PREFIX_SIZE..])?;
+
+        Ok(data)
+    }
+
+    /// Reads the bytes of the header from an async `reader` without reading the body.
+    pub(crate) async fn read_bytes_async<R: AsyncRead + Unpin>(
+        mut reader: R,
+    ) -> Result<Vec<u8>, GbxError> {
+        let mut data = vec![0; HEADER_PREFIX_SIZE];
+        reader.read_exact(&mut data).await?;
+        let user_data_size = user_data_size(&data)?;
+        data.resize(HEADER_PREFIX_SIZE + user_data_size, 0);
+        reader.read_exact(&mut data[HEADER_PREFIX_SIZE..]).await?;
+
+        Ok(data)
+    }
+
+    /// Returns the data of the header chunk with the given ID.
+    pub(crate) fn chunk(&self, id: u32) -> Option<&'a [u8]> {
+        self.chunks
+            .iter()
+            .find(|chunk| chunk.id == id)
+            .map(|chunk| chunk.data)
+    }
+}
+
+/// Validates the fixed part of the header and returns the size of the header chunks.
+fn user_data_size(prefix: &[u8]) -> Result<usize, GbxError> {
+    let mut r = Reader::new(prefix);
+    if r.bytes(3)? != b"GBX" {
+        return Err(GbxError::InvalidMagic);
+    }
+    let version = r.u16()?;
+    if version != 6 {
+        return Err(GbxError::UnsupportedVersion(version as u32));
+    }
+    r.skip(4 + 4)?;
+
+    let size = r.u32()? as usize;
+    if size > MAX_USER_DATA_SIZE {
+        return Err(GbxError::HeaderTooLarge(size));
+    }
+
+    Ok(size)
+}
+
+/// Errors while parsing a Gbx file.
+#[derive(Error, Debug)]
+pub enum GbxError {
+    #[error("the file is not a Gbx file")]
+    InvalidMagic,
+    #[error("version {0} is not supported")]
+    UnsupportedVersion(u32),
+    #[error("expected class {expected:#010x} but the file contains {actual:#010x}")]
+    UnexpectedClass { expected: u32, actual: u32 },
+    #[error("header chunk {0:#010x} is missing")]
+    MissingChunk(u32),
+    #[error("unexpected end of data")]
+    UnexpectedEof,
+    #[error("a string is not valid UTF-8")]
+    InvalidString,
+    #[error("a lookback string references an unknown string")]
+    InvalidLookbackString,
+    #[error("the header is too large ({0} bytes)")]
+    HeaderTooLarge(usize),
+    #[error("chunk {0:#010x} is invalid")]
+    InvalidChunk(u32),
+    #[error("chunk {0:#010x} can not be skipped")]
+    UnskippableChunk(u32),
+    #[error("files referencing external nodes are not supported")]
+    ExternalNodes,
+    #[error("the body is too large ({0} bytes)")]
+    BodyTooLarge(usize),
+    #[error("the compressed body is invalid")]
+    InvalidCompression,
+    #[error("the file could not be read: {0}")]
+    Io(#[from] std::io::Error),
+}
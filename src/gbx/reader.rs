@@ -0,0 +1,120 @@
+use crate::gbx::GbxError;
+use crate::types::RaceTime;
+
+/// Reads little-endian values from a byte slice.
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    /// Version of the lookback strings. `None` until the first lookback string is read.
+    lookback_version: Option<u32>,
+    lookback_strings: Vec<String>,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            lookback_version: None,
+            lookback_strings: Vec::new(),
+        }
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> Result<&'a [u8], GbxError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or(GbxError::UnexpectedEof)?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+
+        Ok(bytes)
+    }
+
+    pub(crate) fn skip(&mut self, len: usize) -> Result<(), GbxError> {
+        self.bytes(len).map(|_| ())
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, GbxError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, GbxError> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, GbxError> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn i32(&mut self) -> Result<i32, GbxError> {
+        Ok(i32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    /// Reads a time in milliseconds. `-1` means the time is not set.
+    pub(crate) fn time(&mut self) -> Result<Option<RaceTime>, GbxError> {
+        let millis = self.i32()?;
+
+        Ok((millis >= 0).then_some(RaceTime::from_millis(millis as i64)))
+    }
+
+    /// Reads a string prefixed with its length.
+    pub(crate) fn string(&mut self) -> Result<String, GbxError> {
+        let len = self.u32()? as usize;
+        let bytes = self.bytes(len)?;
+
+        String::from_utf8(bytes.to_vec()).map_err(|_| GbxError::InvalidString)
+    }
+
+    /// Reads a lookback string. Strings are stored once and referenced by their index afterwards.
+    pub(crate) fn lookback_string(&mut self) -> Result<String, GbxError> {
+        if self.lookback_version.is_none() {
+            let version = self.u32()?;
+            if version < 3 {
+                return Err(GbxError::UnsupportedVersion(version));
+            }
+            self.lookback_version = Some(version);
+        }
+
+        let index = self.u32()?;
+        if index == u32::MAX {
+            return Ok(String::new());
+        }
+        if index & 0xC000_0000 != 0 && index & 0x3FFF_FFFF == 0 {
+            let string = self.string()?;
+            self.lookback_strings.push(string.clone());
+            return Ok(string);
+        }
+        if index & 0xC000_0000 == 0 {
+            return Ok(collection_name(index));
+        }
+
+        let index = (index & 0x3FFF_FFFF) as usize;
+        self.lookback_strings
+            .get(index - 1)
+            .cloned()
+            .ok_or(GbxError::InvalidLookbackString)
+    }
+}
+
+/// Returns the name of a collection stored by its ID instead of its name.
+fn collection_name(id: u32) -> String {
+    match id {
+        0 => "Speed",
+        1 => "Alpine",
+        2 => "Rally",
+        3 => "Island",
+        4 => "Bay",
+        5 => "Coast",
+        6 => "Stadium",
+        11 => "Valley",
+        12 => "Canyon",
+        13 => "Lagoon",
+        17 => "TMCommon",
+        25 => "Stadium256",
+        26 => "Stadium",
+        _ => return id.to_string(),
+    }
+    .to_string()
+}
@@ -0,0 +1,180 @@
+use crate::gbx_remote::{GbxRemote, GbxRemoteError, Value};
+use crate::types::RaceTime;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A player on the server as returned by `GetPlayerList`.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlayerInfo {
+    pub login: String,
+    pub nick_name: String,
+    pub player_id: i32,
+    #[serde(default)]
+    pub team_id: i32,
+    #[serde(default)]
+    pub spectator_status: i32,
+    #[serde(default)]
+    pub ladder_ranking: i32,
+    #[serde(default)]
+    pub flags: i32,
+}
+
+/// A map on the server as returned by `GetCurrentMapInfo` and `GetMapList`.
+/// `GetMapList` only contains some of the fields, the others are set to their default.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ServerMapInfo {
+    pub name: String,
+    #[serde(rename = "UId")]
+    pub uid: String,
+    pub file_name: String,
+    pub author: String,
+    #[serde(default)]
+    pub author_nickname: String,
+    #[serde(rename = "Environnement", default)]
+    pub environment: String,
+    #[serde(default)]
+    pub mood: String,
+    #[serde(default)]
+    pub bronze_time: RaceTime,
+    #[serde(default)]
+    pub silver_time: RaceTime,
+    #[serde(default)]
+    pub gold_time: RaceTime,
+    #[serde(default)]
+    pub author_time: RaceTime,
+    #[serde(default)]
+    pub copper_price: i32,
+    #[serde(default)]
+    pub lap_race: bool,
+    #[serde(default)]
+    pub nb_laps: i32,
+    #[serde(default)]
+    pub nb_checkpoints: i32,
+    #[serde(default)]
+    pub map_type: String,
+    #[serde(default)]
+    pub map_style: String,
+}
+
+impl GbxRemote {
+    /// Authenticates with the credentials of a server user, e.g. `SuperAdmin`.
+    pub async fn authenticate(&self, login: &str, password: &str) -> Result<()> {
+        self.call_ok("Authenticate", vec![login.into(), password.into()])
+            .await
+    }
+
+    /// Sets the version of the API which determines the format of callbacks.
+    pub async fn set_api_version(&self, version: &str) -> Result<()> {
+        self.call_ok("SetApiVersion", vec![version.into()]).await
+    }
+
+    /// Enables or disables the callbacks of the server.
+    pub async fn enable_callbacks(&self, enable: bool) -> Result<()> {
+        self.call_ok("EnableCallbacks", vec![enable.into()]).await
+    }
+
+    /// Gets up to `max` players starting at `offset`.
+    pub async fn get_player_list(&self, max: i32, offset: i32) -> Result<Vec<PlayerInfo>> {
+        self.call_as("GetPlayerList", vec![max.into(), offset.into(), 1.into()])
+            .await
+    }
+
+    /// Sends a message to the chat of every player.
+    pub async fn chat_send_server_message(&self, message: &str) -> Result<()> {
+        self.call_ok("ChatSendServerMessage", vec![message.into()])
+            .await
+    }
+
+    pub async fn get_current_map_info(&self) -> Result<ServerMapInfo> {
+        self.call_as("GetCurrentMapInfo", Vec::new()).await
+    }
+
+    /// Gets up to `max` maps of the map list starting at `offset`.
+    pub async fn get_map_list(&self, max: i32, offset: i32) -> Result<Vec<ServerMapInfo>> {
+        self.call_as("GetMapList", vec![max.into(), offset.into()])
+            .await
+    }
+
+    /// Adds maps to the map list. Returns the number of added maps.
+    pub async fn add_map_list(&self, file_names: &[&str]) -> Result<i32> {
+        self.call_as("AddMapList", vec![file_names.to_vec().into()])
+            .await
+    }
+
+    /// Removes maps from the map list. Returns the number of removed maps.
+    pub async fn remove_map_list(&self, file_names: &[&str]) -> Result<i32> {
+        self.call_as("RemoveMapList", vec![file_names.to_vec().into()])
+            .await
+    }
+
+    /// Replaces the map list with the given maps. Returns the number of added maps.
+    ///
+    /// The server has no method for this, so every map which is not in `file_names` is removed with `RemoveMapList`
+    /// and the missing ones are added with `AddMapList`. The current map can not be removed and stays in the list.
+    pub async fn set_map_list(&self, file_names: &[&str]) -> Result<i32> {
+        let current = self.get_current_map_info().await?;
+        let maps = self.get_map_list(i32::MAX, 0).await?;
+
+        let remove = maps
+            .iter()
+            .map(|map| map.file_name.as_str())
+            .filter(|file_name| !file_names.contains(file_name) && *file_name != current.file_name)
+            .collect::<Vec<_>>();
+        if !remove.is_empty() {
+            self.remove_map_list(&remove).await?;
+        }
+
+        let add = file_names
+            .iter()
+            .copied()
+            .filter(|file_name| !maps.iter().any(|map| map.file_name == *file_name))
+            .collect::<Vec<_>>();
+        if add.is_empty() {
+            return Ok(0);
+        }
+
+        self.add_map_list(&add).await
+    }
+
+    /// Gets the settings of the mode script.
+    pub async fn get_mode_script_settings(&self) -> Result<BTreeMap<String, Value>> {
+        match self.call("GetModeScriptSettings", Vec::new()).await? {
+            Value::Struct(settings) => Ok(settings),
+            _ => Err(Error::from(GbxRemoteError::InvalidResponse(
+                "expected a struct".to_string(),
+            ))),
+        }
+    }
+
+    /// Changes settings of the mode script. Settings which are not given are left unchanged.
+    pub async fn set_mode_script_settings(&self, settings: BTreeMap<String, Value>) -> Result<()> {
+        self.call_ok("SetModeScriptSettings", vec![Value::Struct(settings)])
+            .await
+    }
+
+    /// Sends an event to the mode script, e.g. `XmlRpc.EnableCallbacks` with `["true"]`.
+    pub async fn trigger_mode_script_event_array(
+        &self,
+        method: &str,
+        params: &[&str],
+    ) -> Result<()> {
+        self.call_ok(
+            "TriggerModeScriptEventArray",
+            vec![method.into(), params.to_vec().into()],
+        )
+        .await
+    }
+
+    /// Calls a method which returns `true` on success.
+    async fn call_ok(&self, method: &str, params: Vec<Value>) -> Result<()> {
+        match self.call(method, params).await? {
+            Value::Bool(true) => Ok(()),
+            other => Err(Error::from(GbxRemoteError::InvalidResponse(format!(
+                "{method} returned {other:?}"
+            )))),
+        }
+    }
+}
@@ -0,0 +1,408 @@
+//! A client for the GbxRemote XML-RPC protocol of dedicated servers.
+
+use crate::{Error, Result};
+use futures::channel::{mpsc, oneshot};
+use futures::Stream;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::{TcpStream, ToSocketAddrs};
+use tokio::task::JoinHandle;
+
+pub mod methods;
+pub mod xml_rpc;
+
+pub use methods::{PlayerInfo, ServerMapInfo};
+pub use xml_rpc::{MethodCall, Value};
+
+const PROTOCOL: &str = "GBXRemote 2";
+/// Handles of requests have the highest bit set. Callbacks of the server don't.
+const REQUEST_HANDLE: u32 = 0x8000_0000;
+/// Upper limit for the size of a message. Protects against allocating huge buffers for invalid data.
+const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+/// Number of callbacks which are queued until the stream of callbacks is read. Further callbacks are dropped.
+const MAX_QUEUED_CALLBACKS: usize = 1024;
+
+/// The calls waiting for their response.
+#[derive(Debug, Default)]
+struct PendingCalls {
+    /// Set when the connection is closed. Calls fail immediately afterwards.
+    closed: bool,
+    calls: HashMap<u32, oneshot::Sender<std::result::Result<Value, GbxRemoteError>>>,
+}
+
+type Pending = Arc<Mutex<PendingCalls>>;
+
+/// A connection to the XML-RPC port of a dedicated server.
+///
+/// Requests can be made concurrently, the responses are matched by their handle.
+/// Callbacks of the server are available through [`GbxRemote::take_callbacks`] after enabling them.
+///
+/// # Examples
+///
+/// ```rust
+/// # use nadeo_api::gbx_remote::GbxRemote;
+/// # async fn run() -> nadeo_api::Result<()> {
+/// let server = GbxRemote::connect("127.0.0.1:5000").await?;
+/// server.authenticate("SuperAdmin", "SuperAdmin").await?;
+/// server.chat_send_server_message("Hello!").await?;
+///
+/// for player in server.get_player_list(100, 0).await? {
+///     println!("{}", player.login);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct GbxRemote {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    writer: tokio::sync::Mutex<OwnedWriteHalf>,
+    pending: Pending,
+    next_handle: AtomicU32,
+    callbacks: Mutex<Option<mpsc::Receiver<MethodCall>>>,
+    reader: JoinHandle<()>,
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+impl GbxRemote {
+    /// Connects to the XML-RPC port of a server and performs the handshake.
+    pub async fn connect(addr: impl ToSocketAddrs) -> Result<Self> {
+        let stream = TcpStream::connect(addr)
+            .await
+            .map_err(GbxRemoteError::from)?;
+        let (mut read, write) = stream.into_split();
+
+        let len = read.read_u32_le().await.map_err(GbxRemoteError::from)? as usize;
+        if len > 64 {
+            return Err(Error::from(GbxRemoteError::Handshake));
+        }
+        let mut protocol = vec![0; len];
+        read.read_exact(&mut protocol)
+            .await
+            .map_err(GbxRemoteError::from)?;
+        let protocol = String::from_utf8_lossy(&protocol).to_string();
+        if protocol != PROTOCOL {
+            return Err(Error::from(GbxRemoteError::UnsupportedProtocol(protocol)));
+        }
+
+        let pending = Pending::default();
+        let (sender, receiver) = mpsc::channel(MAX_QUEUED_CALLBACKS);
+        let reader = tokio::spawn(read_messages(read, pending.clone(), sender));
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                writer: tokio::sync::Mutex::new(write),
+                pending,
+                next_handle: AtomicU32::new(0),
+                callbacks: Mutex::new(Some(receiver)),
+                reader,
+            }),
+        })
+    }
+
+    /// Calls a method of the server and returns the response.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GbxRemoteError::Fault`] if the server returned a fault and [`GbxRemoteError::Closed`] if the connection is closed.
+    pub async fn call(&self, method: &str, params: Vec<Value>) -> Result<Value> {
+        let call = MethodCall {
+            method: method.to_string(),
+            params,
+        };
+        let xml = call.to_xml();
+        if xml.len() > MAX_MESSAGE_SIZE {
+            return Err(Error::from(GbxRemoteError::TooLarge(xml.len())));
+        }
+
+        let handle = REQUEST_HANDLE
+            | (self.inner.next_handle.fetch_add(1, Ordering::Relaxed) & !REQUEST_HANDLE);
+        let (sender, receiver) = oneshot::channel();
+        {
+            let mut pending = self.inner.pending.lock().unwrap();
+            if pending.closed {
+                return Err(Error::from(GbxRemoteError::Closed));
+            }
+            pending.calls.insert(handle, sender);
+        }
+
+        let mut message = Vec::with_capacity(8 + xml.len());
+        message.extend_from_slice(&(xml.len() as u32).to_le_bytes());
+        message.extend_from_slice(&handle.to_le_bytes());
+        message.extend_from_slice(xml.as_bytes());
+
+        let written = self.inner.writer.lock().await.write_all(&message).await;
+        if let Err(e) = written {
+            self.inner.pending.lock().unwrap().calls.remove(&handle);
+            return Err(Error::from(GbxRemoteError::from(e)));
+        }
+
+        match receiver.await {
+            Ok(res) => Ok(res?),
+            Err(_) => Err(Error::from(GbxRemoteError::Closed)),
+        }
+    }
+
+    /// Calls a method of the server and deserializes the response.
+    pub async fn call_as<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Vec<Value>,
+    ) -> Result<T> {
+        let value = self.call(method, params).await?;
+
+        serde_json::from_value(value.into())
+            .map_err(|e| Error::from(GbxRemoteError::InvalidResponse(e.to_string())))
+    }
+
+    /// Returns the stream of callbacks sent by the server. The stream ends when the connection is closed.
+    /// Returns `None` if the stream was already taken.
+    ///
+    /// Callbacks are only sent after enabling them with [`GbxRemote::enable_callbacks`].
+    /// Up to 1024 callbacks are queued until they are read, further callbacks are dropped.
+    pub fn take_callbacks(&self) -> Option<impl Stream<Item = MethodCall>> {
+        self.inner.callbacks.lock().unwrap().take()
+    }
+}
+
+/// Reads messages until the connection is closed. Responses are sent to the pending request, callbacks to `callbacks`.
+async fn read_messages<R: AsyncRead + Unpin>(
+    mut read: R,
+    pending: Pending,
+    mut callbacks: mpsc::Sender<MethodCall>,
+) {
+    while let Ok((handle, xml)) = read_message(&mut read).await {
+        if handle & REQUEST_HANDLE != 0 {
+            let sender = pending.lock().unwrap().calls.remove(&handle);
+            if let Some(sender) = sender {
+                let _ = sender.send(xml_rpc::read_response(&xml));
+            }
+        } else if let Ok(call) = MethodCall::from_xml(&xml) {
+            // callbacks are discarded if the queue is full or the receiver was dropped
+            let _ = callbacks.try_send(call);
+        }
+    }
+
+    let mut pending = pending.lock().unwrap();
+    pending.closed = true;
+    for (_, sender) in pending.calls.drain() {
+        let _ = sender.send(Err(GbxRemoteError::Closed));
+    }
+}
+
+async fn read_message<R: AsyncRead + Unpin>(
+    read: &mut R,
+) -> std::result::Result<(u32, String), GbxRemoteError> {
+    let size = read.read_u32_le().await? as usize;
+    let handle = read.read_u32_le().await?;
+    if size > MAX_MESSAGE_SIZE {
+        return Err(GbxRemoteError::TooLarge(size));
+    }
+
+    let mut xml = vec![0; size];
+    read.read_exact(&mut xml).await?;
+
+    Ok((handle, String::from_utf8_lossy(&xml).to_string()))
+}
+
+/// Errors of the GbxRemote protocol.
+#[derive(Error, Debug)]
+pub enum GbxRemoteError {
+    #[error("the server did not send a valid handshake")]
+    Handshake,
+    #[error("the server uses the unsupported protocol {0:?}")]
+    UnsupportedProtocol(String),
+    #[error("the server returned fault {code}: {message}")]
+    Fault { code: i32, message: String },
+    #[error("invalid XML-RPC message: {0}")]
+    InvalidXml(String),
+    #[error("the response has an unexpected format: {0}")]
+    InvalidResponse(String),
+    #[error("the message is too large ({0} bytes)")]
+    TooLarge(usize),
+    #[error("the connection is closed")]
+    Closed,
+    #[error("connection error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use std::time::Duration;
+    use tokio::net::TcpListener;
+
+    const TRUE_RESPONSE: &str = "<?xml version=\"1.0\"?><methodResponse><params><param><value><boolean>1</boolean></value></param></params></methodResponse>";
+    const FAULT_RESPONSE: &str = "<?xml version=\"1.0\"?><methodResponse><fault><value><struct><member><name>faultCode</name><value><int>-1000</int></value></member><member><name>faultString</name><value><string>Login unknown.</string></value></member></struct></value></fault></methodResponse>";
+
+    async fn write_message(stream: &mut TcpStream, handle: u32, xml: &str) {
+        let mut message = (xml.len() as u32).to_le_bytes().to_vec();
+        message.extend_from_slice(&handle.to_le_bytes());
+        message.extend_from_slice(xml.as_bytes());
+        stream.write_all(&message).await.unwrap();
+    }
+
+    async fn read_call(stream: &mut TcpStream) -> (u32, MethodCall) {
+        let (handle, xml) = read_message(stream).await.unwrap();
+
+        (handle, MethodCall::from_xml(&xml).unwrap())
+    }
+
+    /// Connects to a local server which sends the given protocol in the handshake.
+    async fn connect(protocol: &str) -> (Result<GbxRemote>, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = async {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            stream
+                .write_all(&(protocol.len() as u32).to_le_bytes())
+                .await
+                .unwrap();
+            stream.write_all(protocol.as_bytes()).await.unwrap();
+            stream
+        };
+
+        tokio::join!(GbxRemote::connect(addr), server)
+    }
+
+    #[tokio::test]
+    async fn rejects_other_protocols() {
+        let (remote, _stream) = connect("GBXRemote 1").await;
+
+        assert!(matches!(
+            remote,
+            Err(Error::GbxRemote(GbxRemoteError::UnsupportedProtocol(protocol))) if protocol == "GBXRemote 1"
+        ));
+    }
+
+    #[tokio::test]
+    async fn matches_responses_to_calls() {
+        let (remote, mut stream) = connect(PROTOCOL).await;
+        let remote = remote.unwrap();
+
+        let server = async {
+            let (first, call) = read_call(&mut stream).await;
+            assert_eq!(call.method, "Authenticate");
+            assert_eq!(
+                call.params,
+                vec![Value::from("SuperAdmin"), Value::from("password")]
+            );
+            let (second, call) = read_call(&mut stream).await;
+            assert_eq!(call.method, "GetVersion");
+            assert_ne!(first & REQUEST_HANDLE, 0);
+            assert_ne!(first, second);
+
+            // answered in reverse order
+            write_message(
+                &mut stream,
+                second,
+                "<?xml version=\"1.0\"?><methodResponse><params><param><value><string>2.11.26</string></value></param></params></methodResponse>",
+            )
+            .await;
+            write_message(&mut stream, first, TRUE_RESPONSE).await;
+        };
+        let (authenticated, version, _) = tokio::join!(
+            remote.authenticate("SuperAdmin", "password"),
+            remote.call("GetVersion", Vec::new()),
+            server
+        );
+
+        authenticated.unwrap();
+        assert_eq!(version.unwrap(), Value::from("2.11.26"));
+    }
+
+    #[tokio::test]
+    async fn returns_faults() {
+        let (remote, mut stream) = connect(PROTOCOL).await;
+        let remote = remote.unwrap();
+
+        let server = async {
+            let (handle, _) = read_call(&mut stream).await;
+            write_message(&mut stream, handle, FAULT_RESPONSE).await;
+        };
+        let (res, _) = tokio::join!(remote.call("Kick", vec!["login".into()]), server);
+
+        assert!(matches!(
+            res,
+            Err(Error::GbxRemote(GbxRemoteError::Fault { code: -1000, message })) if message == "Login unknown."
+        ));
+    }
+
+    #[tokio::test]
+    async fn streams_callbacks() {
+        let (remote, mut stream) = connect(PROTOCOL).await;
+        let remote = remote.unwrap();
+        let mut callbacks = remote.take_callbacks().unwrap();
+        assert!(remote.take_callbacks().is_none());
+
+        let callback = MethodCall {
+            method: "ManiaPlanet.PlayerConnect".to_string(),
+            params: vec!["login".into(), false.into()],
+        };
+        write_message(&mut stream, 1, &callback.to_xml()).await;
+
+        assert_eq!(callbacks.next().await, Some(callback));
+        drop(stream);
+        assert_eq!(callbacks.next().await, None);
+    }
+
+    #[tokio::test]
+    async fn drops_callbacks_which_are_not_read() {
+        let (remote, mut stream) = connect(PROTOCOL).await;
+        let remote = remote.unwrap();
+
+        let callback = MethodCall {
+            method: "ManiaPlanet.PlayerChat".to_string(),
+            params: vec![0.into()],
+        }
+        .to_xml();
+        for _ in 0..MAX_QUEUED_CALLBACKS + 100 {
+            write_message(&mut stream, 1, &callback).await;
+        }
+        drop(stream);
+        // wait until every message was read before reading the queue
+        while !remote.inner.pending.lock().unwrap().closed {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        let queued = remote.take_callbacks().unwrap().count().await;
+        assert!(queued > 0);
+        assert!(queued <= MAX_QUEUED_CALLBACKS + 1);
+    }
+
+    #[tokio::test]
+    async fn fails_calls_when_the_connection_is_closed() {
+        let (remote, mut stream) = connect(PROTOCOL).await;
+        let remote = remote.unwrap();
+
+        let server = async {
+            read_call(&mut stream).await;
+            drop(stream);
+        };
+        let (res, _) = tokio::join!(remote.call("GetVersion", Vec::new()), server);
+        assert!(matches!(res, Err(Error::GbxRemote(GbxRemoteError::Closed))));
+
+        let res = tokio::time::timeout(
+            Duration::from_secs(1),
+            remote.call("GetVersion", Vec::new()),
+        )
+        .await
+        .expect("calls on a closed connection fail immediately");
+        assert!(matches!(res, Err(Error::GbxRemote(GbxRemoteError::Closed))));
+    }
+}
@@ -0,0 +1,368 @@
+use crate::gbx_remote::GbxRemoteError;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use std::collections::BTreeMap;
+
+/// A XML-RPC value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i32),
+    Bool(bool),
+    String(String),
+    Double(f64),
+    /// A date in the ISO 8601 format as sent by the server.
+    DateTime(String),
+    Base64(Vec<u8>),
+    Array(Vec<Value>),
+    Struct(BTreeMap<String, Value>),
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            Value::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Returns the member `key` if the value is a struct.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Struct(members) => members.get(key),
+            _ => None,
+        }
+    }
+
+    fn write(&self, xml: &mut String) {
+        xml.push_str("<value>");
+        match self {
+            Value::Int(i) => xml.push_str(&format!("<int>{i}</int>")),
+            Value::Bool(b) => xml.push_str(&format!("<boolean>{}</boolean>", *b as u8)),
+            Value::String(s) => xml.push_str(&format!("<string>{}</string>", escape(s))),
+            Value::Double(d) => xml.push_str(&format!("<double>{d}</double>")),
+            Value::DateTime(d) => xml.push_str(&format!(
+                "<dateTime.iso8601>{}</dateTime.iso8601>",
+                escape(d)
+            )),
+            Value::Base64(bytes) => xml.push_str(&format!(
+                "<base64>{}</base64>",
+                BASE64_STANDARD.encode(bytes)
+            )),
+            Value::Array(values) => {
+                xml.push_str("<array><data>");
+                for value in values {
+                    value.write(xml);
+                }
+                xml.push_str("</data></array>");
+            }
+            Value::Struct(members) => {
+                xml.push_str("<struct>");
+                for (name, value) in members {
+                    xml.push_str(&format!("<member><name>{}</name>", escape(name)));
+                    value.write(xml);
+                    xml.push_str("</member>");
+                }
+                xml.push_str("</struct>");
+            }
+        }
+        xml.push_str("</value>");
+    }
+
+    fn read(element: &Element) -> Result<Self, GbxRemoteError> {
+        // a value without a type is a string
+        let Some(typed) = element.children.first() else {
+            return Ok(Value::String(element.text.clone()));
+        };
+
+        let value = match typed.name.as_str() {
+            "int" | "i4" => Value::Int(typed.parse_text()?),
+            "boolean" => Value::Bool(typed.text.trim() == "1"),
+            "string" => Value::String(typed.text.clone()),
+            "double" => Value::Double(typed.parse_text()?),
+            "dateTime.iso8601" => Value::DateTime(typed.text.trim().to_string()),
+            "base64" => Value::Base64(
+                BASE64_STANDARD
+                    .decode(typed.text.split_whitespace().collect::<String>())
+                    .map_err(|_| invalid("invalid base64"))?,
+            ),
+            "array" => Value::Array(
+                typed
+                    .child("data")?
+                    .children
+                    .iter()
+                    .map(Value::read)
+                    .collect::<Result<_, _>>()?,
+            ),
+            "struct" => Value::Struct(
+                typed
+                    .children
+                    .iter()
+                    .map(|member| {
+                        let name = member.child("name")?.text.clone();
+                        let value = Value::read(member.child("value")?)?;
+                        Ok((name, value))
+                    })
+                    .collect::<Result<_, GbxRemoteError>>()?,
+            ),
+            other => return Err(invalid(&format!("unknown type {other}"))),
+        };
+
+        Ok(value)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::Int(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Double(value)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(values: Vec<T>) -> Self {
+        Value::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<Value> for serde_json::Value {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Int(i) => i.into(),
+            Value::Bool(b) => b.into(),
+            Value::String(s) | Value::DateTime(s) => s.into(),
+            Value::Double(d) => d.into(),
+            Value::Base64(bytes) => BASE64_STANDARD.encode(bytes).into(),
+            Value::Array(values) => values
+                .into_iter()
+                .map(serde_json::Value::from)
+                .collect::<Vec<_>>()
+                .into(),
+            Value::Struct(members) => members
+                .into_iter()
+                .map(|(name, value)| (name, value.into()))
+                .collect::<serde_json::Map<_, _>>()
+                .into(),
+        }
+    }
+}
+
+impl TryFrom<serde_json::Value> for Value {
+    type Error = GbxRemoteError;
+
+    /// Converts JSON into a XML-RPC value. Integers which don't fit into an `i32` are converted into doubles.
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        let value = match value {
+            serde_json::Value::Null => return Err(invalid("XML-RPC has no null value")),
+            serde_json::Value::Bool(b) => Value::Bool(b),
+            serde_json::Value::Number(n) => match n.as_i64().and_then(|i| i32::try_from(i).ok()) {
+                Some(i) => Value::Int(i),
+                None => Value::Double(n.as_f64().unwrap_or_default()),
+            },
+            serde_json::Value::String(s) => Value::String(s),
+            serde_json::Value::Array(values) => Value::Array(
+                values
+                    .into_iter()
+                    .map(Value::try_from)
+                    .collect::<Result<_, _>>()?,
+            ),
+            serde_json::Value::Object(members) => Value::Struct(
+                members
+                    .into_iter()
+                    .map(|(name, value)| Ok((name, Value::try_from(value)?)))
+                    .collect::<Result<_, GbxRemoteError>>()?,
+            ),
+        };
+
+        Ok(value)
+    }
+}
+
+/// A method call, either a request to the server or a callback from the server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodCall {
+    pub method: String,
+    pub params: Vec<Value>,
+}
+
+impl MethodCall {
+    pub(crate) fn to_xml(&self) -> String {
+        let mut xml = format!(
+            "<?xml version=\"1.0\"?><methodCall><methodName>{}</methodName><params>",
+            escape(&self.method)
+        );
+        for param in &self.params {
+            xml.push_str("<param>");
+            param.write(&mut xml);
+            xml.push_str("</param>");
+        }
+        xml.push_str("</params></methodCall>");
+
+        xml
+    }
+
+    pub(crate) fn from_xml(xml: &str) -> Result<Self, GbxRemoteError> {
+        let root = Element::parse(xml)?;
+        if root.name != "methodCall" {
+            return Err(invalid("expected methodCall"));
+        }
+
+        Ok(Self {
+            method: root.child("methodName")?.text.trim().to_string(),
+            params: read_params(&root)?,
+        })
+    }
+}
+
+/// Parses a `methodResponse`. A fault is returned as [`GbxRemoteError::Fault`].
+pub(crate) fn read_response(xml: &str) -> Result<Value, GbxRemoteError> {
+    let root = Element::parse(xml)?;
+    if root.name != "methodResponse" {
+        return Err(invalid("expected methodResponse"));
+    }
+
+    if let Ok(fault) = root.child("fault") {
+        let fault = Value::read(fault.child("value")?)?;
+        return Err(GbxRemoteError::Fault {
+            code: fault
+                .get("faultCode")
+                .and_then(Value::as_i32)
+                .unwrap_or_default(),
+            message: fault
+                .get("faultString")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+        });
+    }
+
+    let mut params = read_params(&root)?;
+    if params.is_empty() {
+        return Err(invalid("the response has no value"));
+    }
+
+    Ok(params.swap_remove(0))
+}
+
+fn read_params(root: &Element) -> Result<Vec<Value>, GbxRemoteError> {
+    let Ok(params) = root.child("params") else {
+        return Ok(Vec::new());
+    };
+
+    params
+        .children
+        .iter()
+        .map(|param| Value::read(param.child("value")?))
+        .collect()
+}
+
+fn invalid(message: &str) -> GbxRemoteError {
+    GbxRemoteError::InvalidXml(message.to_string())
+}
+
+/// A minimal XML element tree.
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl Element {
+    fn parse(xml: &str) -> Result<Self, GbxRemoteError> {
+        let mut reader = quick_xml::Reader::from_str(xml);
+        let mut stack: Vec<Element> = Vec::new();
+
+        loop {
+            let event = reader.read_event().map_err(|e| invalid(&e.to_string()))?;
+            match event {
+                Event::Start(start) => stack.push(Element {
+                    name: String::from_utf8_lossy(start.name().as_ref()).to_string(),
+                    ..Default::default()
+                }),
+                Event::Empty(empty) => {
+                    let element = Element {
+                        name: String::from_utf8_lossy(empty.name().as_ref()).to_string(),
+                        ..Default::default()
+                    };
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => return Ok(element),
+                    }
+                }
+                Event::Text(text) => {
+                    if let Some(element) = stack.last_mut() {
+                        let text = text.unescape().map_err(|e| invalid(&e.to_string()))?;
+                        element.text.push_str(&text);
+                    }
+                }
+                Event::CData(data) => {
+                    if let Some(element) = stack.last_mut() {
+                        element.text.push_str(&String::from_utf8_lossy(&data));
+                    }
+                }
+                Event::End(_) => {
+                    let element = stack.pop().ok_or_else(|| invalid("unexpected end tag"))?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => return Ok(element),
+                    }
+                }
+                Event::Eof => return Err(invalid("unexpected end of document")),
+                _ => {}
+            }
+        }
+    }
+
+    fn child(&self, name: &str) -> Result<&Element, GbxRemoteError> {
+        self.children
+            .iter()
+            .find(|child| child.name == name)
+            .ok_or_else(|| invalid(&format!("missing element {name}")))
+    }
+
+    fn parse_text<T: std::str::FromStr>(&self) -> Result<T, GbxRemoteError> {
+        self.text
+            .trim()
+            .parse()
+            .map_err(|_| invalid(&format!("invalid {}", self.name)))
+    }
+}
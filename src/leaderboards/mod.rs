@@ -0,0 +1,89 @@
+//! Map leaderboards of the live services.
+
+use crate::auth::AuthType;
+use crate::types::{AccountId, MapUid, RaceTime, ZoneId};
+use crate::{NadeoClient, NadeoRequest, Result};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+pub mod monitor;
+
+pub use monitor::{LeaderboardEvent, LeaderboardMonitor, Scope};
+
+const LEADERBOARD_URL: &str =
+    "https://live-services.trackmania.nadeo.live/api/token/leaderboard/group/Personal_Best/map";
+
+/// Maximum number of records of a leaderboard request.
+pub const MAX_LEADERBOARD_LENGTH: u32 = 100;
+/// Maximum number of records served by the leaderboard endpoint. `offset + length` must not exceed it.
+pub const MAX_LEADERBOARD_RECORDS: u32 = 10_000;
+
+/// A record of a map leaderboard.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardRecord {
+    pub account_id: AccountId,
+    pub zone_id: ZoneId,
+    #[serde(default)]
+    pub zone_name: String,
+    /// Position starting at 1.
+    pub position: u32,
+    pub score: RaceTime,
+}
+
+/// The top records of a zone.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ZoneTop {
+    pub zone_id: ZoneId,
+    pub zone_name: String,
+    pub top: Vec<LeaderboardRecord>,
+}
+
+/// The leaderboard of a map. Contains the world and, if requested, the zones of the authenticated account.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MapLeaderboard {
+    pub map_uid: MapUid,
+    /// The world leaderboard comes first, followed by the zones from the largest to the smallest.
+    pub tops: Vec<ZoneTop>,
+}
+
+impl MapLeaderboard {
+    /// The world leaderboard.
+    pub fn world(&self) -> Option<&ZoneTop> {
+        self.tops.first()
+    }
+
+    /// The leaderboard of a zone.
+    pub fn zone(&self, zone_id: ZoneId) -> Option<&ZoneTop> {
+        self.tops.iter().find(|top| top.zone_id == zone_id)
+    }
+}
+
+impl NadeoClient {
+    /// Gets the top records of a map. `length` is limited to [`MAX_LEADERBOARD_LENGTH`].
+    /// Only the best [`MAX_LEADERBOARD_RECORDS`] records are available.
+    /// If `only_world` is `false` the leaderboards of the zones of the authenticated account are included.
+    /// Requires [`AuthType::NadeoLiveServices`].
+    pub async fn map_leaderboard(
+        &mut self,
+        map_uid: &MapUid,
+        offset: u32,
+        length: u32,
+        only_world: bool,
+    ) -> Result<MapLeaderboard> {
+        let length = length.min(MAX_LEADERBOARD_LENGTH);
+        let request = NadeoRequest::builder()
+            .url(&format!(
+                "{LEADERBOARD_URL}/{map_uid}/top?length={length}&offset={offset}&onlyWorld={only_world}"
+            ))
+            .auth_type(AuthType::NadeoLiveServices)
+            .method(Method::GET)
+            .build()?;
+
+        let res = self.execute(request).await?;
+
+        Ok(res.json().await?)
+    }
+}
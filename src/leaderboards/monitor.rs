@@ -0,0 +1,400 @@
+use crate::leaderboards::{LeaderboardRecord, MapLeaderboard, MAX_LEADERBOARD_LENGTH};
+use crate::types::{AccountId, MapUid, ZoneId};
+use crate::{NadeoClient, Result};
+use futures::Stream;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// The leaderboard of a map which is monitored.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Scope {
+    World,
+    /// A zone of the authenticated account, e.g. its country.
+    Zone(ZoneId),
+}
+
+/// Changes between two snapshots of a leaderboard.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LeaderboardEvent {
+    /// The first place has a better score than before, or the first record of a leaderboard which was empty.
+    ///
+    /// The player is reported by a [`LeaderboardEvent::PersonalBest`] or [`LeaderboardEvent::Entered`] as well.
+    /// A player who was not in the top before gets both events, so handlers of entries don't miss them.
+    NewWorldRecord {
+        map_uid: MapUid,
+        scope: Scope,
+        record: LeaderboardRecord,
+        /// `None` if the leaderboard was empty.
+        previous: Option<LeaderboardRecord>,
+    },
+    /// A player which was already in the top improved their score.
+    PersonalBest {
+        map_uid: MapUid,
+        scope: Scope,
+        record: LeaderboardRecord,
+        previous: LeaderboardRecord,
+    },
+    Entered {
+        map_uid: MapUid,
+        scope: Scope,
+        record: LeaderboardRecord,
+    },
+    Left {
+        map_uid: MapUid,
+        scope: Scope,
+        previous: LeaderboardRecord,
+    },
+}
+
+/// Periodically snapshots the top records of maps and reports the changes.
+///
+/// Every poll interval each map is requested once. Requests are spaced by the request interval
+/// to stay within the rate limits of the account. The first snapshot of a leaderboard does not emit events.
+///
+/// # Examples
+///
+/// ```rust
+/// # use nadeo_api::NadeoClient;
+/// # use nadeo_api::leaderboards::{LeaderboardEvent, LeaderboardMonitor};
+/// # use std::time::Duration;
+/// # async fn run(client: NadeoClient, maps: Vec<nadeo_api::types::MapUid>) -> nadeo_api::Result<()> {
+/// let mut monitor = LeaderboardMonitor::new(client, maps)
+///     .top(10)
+///     .poll_interval(Duration::from_secs(5 * 60));
+///
+/// loop {
+///     if let LeaderboardEvent::NewWorldRecord { map_uid, record, .. } = monitor.next_event().await? {
+///         println!("new WR on {map_uid}: {}", record.score);
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct LeaderboardMonitor {
+    client: NadeoClient,
+    maps: Vec<MapUid>,
+    zones: Vec<ZoneId>,
+    top: u32,
+    poll_interval: Duration,
+    request_interval: Duration,
+    snapshots: HashMap<(MapUid, Scope), Vec<LeaderboardRecord>>,
+    queue: VecDeque<MapUid>,
+    next_poll: Instant,
+    next_request: Instant,
+    pending: VecDeque<LeaderboardEvent>,
+}
+
+impl LeaderboardMonitor {
+    /// Creates a monitor for the world top 100 of the maps.
+    pub fn new(client: NadeoClient, maps: Vec<MapUid>) -> Self {
+        let now = Instant::now();
+
+        Self {
+            client,
+            maps,
+            zones: Vec::new(),
+            top: MAX_LEADERBOARD_LENGTH,
+            poll_interval: Duration::from_secs(10 * 60),
+            request_interval: Duration::from_secs(1),
+            snapshots: HashMap::new(),
+            queue: VecDeque::new(),
+            next_poll: now,
+            next_request: now,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Number of records per leaderboard. Limited to [`MAX_LEADERBOARD_LENGTH`].
+    pub fn top(mut self, top: u32) -> Self {
+        self.top = top.clamp(1, MAX_LEADERBOARD_LENGTH);
+
+        self
+    }
+
+    /// Also monitors the leaderboard of a zone. Only the zones of the authenticated account are available.
+    pub fn zone(mut self, zone_id: ZoneId) -> Self {
+        if !self.zones.contains(&zone_id) {
+            self.zones.push(zone_id);
+        }
+
+        self
+    }
+
+    /// Time between two snapshots of a map. Defaults to 10 minutes.
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+
+        self
+    }
+
+    /// Minimum time between two requests. Defaults to 1 second.
+    pub fn request_interval(mut self, interval: Duration) -> Self {
+        self.request_interval = interval;
+
+        self
+    }
+
+    /// The latest snapshot of a leaderboard.
+    pub fn snapshot(&self, map_uid: &MapUid, scope: Scope) -> Option<&[LeaderboardRecord]> {
+        self.snapshots
+            .get(&(map_uid.clone(), scope))
+            .map(Vec::as_slice)
+    }
+
+    /// Waits for the next event. Never returns if no maps are monitored.
+    ///
+    /// A failed request returns an error, the map is requested again in the next poll.
+    pub async fn next_event(&mut self) -> Result<LeaderboardEvent> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(event);
+            }
+            if self.maps.is_empty() {
+                return std::future::pending().await;
+            }
+
+            if self.queue.is_empty() {
+                tokio::time::sleep_until(self.next_poll).await;
+                self.next_poll = Instant::now() + self.poll_interval;
+                self.queue.extend(self.maps.iter().cloned());
+            }
+            let Some(map_uid) = self.queue.pop_front() else {
+                continue;
+            };
+
+            tokio::time::sleep_until(self.next_request).await;
+            self.next_request = Instant::now() + self.request_interval;
+            let leaderboard = self
+                .client
+                .map_leaderboard(&map_uid, 0, self.top, self.zones.is_empty())
+                .await?;
+            self.update(leaderboard);
+        }
+    }
+
+    /// Turns the monitor into an endless stream of events.
+    pub fn into_stream(self) -> impl Stream<Item = Result<LeaderboardEvent>> {
+        futures::stream::unfold(self, |mut monitor| async move {
+            let event = monitor.next_event().await;
+
+            Some((event, monitor))
+        })
+    }
+
+    fn update(&mut self, leaderboard: MapLeaderboard) {
+        let mut tops = Vec::new();
+        if let Some(world) = leaderboard.world() {
+            tops.push((Scope::World, world.top.clone()));
+        }
+        for zone_id in &self.zones {
+            if let Some(zone) = leaderboard.zone(*zone_id) {
+                tops.push((Scope::Zone(*zone_id), zone.top.clone()));
+            }
+        }
+
+        for (scope, top) in tops {
+            let key = (leaderboard.map_uid.clone(), scope);
+            if let Some(previous) = self.snapshots.get(&key) {
+                self.pending
+                    .extend(diff(&leaderboard.map_uid, scope, previous, &top));
+            }
+            self.snapshots.insert(key, top);
+        }
+    }
+}
+
+/// Compares two snapshots of a leaderboard. A new world record is reported before the events of the players.
+fn diff(
+    map_uid: &MapUid,
+    scope: Scope,
+    previous: &[LeaderboardRecord],
+    current: &[LeaderboardRecord],
+) -> Vec<LeaderboardEvent> {
+    let mut events = Vec::new();
+
+    if let Some(record) = current.first() {
+        let old = previous.first();
+        if old.is_none_or(|old| record.score < old.score) {
+            events.push(LeaderboardEvent::NewWorldRecord {
+                map_uid: map_uid.clone(),
+                scope,
+                record: record.clone(),
+                previous: old.cloned(),
+            });
+        }
+    }
+
+    let old_records = previous
+        .iter()
+        .map(|record| (record.account_id, record))
+        .collect::<HashMap<AccountId, _>>();
+    for record in current {
+        match old_records.get(&record.account_id) {
+            None => events.push(LeaderboardEvent::Entered {
+                map_uid: map_uid.clone(),
+                scope,
+                record: record.clone(),
+            }),
+            Some(old) if record.score < old.score => events.push(LeaderboardEvent::PersonalBest {
+                map_uid: map_uid.clone(),
+                scope,
+                record: record.clone(),
+                previous: (*old).clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for old in previous {
+        if !current
+            .iter()
+            .any(|record| record.account_id == old.account_id)
+        {
+            events.push(LeaderboardEvent::Left {
+                map_uid: map_uid.clone(),
+                scope,
+                previous: old.clone(),
+            });
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RaceTime;
+    use uuid::Uuid;
+
+    fn record(player: u128, position: u32, score: i64) -> LeaderboardRecord {
+        LeaderboardRecord {
+            account_id: AccountId::new(Uuid::from_u128(player)),
+            zone_id: ZoneId::new(Uuid::nil()),
+            zone_name: "World".to_string(),
+            position,
+            score: RaceTime::from_millis(score),
+        }
+    }
+
+    fn map_uid() -> MapUid {
+        "map".parse().unwrap()
+    }
+
+    #[test]
+    fn unchanged_leaderboard_has_no_events() {
+        let top = vec![record(1, 1, 40_000), record(2, 2, 41_000)];
+
+        assert!(diff(&map_uid(), Scope::World, &top, &top).is_empty());
+    }
+
+    #[test]
+    fn reports_personal_bests() {
+        let previous = vec![record(1, 1, 40_000), record(2, 2, 41_000)];
+        let current = vec![record(1, 1, 40_000), record(2, 2, 40_500)];
+
+        assert_eq!(
+            diff(&map_uid(), Scope::World, &previous, &current),
+            vec![LeaderboardEvent::PersonalBest {
+                map_uid: map_uid(),
+                scope: Scope::World,
+                record: record(2, 2, 40_500),
+                previous: record(2, 2, 41_000),
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_world_record_of_player_in_the_top() {
+        let previous = vec![record(1, 1, 40_000), record(2, 2, 41_000)];
+        let current = vec![record(2, 1, 39_000), record(1, 2, 40_000)];
+
+        assert_eq!(
+            diff(&map_uid(), Scope::World, &previous, &current),
+            vec![
+                LeaderboardEvent::NewWorldRecord {
+                    map_uid: map_uid(),
+                    scope: Scope::World,
+                    record: record(2, 1, 39_000),
+                    previous: Some(record(1, 1, 40_000)),
+                },
+                LeaderboardEvent::PersonalBest {
+                    map_uid: map_uid(),
+                    scope: Scope::World,
+                    record: record(2, 1, 39_000),
+                    previous: record(2, 2, 41_000),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn world_record_of_new_player_is_also_an_entry() {
+        let previous = vec![record(1, 1, 40_000), record(2, 2, 41_000)];
+        let current = vec![record(3, 1, 39_000), record(1, 2, 40_000)];
+
+        assert_eq!(
+            diff(&map_uid(), Scope::World, &previous, &current),
+            vec![
+                LeaderboardEvent::NewWorldRecord {
+                    map_uid: map_uid(),
+                    scope: Scope::World,
+                    record: record(3, 1, 39_000),
+                    previous: Some(record(1, 1, 40_000)),
+                },
+                LeaderboardEvent::Entered {
+                    map_uid: map_uid(),
+                    scope: Scope::World,
+                    record: record(3, 1, 39_000),
+                },
+                LeaderboardEvent::Left {
+                    map_uid: map_uid(),
+                    scope: Scope::World,
+                    previous: record(2, 2, 41_000),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn first_records_are_a_world_record_and_entries() {
+        let current = vec![record(1, 1, 40_000), record(2, 2, 41_000)];
+
+        assert_eq!(
+            diff(&map_uid(), Scope::World, &[], &current),
+            vec![
+                LeaderboardEvent::NewWorldRecord {
+                    map_uid: map_uid(),
+                    scope: Scope::World,
+                    record: record(1, 1, 40_000),
+                    previous: None,
+                },
+                LeaderboardEvent::Entered {
+                    map_uid: map_uid(),
+                    scope: Scope::World,
+                    record: record(1, 1, 40_000),
+                },
+                LeaderboardEvent::Entered {
+                    map_uid: map_uid(),
+                    scope: Scope::World,
+                    record: record(2, 2, 41_000),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn emptied_leaderboard_has_no_world_record() {
+        let previous = vec![record(1, 1, 40_000)];
+
+        assert_eq!(
+            diff(&map_uid(), Scope::World, &previous, &[]),
+            vec![LeaderboardEvent::Left {
+                map_uid: map_uid(),
+                scope: Scope::World,
+                previous: record(1, 1, 40_000),
+            }]
+        );
+    }
+}
@@ -20,10 +20,11 @@
 //!
 //! Use [`NadeoRequest::builder`] to create a `NadeoRequestBuilder`.
 //! To create a [`NadeoRequest`] you will need to supply:
-//! - an [`AuthType`]:
+//! - an [`AuthType`] (optional if the host of the `URL` is known, see [`AudienceTable`]):
 //!     - The depends on the API endpoint you want to make a request to.
 //!       If the endpoint requires `AuthType::NadeoServices` or `AuthType::NadeoLiveServices` you need to build the [`NadeoClient`] with `NadeoClientBuilder::with_normal_auth()`.
 //!       If the endpoint requires `AuthType::OAuth` you need to build the [`NadeoClient`] with `NadeoClientBuilder::with_oauth()`.
+//!       If the endpoint requires `AuthType::UbiServices` you need to build the [`NadeoClient`] with `NadeoClientBuilder::with_normal_auth()`.
 //! - an `URL`
 //! - a [`Method`]
 //!
@@ -59,13 +60,33 @@
 //! [`AuthType::NadeoLiveServices`]: auth::AuthType::NadeoLiveServices
 //! [`AuthType::OAuth`]: auth::AuthType::OAuth
 //! [`AuthType`]: auth::AuthType
+//! [`AudienceTable`]: request::audience::AudienceTable
 //! [`NadeoClientBuilder::with_normal_auth()`]: client::client_builder::NadeoClientBuilder::with_normal_auth
 //! [`NadeoClientBuilder::with_oauth_auth()`]: client::client_builder::NadeoClientBuilder::with_oauth
 
 pub mod auth;
+pub mod cache;
+pub mod campaigns;
 pub mod client;
+pub mod clubs;
+pub mod competitions;
+pub mod download;
 pub mod error;
+pub mod export;
+pub mod gbx;
+pub mod gbx_remote;
+pub mod leaderboards;
+pub mod loader;
+pub mod maps;
+pub mod matchmaking;
+pub mod pool;
+pub mod profiles;
+pub mod records;
 pub mod request;
+pub mod text;
+pub mod types;
+pub mod ubi_services;
+pub mod zones;
 
 pub use error::{Error, Result};
 
@@ -0,0 +1,545 @@
+//! Automatic batching of single-id lookups.
+//!
+//! Many endpoints accept lists of ids. A [`BatchLoader`] collects the ids requested by many tasks within a short window
+//! and requests them together, respecting the maximum list size of the endpoint and the maximum URL length.
+//! Every caller receives only its own item.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use nadeo_api::NadeoClient;
+//! # use nadeo_api::loader::{BatchLoader, DisplayNames};
+//! # use nadeo_api::types::AccountId;
+//! # async fn run(client: NadeoClient, account_id: AccountId) -> nadeo_api::Result<()> {
+//! let loader = BatchLoader::new(client, DisplayNames);
+//!
+//! // can be called from many tasks at once
+//! let name = loader.load(account_id).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::auth::AuthType;
+use crate::maps::MapInfo;
+use crate::types::{AccountId, MapUid, ZoneId};
+use crate::{Error, NadeoClient, NadeoRequest, Result};
+use futures::channel::oneshot;
+use futures::future::{join_all, BoxFuture};
+use futures::FutureExt;
+use reqwest::{Method, Response};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::mpsc;
+
+/// Future returned by [`BatchEndpoint::parse`].
+pub type ParseFuture<K, V> = BoxFuture<'static, Result<Vec<(K, V)>>>;
+
+/// An endpoint which accepts a list of ids.
+pub trait BatchEndpoint: Send + Sync + 'static {
+    type Key: Clone + Eq + Hash + Display + Send + Sync + 'static;
+    type Item: Clone + Send + 'static;
+
+    /// The [`AuthType`] required by the endpoint.
+    fn auth_type(&self) -> AuthType;
+
+    /// The maximum amount of ids per request.
+    fn max_batch_size(&self) -> usize;
+
+    /// Returns the URL for requesting the given ids.
+    fn url(&self, keys: &[Self::Key]) -> String;
+
+    /// Parses the response into the items and their ids. Ids without an item are left out.
+    fn parse(&self, res: Response) -> ParseFuture<Self::Key, Self::Item>;
+}
+
+/// Joins ids for URLs.
+fn join<K: Display>(keys: &[K], separator: &str) -> String {
+    keys.iter()
+        .map(K::to_string)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Display names of accounts. Requires [`AuthType::OAuth`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DisplayNames;
+
+impl BatchEndpoint for DisplayNames {
+    type Key = AccountId;
+    type Item = String;
+
+    fn auth_type(&self) -> AuthType {
+        AuthType::OAuth
+    }
+
+    fn max_batch_size(&self) -> usize {
+        50
+    }
+
+    fn url(&self, keys: &[Self::Key]) -> String {
+        format!(
+            "https://api.trackmania.com/api/display-names?accountId[]={}",
+            join(keys, "&accountId[]=")
+        )
+    }
+
+    fn parse(&self, res: Response) -> ParseFuture<Self::Key, Self::Item> {
+        async move {
+            let names = res.json::<HashMap<AccountId, String>>().await?;
+
+            Ok(names.into_iter().collect())
+        }
+        .boxed()
+    }
+}
+
+/// Club tags of accounts. Requires [`AuthType::NadeoServices`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ClubTags;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClubTag {
+    account_id: AccountId,
+    club_tag: String,
+}
+
+impl BatchEndpoint for ClubTags {
+    type Key = AccountId;
+    type Item = String;
+
+    fn auth_type(&self) -> AuthType {
+        AuthType::NadeoServices
+    }
+
+    fn max_batch_size(&self) -> usize {
+        50
+    }
+
+    fn url(&self, keys: &[Self::Key]) -> String {
+        format!(
+            "https://prod.trackmania.core.nadeo.online/accounts/clubTags/?accountIdList={}",
+            join(keys, ",")
+        )
+    }
+
+    fn parse(&self, res: Response) -> ParseFuture<Self::Key, Self::Item> {
+        async move {
+            let tags = res.json::<Vec<ClubTag>>().await?;
+
+            Ok(tags
+                .into_iter()
+                .map(|tag| (tag.account_id, tag.club_tag))
+                .collect())
+        }
+        .boxed()
+    }
+}
+
+/// Zones of accounts. Requires [`AuthType::NadeoServices`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Zones;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountZone {
+    account_id: AccountId,
+    zone_id: ZoneId,
+}
+
+impl BatchEndpoint for Zones {
+    type Key = AccountId;
+    type Item = ZoneId;
+
+    fn auth_type(&self) -> AuthType {
+        AuthType::NadeoServices
+    }
+
+    fn max_batch_size(&self) -> usize {
+        50
+    }
+
+    fn url(&self, keys: &[Self::Key]) -> String {
+        format!(
+            "https://prod.trackmania.core.nadeo.online/accounts/zones/?accountIdList={}",
+            join(keys, ",")
+        )
+    }
+
+    fn parse(&self, res: Response) -> ParseFuture<Self::Key, Self::Item> {
+        async move {
+            let zones = res.json::<Vec<AccountZone>>().await?;
+
+            Ok(zones
+                .into_iter()
+                .map(|zone| (zone.account_id, zone.zone_id))
+                .collect())
+        }
+        .boxed()
+    }
+}
+
+/// Map information by *mapUID*. Requires [`AuthType::NadeoServices`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Maps;
+
+impl BatchEndpoint for Maps {
+    type Key = MapUid;
+    type Item = MapInfo;
+
+    fn auth_type(&self) -> AuthType {
+        AuthType::NadeoServices
+    }
+
+    fn max_batch_size(&self) -> usize {
+        100
+    }
+
+    fn url(&self, keys: &[Self::Key]) -> String {
+        format!(
+            "https://prod.trackmania.core.nadeo.online/maps/?mapUidList={}",
+            join(keys, ",")
+        )
+    }
+
+    fn parse(&self, res: Response) -> ParseFuture<Self::Key, Self::Item> {
+        async move {
+            let maps = res.json::<Vec<MapInfo>>().await?;
+
+            Ok(maps
+                .into_iter()
+                .map(|map| (map.map_uid.clone(), map))
+                .collect())
+        }
+        .boxed()
+    }
+}
+
+type Reply<V> = oneshot::Sender<std::result::Result<Option<V>, String>>;
+
+struct Load<E: BatchEndpoint> {
+    key: E::Key,
+    reply: Reply<E::Item>,
+}
+
+/// Batches lookups of single ids into requests for lists of ids. See the [module documentation](self).
+///
+/// The loader can be cloned cheaply, all clones share the same batches.
+/// Must be created within a Tokio runtime.
+pub struct BatchLoader<E: BatchEndpoint> {
+    sender: mpsc::UnboundedSender<Load<E>>,
+}
+
+impl<E: BatchEndpoint> Clone for BatchLoader<E> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<E: BatchEndpoint> Debug for BatchLoader<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BatchLoader").finish_non_exhaustive()
+    }
+}
+
+/// Configures a [`BatchLoader`].
+#[derive(Debug, Clone, Copy)]
+pub struct BatchLoaderConfig {
+    /// How long ids are collected before the requests are sent.
+    pub window: Duration,
+    /// The maximum length of a request URL.
+    pub max_url_length: usize,
+}
+
+impl Default for BatchLoaderConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_millis(10),
+            max_url_length: 4096,
+        }
+    }
+}
+
+impl<E: BatchEndpoint> BatchLoader<E> {
+    /// Creates a loader with the default [`BatchLoaderConfig`].
+    pub fn new(client: NadeoClient, endpoint: E) -> Self {
+        Self::with_config(client, endpoint, BatchLoaderConfig::default())
+    }
+
+    pub fn with_config(client: NadeoClient, endpoint: E, config: BatchLoaderConfig) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(run(client, Arc::new(endpoint), config, receiver));
+
+        Self { sender }
+    }
+
+    /// Loads the item with the given id. Returns `None` if the endpoint did not return an item for the id.
+    pub async fn load(&self, key: E::Key) -> Result<Option<E::Item>> {
+        let (reply, receiver) = oneshot::channel();
+        self.sender
+            .send(Load { key, reply })
+            .map_err(|_| LoaderError::Closed)?;
+
+        match receiver.await {
+            Ok(Ok(item)) => Ok(item),
+            Ok(Err(e)) => Err(Error::from(LoaderError::BatchFailed(e))),
+            Err(_) => Err(Error::from(LoaderError::Closed)),
+        }
+    }
+
+    /// Loads multiple items. The result contains the items in the order of the ids.
+    pub async fn load_many(&self, keys: &[E::Key]) -> Result<Vec<Option<E::Item>>> {
+        join_all(keys.iter().cloned().map(|key| self.load(key)))
+            .await
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Collects lookups and dispatches them in batches until all loaders are dropped.
+///
+/// The task owns the client and refreshes its tokens before every batch, so the refreshed tokens are kept
+/// and the clones used for the concurrent requests of a batch have valid tokens.
+async fn run<E: BatchEndpoint>(
+    mut client: NadeoClient,
+    endpoint: Arc<E>,
+    config: BatchLoaderConfig,
+    mut receiver: mpsc::UnboundedReceiver<Load<E>>,
+) {
+    while let Some(first) = receiver.recv().await {
+        let mut pending = vec![first];
+
+        let window = tokio::time::sleep(config.window);
+        tokio::pin!(window);
+        loop {
+            tokio::select! {
+                _ = &mut window => break,
+                load = receiver.recv() => match load {
+                    Some(load) => pending.push(load),
+                    None => break,
+                },
+            }
+        }
+
+        if let Err(e) = client.ensure_auth(endpoint.auth_type()).await {
+            let e = e.to_string();
+            for load in pending {
+                let _ = load.reply.send(Err(e.clone()));
+            }
+            continue;
+        }
+        tokio::spawn(dispatch(
+            client.clone(),
+            Arc::clone(&endpoint),
+            config,
+            pending,
+        ));
+    }
+}
+
+/// Splits the ids into chunks respecting the maximum list size and URL length.
+fn chunks<E: BatchEndpoint>(
+    endpoint: &E,
+    keys: Vec<E::Key>,
+    max_url_length: usize,
+) -> Vec<Vec<E::Key>> {
+    let mut chunks = Vec::new();
+    let mut chunk: Vec<E::Key> = Vec::new();
+    for key in keys {
+        chunk.push(key);
+        let too_long = endpoint.url(&chunk).len() > max_url_length;
+        if chunk.len() > 1 && (too_long || chunk.len() > endpoint.max_batch_size()) {
+            let key = chunk.pop().unwrap();
+            chunks.push(std::mem::replace(&mut chunk, vec![key]));
+        }
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+
+    chunks
+}
+
+/// Requests the items of one chunk of ids.
+async fn request_chunk<E: BatchEndpoint>(
+    mut client: NadeoClient,
+    endpoint: &E,
+    chunk: &[E::Key],
+) -> Result<Vec<(E::Key, E::Item)>> {
+    let request = NadeoRequest::builder()
+        .url(&endpoint.url(chunk))
+        .auth_type(endpoint.auth_type())
+        .method(Method::GET)
+        .build()?;
+    let res = client.execute(request).await?;
+
+    endpoint.parse(res).await
+}
+
+/// Requests the items of all ids at once without waiting for other lookups. Fails if any of the requests fails.
+///
+/// The chunks are requested with clones of the client, so its tokens of the [`AuthType`] of the endpoint should be
+/// refreshed with [`NadeoClient::ensure_auth`] first.
+pub(crate) async fn load_all<E: BatchEndpoint>(
+    client: &NadeoClient,
+    endpoint: &E,
+    keys: &[E::Key],
+) -> Result<HashMap<E::Key, E::Item>> {
+    let max_url_length = BatchLoaderConfig::default().max_url_length;
+    let requests = chunks(endpoint, keys.to_vec(), max_url_length)
+        .into_iter()
+        .map(|chunk| {
+            let client = client.clone();
+            async move { request_chunk(client, endpoint, &chunk).await }
+        });
+
+    let mut items = HashMap::new();
+    for res in join_all(requests).await {
+        items.extend(res?);
+    }
+
+    Ok(items)
+}
+
+/// Requests the items of all pending lookups and replies to every caller.
+async fn dispatch<E: BatchEndpoint>(
+    client: NadeoClient,
+    endpoint: Arc<E>,
+    config: BatchLoaderConfig,
+    pending: Vec<Load<E>>,
+) {
+    let mut waiters: HashMap<E::Key, Vec<Reply<E::Item>>> = HashMap::new();
+    let mut keys = Vec::new();
+    for load in pending {
+        let replies = waiters.entry(load.key.clone()).or_default();
+        if replies.is_empty() {
+            keys.push(load.key);
+        }
+        replies.push(load.reply);
+    }
+
+    let requests = chunks(endpoint.as_ref(), keys, config.max_url_length)
+        .into_iter()
+        .map(|chunk| {
+            let client = client.clone();
+            let endpoint = Arc::clone(&endpoint);
+            async move {
+                let res = request_chunk(client, endpoint.as_ref(), &chunk).await;
+
+                (chunk, res)
+            }
+        });
+
+    for (chunk, res) in join_all(requests).await {
+        match res {
+            Ok(items) => {
+                let mut items = items.into_iter().collect::<HashMap<_, _>>();
+                for key in chunk {
+                    let item = items.remove(&key);
+                    for reply in waiters.remove(&key).unwrap_or_default() {
+                        let _ = reply.send(Ok(item.clone()));
+                    }
+                }
+            }
+            Err(e) => {
+                let e = e.to_string();
+                for key in chunk {
+                    for reply in waiters.remove(&key).unwrap_or_default() {
+                        let _ = reply.send(Err(e.clone()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Errors of a [`BatchLoader`].
+#[derive(Error, Debug)]
+pub enum LoaderError {
+    #[error("the batched request failed: {0}")]
+    BatchFailed(String),
+    #[error("the loader was shut down")]
+    Closed,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An endpoint with URLs like `https://example.com/?ids=1,2,3`.
+    struct Numbers {
+        max_batch_size: usize,
+    }
+
+    impl BatchEndpoint for Numbers {
+        type Key = u32;
+        type Item = ();
+
+        fn auth_type(&self) -> AuthType {
+            AuthType::NadeoServices
+        }
+
+        fn max_batch_size(&self) -> usize {
+            self.max_batch_size
+        }
+
+        fn url(&self, keys: &[Self::Key]) -> String {
+            format!("https://example.com/?ids={}", join(keys, ","))
+        }
+
+        fn parse(&self, _res: Response) -> ParseFuture<Self::Key, Self::Item> {
+            async { Ok(Vec::new()) }.boxed()
+        }
+    }
+
+    #[test]
+    fn chunks_by_batch_size() {
+        let endpoint = Numbers { max_batch_size: 3 };
+
+        assert_eq!(
+            chunks(&endpoint, (1..=7).collect(), 4096),
+            vec![vec![1, 2, 3], vec![4, 5, 6], vec![7]]
+        );
+        assert_eq!(chunks(&endpoint, vec![1, 2, 3], 4096), vec![vec![1, 2, 3]]);
+        assert!(chunks(&endpoint, Vec::new(), 4096).is_empty());
+    }
+
+    #[test]
+    fn chunks_by_url_length() {
+        let endpoint = Numbers {
+            max_batch_size: 100,
+        };
+        // the base URL has 24 characters, every id adds 2 or 3
+        let max_url_length = "https://example.com/?ids=10,11,12".len();
+
+        let chunks = chunks(&endpoint, (10..20).collect(), max_url_length);
+        assert_eq!(
+            chunks,
+            vec![
+                vec![10, 11, 12],
+                vec![13, 14, 15],
+                vec![16, 17, 18],
+                vec![19]
+            ]
+        );
+        for chunk in &chunks {
+            assert!(endpoint.url(chunk).len() <= max_url_length);
+        }
+    }
+
+    #[test]
+    fn single_ids_exceeding_the_url_length_are_kept() {
+        let endpoint = Numbers {
+            max_batch_size: 100,
+        };
+
+        assert_eq!(chunks(&endpoint, vec![1, 2], 10), vec![vec![1], vec![2]]);
+    }
+}
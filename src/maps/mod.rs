@@ -0,0 +1,397 @@
+use crate::auth::AuthType;
+use crate::gbx::MapHeader;
+use crate::request::multipart::MultipartForm;
+use crate::text::{self, FormattedText};
+use crate::types::{AccountId, MapId, MapUid, Medal, MedalTimes, RaceTime};
+use crate::{Error, NadeoClient, NadeoRequest, Result};
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::Path;
+use thiserror::Error;
+
+const MAPS_URL: &str = "https://prod.trackmania.core.nadeo.online/maps/";
+
+/// Information about a map from the core services.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MapInfo {
+    pub map_id: MapId,
+    pub map_uid: MapUid,
+    pub name: String,
+    pub author: AccountId,
+    pub submitter: AccountId,
+    pub author_score: RaceTime,
+    pub gold_score: RaceTime,
+    pub silver_score: RaceTime,
+    pub bronze_score: RaceTime,
+    #[serde(default)]
+    pub collection_name: String,
+    #[serde(default)]
+    pub map_style: String,
+    #[serde(default)]
+    pub map_type: String,
+    #[serde(default)]
+    pub filename: String,
+    /// URL of the `.Map.Gbx` file.
+    pub file_url: String,
+    pub thumbnail_url: String,
+    #[serde(default)]
+    pub is_playable: bool,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl MapInfo {
+    /// Returns the medal thresholds of the map.
+    pub fn medal_times(&self) -> MedalTimes {
+        MedalTimes {
+            author: self.author_score,
+            gold: self.gold_score,
+            silver: self.silver_score,
+            bronze: self.bronze_score,
+        }
+    }
+
+    /// Parses the formatting codes of the map name.
+    pub fn formatted_name(&self) -> FormattedText {
+        text::parse(&self.name)
+    }
+
+    /// Returns the best medal achieved on this map with the given time.
+    pub fn medal_for(&self, time: RaceTime) -> Medal {
+        self.medal_times().medal_for(time)
+    }
+}
+
+/// Metadata of a map upload.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MapUpload {
+    map_uid: MapUid,
+    name: String,
+    medal_times: MedalTimes,
+    author: Option<AccountId>,
+    collection: String,
+    map_type: String,
+    map_style: String,
+    created_with_simple_editor: bool,
+    created_with_gamepad_editor: bool,
+    playable: bool,
+}
+
+impl MapUpload {
+    /// Creates the metadata of a playable race map in the Stadium environment.
+    pub fn new(map_uid: MapUid, name: &str, medal_times: MedalTimes) -> Self {
+        Self {
+            map_uid,
+            name: name.to_string(),
+            medal_times,
+            author: None,
+            collection: "Stadium".to_string(),
+            map_type: "TrackMania\\TM_Race".to_string(),
+            map_style: String::new(),
+            created_with_simple_editor: false,
+            created_with_gamepad_editor: false,
+            playable: true,
+        }
+    }
+
+    /// Reads the metadata from the header of a `.Map.Gbx` file. The map must be validated.
+    pub fn from_header(header: &MapHeader) -> Result<Self> {
+        let map_uid = header.uid.parse::<MapUid>()?;
+        let medal_times = header.medal_times.ok_or(UploadError::NotValidated)?;
+
+        Ok(Self {
+            author: header.author_account_id(),
+            collection: header.collection.clone(),
+            map_type: header.map_type.clone(),
+            map_style: header.map_style.clone(),
+            created_with_simple_editor: header.created_with_simple_editor,
+            created_with_gamepad_editor: header.created_with_gamepad_editor,
+            ..Self::new(map_uid, &header.name, medal_times)
+        })
+    }
+
+    /// Parses the header of a `.Map.Gbx` file and reads the metadata using [`MapUpload::from_header`].
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        Self::from_header(&MapHeader::parse(data)?)
+    }
+
+    /// Sets the author. Defaults to the account of the client.
+    pub fn author(mut self, author: AccountId) -> Self {
+        self.author = Some(author);
+
+        self
+    }
+
+    /// Sets the environment, e.g. `Stadium`.
+    pub fn collection(mut self, collection: &str) -> Self {
+        self.collection = collection.to_string();
+
+        self
+    }
+
+    pub fn map_type(mut self, map_type: &str) -> Self {
+        self.map_type = map_type.to_string();
+
+        self
+    }
+
+    pub fn map_style(mut self, map_style: &str) -> Self {
+        self.map_style = map_style.to_string();
+
+        self
+    }
+
+    pub fn created_with_simple_editor(mut self, simple_editor: bool) -> Self {
+        self.created_with_simple_editor = simple_editor;
+
+        self
+    }
+
+    pub fn created_with_gamepad_editor(mut self, gamepad_editor: bool) -> Self {
+        self.created_with_gamepad_editor = gamepad_editor;
+
+        self
+    }
+
+    /// Whether the map can be played by other players. Enabled by default.
+    pub fn playable(mut self, playable: bool) -> Self {
+        self.playable = playable;
+
+        self
+    }
+}
+
+impl NadeoClient {
+    /// Gets information about a map by its *mapUID*. Returns `None` if the map does not exist.
+    /// Requires [`AuthType::NadeoServices`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use nadeo_api::NadeoClient;
+    /// # use nadeo_api::types::RaceTime;
+    /// # async fn run(mut client: NadeoClient) -> nadeo_api::Result<()> {
+    /// let map_uid = "olsKnq_qAghcVAnEkoeUnVHFZei".parse()?;
+    /// if let Some(map) = client.map_info(&map_uid).await? {
+    ///     let medal = map.medal_for(RaceTime::from_millis(45_123));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn map_info(&mut self, map_uid: &MapUid) -> Result<Option<MapInfo>> {
+        let maps = self.maps_info(std::slice::from_ref(map_uid)).await?;
+
+        Ok(maps.into_iter().next())
+    }
+
+    /// Gets information about multiple maps by their *mapUIDs*. Maps which do not exist are left out.
+    /// Requires [`AuthType::NadeoServices`].
+    pub async fn maps_info(&mut self, map_uids: &[MapUid]) -> Result<Vec<MapInfo>> {
+        let map_uids = map_uids
+            .iter()
+            .map(MapUid::as_str)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        self.get_maps(&format!("{MAPS_URL}?mapUidList={map_uids}"))
+            .await
+    }
+
+    /// Gets information about multiple maps by their *mapIDs*. Maps which do not exist are left out.
+    /// Requires [`AuthType::NadeoServices`].
+    pub async fn maps_info_by_id(&mut self, map_ids: &[MapId]) -> Result<Vec<MapInfo>> {
+        let map_ids = map_ids
+            .iter()
+            .map(MapId::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        self.get_maps(&format!("{MAPS_URL}?mapIdList={map_ids}"))
+            .await
+    }
+
+    async fn get_maps(&mut self, url: &str) -> Result<Vec<MapInfo>> {
+        let request = NadeoRequest::builder()
+            .url(url)
+            .auth_type(AuthType::NadeoServices)
+            .method(Method::GET)
+            .build()?;
+
+        let res = self.execute(request).await?;
+
+        Ok(res.json().await?)
+    }
+
+    /// Uploads a `.Map.Gbx` file and returns the information of the created map. The map must be validated.
+    /// If the upload has no author, the account of the client is used. Requires [`AuthType::NadeoServices`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use nadeo_api::NadeoClient;
+    /// # use nadeo_api::maps::MapUpload;
+    /// # async fn run(mut client: NadeoClient, data: Vec<u8>) -> nadeo_api::Result<()> {
+    /// let upload = MapUpload::parse(&data)?.playable(false);
+    /// let map = client.upload_map(data, &upload).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the author is unknown.
+    pub async fn upload_map(
+        &mut self,
+        data: impl Into<Bytes>,
+        upload: &MapUpload,
+    ) -> Result<MapInfo> {
+        let author = upload
+            .author
+            .or(self.account_id())
+            .ok_or(UploadError::UnknownAuthor)?;
+
+        let form = upload_form(upload, author, data.into());
+
+        let request = NadeoRequest::builder()
+            .url(MAPS_URL)
+            .auth_type(AuthType::NadeoServices)
+            .method(Method::POST)
+            .multipart(form)
+            .build()?;
+
+        let res = self.execute(request).await?;
+
+        Ok(res.json().await?)
+    }
+
+    /// Reads a `.Map.Gbx` file, extracts the metadata with [`MapUpload::parse`] and uploads it using [`NadeoClient::upload_map`].
+    /// Use [`NadeoClient::upload_map`] to change the metadata before uploading.
+    pub async fn upload_map_file(&mut self, path: impl AsRef<Path>) -> Result<MapInfo> {
+        let data = tokio::fs::read(path)
+            .await
+            .map_err(|e| Error::from(UploadError::Io(e)))?;
+        let upload = MapUpload::parse(&data)?;
+
+        self.upload_map(data, &upload).await
+    }
+}
+
+/// Builds the form of a map upload. The metadata is sent as JSON next to the file.
+fn upload_form(upload: &MapUpload, author: AccountId, data: Bytes) -> MultipartForm {
+    let parameters = json!(
+        {
+            "isPlayable": upload.playable,
+            "collectionName": upload.collection,
+            "createdWithSimpleEditor": upload.created_with_simple_editor,
+            "createdWithGamepadEditor": upload.created_with_gamepad_editor,
+            "mapUid": upload.map_uid,
+            "name": upload.name,
+            "authorScore": upload.medal_times.author,
+            "goldScore": upload.medal_times.gold,
+            "silverScore": upload.medal_times.silver,
+            "bronzeScore": upload.medal_times.bronze,
+            "mapType": upload.map_type,
+            "mapStyle": upload.map_style,
+            "author": author,
+        }
+    );
+
+    MultipartForm::new()
+        .json("nadeoservices-core-parameters", &parameters.to_string())
+        .file("data", &format!("{}.Map.Gbx", upload.map_uid), data)
+}
+
+/// Errors while uploading a map.
+#[derive(Error, Debug)]
+pub enum UploadError {
+    #[error("the map is not validated, it has no medal times")]
+    NotValidated,
+    #[error("the author of the map is unknown")]
+    UnknownAuthor,
+    #[error("the map file could not be read: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_support::{response, temp_path, TestServer};
+    use crate::gbx::GbxError;
+    use uuid::Uuid;
+
+    fn upload() -> MapUpload {
+        let medal_times = MedalTimes {
+            author: RaceTime::from_millis(40_000),
+            gold: RaceTime::from_millis(43_000),
+            silver: RaceTime::from_millis(49_000),
+            bronze: RaceTime::from_millis(60_000),
+        };
+
+        MapUpload::new(
+            "Xy1AbCdEfGhIjKlMnOpQrStUvW".parse().unwrap(),
+            "My map",
+            medal_times,
+        )
+        .map_style("Tech")
+        .playable(false)
+    }
+
+    #[tokio::test]
+    async fn upload_form_contains_parameters_and_file() {
+        let mut server = TestServer::start(|_| response(200, "text/plain", b"")).await;
+        let author = AccountId::new(Uuid::from_u128(1));
+        let form = upload_form(&upload(), author, Bytes::from_static(b"GBX data"));
+
+        reqwest::Client::new()
+            .post(&server.url)
+            .multipart(form.to_form().unwrap())
+            .send()
+            .await
+            .unwrap();
+        let body = server.next_request().await.body_text();
+
+        let parameters = body
+            .split("name=\"nadeoservices-core-parameters\"")
+            .nth(1)
+            .and_then(|part| part.split("\r\n\r\n").nth(1))
+            .and_then(|part| part.split("\r\n").next())
+            .unwrap();
+        let parameters = serde_json::from_str::<serde_json::Value>(parameters).unwrap();
+        assert_eq!(
+            parameters,
+            json!({
+                "isPlayable": false,
+                "collectionName": "Stadium",
+                "createdWithSimpleEditor": false,
+                "createdWithGamepadEditor": false,
+                "mapUid": "Xy1AbCdEfGhIjKlMnOpQrStUvW",
+                "name": "My map",
+                "authorScore": 40_000,
+                "goldScore": 43_000,
+                "silverScore": 49_000,
+                "bronzeScore": 60_000,
+                "mapType": "TrackMania\\TM_Race",
+                "mapStyle": "Tech",
+                "author": author,
+            })
+        );
+        assert!(body.contains(
+            "name=\"data\"; filename=\"Xy1AbCdEfGhIjKlMnOpQrStUvW.Map.Gbx\"\r\nContent-Type: application/octet-stream\r\n\r\nGBX data"
+        ));
+    }
+
+    #[tokio::test]
+    async fn upload_map_file_reads_the_metadata_from_the_file() {
+        let path = temp_path("upload.Map.Gbx");
+        tokio::fs::write(&path, b"not a map").await.unwrap();
+
+        // the metadata is parsed before anything is sent
+        let res = NadeoClient::for_tests().upload_map_file(&path).await;
+        let _ = tokio::fs::remove_file(&path).await;
+
+        assert!(matches!(res, Err(Error::Gbx(GbxError::InvalidMagic))));
+    }
+}
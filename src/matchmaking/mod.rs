@@ -0,0 +1,288 @@
+//! Ranks, leaderboards and matches of the matchmaking of the meet services.
+
+use crate::client::pagination::paginate;
+use crate::competitions::{meet_request, MEET_URL, PAGE_SIZE};
+use crate::types::{AccountId, MatchId};
+use crate::{NadeoClient, Result};
+use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Maximum number of accounts per rank request. Larger lookups are split into several requests.
+pub const MAX_PLAYERS_PER_REQUEST: usize = 50;
+
+/// Types of matchmaking.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum MatchmakingType {
+    /// The ranked 3v3 mode.
+    Ranked,
+    Royal,
+    /// A matchmaking type by its ID.
+    Other(u32),
+}
+
+impl MatchmakingType {
+    /// The ID of the matchmaking type in the meet services.
+    pub fn id(&self) -> u32 {
+        match self {
+            MatchmakingType::Ranked => 2,
+            MatchmakingType::Royal => 3,
+            MatchmakingType::Other(id) => *id,
+        }
+    }
+}
+
+/// Rank and points of a player in a matchmaking type.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+pub struct PlayerRank {
+    pub player: AccountId,
+    pub rank: u32,
+    /// Matchmaking points.
+    pub score: u32,
+}
+
+/// A division of a matchmaking type, e.g. *Gold II*.
+///
+/// Players are in the highest division whose point range contains their points and whose rank requirement they meet.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Division {
+    /// Position of the division starting at 1 for the lowest one.
+    pub position: u32,
+    #[serde(rename = "displayRuleMinimumPoints", default)]
+    pub minimum_points: Option<u32>,
+    #[serde(rename = "displayRuleMaximumPoints", default)]
+    pub maximum_points: Option<u32>,
+    /// Players need at least this rank, used by the highest divisions.
+    #[serde(rename = "displayRuleMinimumRank", default)]
+    pub minimum_rank: Option<u32>,
+}
+
+impl Division {
+    /// Whether the rank is in this division.
+    pub fn contains(&self, rank: &PlayerRank) -> bool {
+        self.minimum_points.is_none_or(|min| rank.score >= min)
+            && self.maximum_points.is_none_or(|max| rank.score <= max)
+            && self.minimum_rank.is_none_or(|min| rank.rank <= min)
+    }
+}
+
+impl PlayerRank {
+    /// Finds the division of the player in the divisions of the matchmaking type.
+    pub fn division<'a>(&self, divisions: &'a [Division]) -> Option<&'a Division> {
+        divisions
+            .iter()
+            .filter(|division| division.contains(self))
+            .max_by_key(|division| division.position)
+    }
+}
+
+/// A past matchmaking match of a player.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchmakingMatch {
+    pub id: MatchId,
+    /// ID of the match in the live services, e.g. `LID-MTCH-xxxxxxxxxxxxxxx`.
+    pub live_id: String,
+    #[serde(default, with = "chrono::serde::ts_seconds_option")]
+    pub start_date: Option<DateTime<Utc>>,
+    #[serde(default, with = "chrono::serde::ts_seconds_option")]
+    pub end_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub status: String,
+}
+
+#[derive(Deserialize)]
+struct RankPage {
+    results: Vec<PlayerRank>,
+}
+
+#[derive(Deserialize)]
+struct DivisionRules {
+    divisions: Vec<Division>,
+}
+
+impl NadeoClient {
+    /// Gets the ranks of accounts. Accounts without a rank are left out.
+    /// More than [`MAX_PLAYERS_PER_REQUEST`] accounts are looked up in several requests.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use nadeo_api::NadeoClient;
+    /// # use nadeo_api::matchmaking::MatchmakingType;
+    /// # async fn run(client: &mut NadeoClient, accounts: &[nadeo_api::types::AccountId]) -> nadeo_api::Result<()> {
+    /// let divisions = client.matchmaking_divisions(MatchmakingType::Ranked).await?;
+    /// for (account_id, rank) in client.matchmaking_ranks(MatchmakingType::Ranked, accounts).await? {
+    ///     println!("{account_id}: {:?}", rank.division(&divisions));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn matchmaking_ranks(
+        &mut self,
+        matchmaking: MatchmakingType,
+        account_ids: &[AccountId],
+    ) -> Result<HashMap<AccountId, PlayerRank>> {
+        let mut ranks = HashMap::new();
+        for chunk in account_ids.chunks(MAX_PLAYERS_PER_REQUEST) {
+            let players = chunk
+                .iter()
+                .map(|account_id| format!("players[]={account_id}"))
+                .collect::<Vec<_>>()
+                .join("&");
+            let page: RankPage = self
+                .meet_get(&format!(
+                    "{MEET_URL}/matchmaking/{}/leaderboard/players?{players}",
+                    matchmaking.id()
+                ))
+                .await?;
+            ranks.extend(page.results.into_iter().map(|rank| (rank.player, rank)));
+        }
+
+        Ok(ranks)
+    }
+
+    /// Streams the leaderboard of a matchmaking type starting at the top. Pages are requested while the stream is polled.
+    pub fn matchmaking_leaderboard(
+        &mut self,
+        matchmaking: MatchmakingType,
+    ) -> BoxStream<'_, Result<PlayerRank>> {
+        let id = matchmaking.id();
+        paginate(
+            self,
+            PAGE_SIZE,
+            move |offset, length| {
+                meet_request(&format!(
+                    "{MEET_URL}/matchmaking/{id}/leaderboard?length={length}&offset={offset}"
+                ))
+            },
+            |page: RankPage| page.results,
+        )
+    }
+
+    /// Gets the divisions of a matchmaking type ordered by their position.
+    pub async fn matchmaking_divisions(
+        &mut self,
+        matchmaking: MatchmakingType,
+    ) -> Result<Vec<Division>> {
+        let rules: DivisionRules = self
+            .meet_get(&format!(
+                "{MEET_URL}/matchmaking/{}/division/display-rules",
+                matchmaking.id()
+            ))
+            .await?;
+        let mut divisions = rules.divisions;
+        divisions.sort_by_key(|division| division.position);
+
+        Ok(divisions)
+    }
+
+    /// Streams the matchmaking matches of a player starting with the most recent one.
+    /// Pages are requested while the stream is polled.
+    pub fn matchmaking_history(
+        &mut self,
+        matchmaking: MatchmakingType,
+        account_id: AccountId,
+    ) -> BoxStream<'_, Result<MatchmakingMatch>> {
+        let id = matchmaking.id();
+        paginate(
+            self,
+            PAGE_SIZE,
+            move |offset, length| {
+                meet_request(&format!(
+                    "{MEET_URL}/matchmaking/{id}/player/{account_id}/matches?length={length}&offset={offset}"
+                ))
+            },
+            |page: Vec<MatchmakingMatch>| page,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn rank(rank: u32, score: u32) -> PlayerRank {
+        PlayerRank {
+            player: AccountId::new(Uuid::nil()),
+            rank,
+            score,
+        }
+    }
+
+    fn division(
+        position: u32,
+        minimum_points: Option<u32>,
+        maximum_points: Option<u32>,
+        minimum_rank: Option<u32>,
+    ) -> Division {
+        Division {
+            position,
+            minimum_points,
+            maximum_points,
+            minimum_rank,
+        }
+    }
+
+    fn divisions() -> Vec<Division> {
+        vec![
+            division(1, Some(100), Some(299), None),
+            division(2, Some(300), Some(599), None),
+            division(3, Some(600), None, None),
+            // the top division also requires a rank
+            division(4, Some(600), None, Some(10)),
+        ]
+    }
+
+    fn position(rank: PlayerRank) -> Option<u32> {
+        rank.division(&divisions())
+            .map(|division| division.position)
+    }
+
+    #[test]
+    fn contains_the_boundaries() {
+        let division = division(2, Some(300), Some(599), None);
+
+        assert!(!division.contains(&rank(500, 299)));
+        assert!(division.contains(&rank(500, 300)));
+        assert!(division.contains(&rank(500, 599)));
+        assert!(!division.contains(&rank(500, 600)));
+    }
+
+    #[test]
+    fn finds_the_division_by_points() {
+        assert_eq!(position(rank(5000, 100)), Some(1));
+        assert_eq!(position(rank(5000, 299)), Some(1));
+        assert_eq!(position(rank(3000, 300)), Some(2));
+        assert_eq!(position(rank(1000, 599)), Some(2));
+        assert_eq!(position(rank(100, 600)), Some(3));
+    }
+
+    #[test]
+    fn top_division_requires_the_rank() {
+        assert_eq!(position(rank(11, 2000)), Some(3));
+        assert_eq!(position(rank(10, 2000)), Some(4));
+        assert_eq!(position(rank(1, 600)), Some(4));
+        // the rank alone is not enough
+        assert_eq!(position(rank(1, 599)), Some(2));
+    }
+
+    #[test]
+    fn rank_without_division() {
+        assert_eq!(position(rank(9000, 99)), None);
+        assert_eq!(rank(1, 1000).division(&[]), None);
+    }
+
+    #[test]
+    fn deserializes_divisions() {
+        let division: Division = serde_json::from_str(
+            r#"{"position":4,"displayRuleMinimumPoints":600,"displayRuleMinimumRank":10}"#,
+        )
+        .unwrap();
+
+        assert_eq!(division, self::division(4, Some(600), None, Some(10)));
+    }
+}
@@ -0,0 +1,354 @@
+use crate::auth::circuit_breaker::AuthError;
+use crate::auth::AuthType;
+use crate::client::client_builder::NadeoClientBuilder;
+use crate::{Error, NadeoClient, NadeoRequest, Result};
+use futures::future::{join_all, BoxFuture};
+use futures::FutureExt;
+use reqwest::Response;
+use std::time::Instant;
+use thiserror::Error;
+
+/// Defines how an [`AccountPool`] picks the account for the next request.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum SelectionStrategy {
+    /// Uses the accounts one after another.
+    #[default]
+    RoundRobin,
+    /// Uses the account which was not used for the longest time.
+    LeastRecentlyUsed,
+}
+
+#[derive(Debug, Clone)]
+struct PooledAccount {
+    client: NadeoClient,
+    last_used: Option<Instant>,
+}
+
+/// A pool of accounts for spreading requests across multiple accounts, e.g. to stay within per-account rate limits.
+/// Every request is executed with a single account which is picked according to the [`SelectionStrategy`].
+/// Accounts whose credentials are rejected while logging in are removed from the pool.
+///
+/// # Examples
+///
+/// ```rust
+/// # use nadeo_api::pool::{AccountPool, SelectionStrategy};
+/// # async fn run() -> nadeo_api::Result<()> {
+/// let mut pool = AccountPool::builder()
+///     .with_server_auth("first_username", "first_password")
+///     .with_server_auth("second_username", "second_password")
+///     .strategy(SelectionStrategy::LeastRecentlyUsed)
+///     .user_agent("Testing the API / mustermann.max@gmail.com")
+///     .build()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AccountPool {
+    accounts: Vec<PooledAccount>,
+    strategy: SelectionStrategy,
+    next: usize,
+}
+
+impl AccountPool {
+    pub fn builder() -> AccountPoolBuilder {
+        AccountPoolBuilder::default()
+    }
+
+    /// Returns the number of accounts in the pool.
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// Returns `true` if all accounts have been removed from the pool.
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Executes a [`NadeoRequest`] with one of the accounts in the pool.
+    /// If the credentials of the account are rejected while logging in, it is removed from the pool and the request is retried with another account.
+    /// Error responses of the request itself, including `401 Unauthorized`, are returned without removing the account.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if no account in the pool supports the [`AuthType`] of the request or when there is an Error while executing the request.
+    pub async fn execute(&mut self, request: NadeoRequest) -> Result<Response> {
+        let auth_type = request.auth_type;
+        self.execute_with(auth_type, |client| client.execute(request.clone()).boxed())
+            .await
+    }
+
+    /// Runs `execute` with the selected account and removes accounts whose credentials are rejected until it succeeds
+    /// or fails for another reason.
+    async fn execute_with<T, F>(&mut self, auth_type: AuthType, mut execute: F) -> Result<T>
+    where
+        F: for<'a> FnMut(&'a mut NadeoClient) -> BoxFuture<'a, Result<T>>,
+    {
+        loop {
+            let Some(idx) = self.select(auth_type) else {
+                return Err(Error::from(PoolError::NoAccounts(auth_type)));
+            };
+
+            let account = &mut self.accounts[idx];
+            account.last_used = Some(Instant::now());
+            match execute(&mut account.client).await {
+                Err(e) if is_auth_failure(&e) => self.remove(idx),
+                res => return res,
+            }
+        }
+    }
+
+    /// Removes an account and keeps the position of the round-robin selection.
+    fn remove(&mut self, idx: usize) {
+        self.accounts.remove(idx);
+        if self.next > idx {
+            self.next -= 1;
+        }
+    }
+
+    /// Picks the next account which supports the given [`AuthType`].
+    fn select(&mut self, auth_type: AuthType) -> Option<usize> {
+        let len = self.accounts.len();
+        match self.strategy {
+            SelectionStrategy::RoundRobin => {
+                let idx = (0..len)
+                    .map(|offset| (self.next + offset) % len)
+                    .find(|&idx| self.accounts[idx].client.has_auth(auth_type))?;
+                self.next = idx + 1;
+
+                Some(idx)
+            }
+            SelectionStrategy::LeastRecentlyUsed => (0..len)
+                .filter(|&idx| self.accounts[idx].client.has_auth(auth_type))
+                .min_by_key(|&idx| self.accounts[idx].last_used),
+        }
+    }
+}
+
+/// Returns `true` if the credentials of the account were rejected while logging in or refreshing the tokens.
+fn is_auth_failure(err: &Error) -> bool {
+    matches!(err, Error::Auth(AuthError::InvalidCredentials))
+}
+
+/// Used for creating an [`AccountPool`]. Every call to `with_normal_auth`, `with_server_auth` or `with_account` adds another account.
+#[derive(Debug, Clone, Default)]
+pub struct AccountPoolBuilder {
+    accounts: Vec<NadeoClientBuilder>,
+    strategy: SelectionStrategy,
+    user_agent: Option<String>,
+}
+
+impl AccountPoolBuilder {
+    /// Adds a Ubisoft account.
+    pub fn with_normal_auth(self, email: &str, password: &str) -> Self {
+        self.with_account(NadeoClient::builder().with_normal_auth(email, password))
+    }
+
+    /// Adds a server account.
+    pub fn with_server_auth(self, username: &str, password: &str) -> Self {
+        self.with_account(NadeoClient::builder().with_server_auth(username, password))
+    }
+
+    /// Adds an account configured with a [`NadeoClientBuilder`]. If the builder has no UserAgent the one of the pool is used.
+    pub fn with_account(mut self, account: NadeoClientBuilder) -> Self {
+        self.accounts.push(account);
+
+        self
+    }
+
+    /// Sets the [`SelectionStrategy`]. Defaults to [`SelectionStrategy::RoundRobin`].
+    pub fn strategy(mut self, strategy: SelectionStrategy) -> Self {
+        self.strategy = strategy;
+
+        self
+    }
+
+    /// Adds a UserAgent which is used by every account. See [`NadeoClientBuilder::user_agent`].
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.to_string());
+
+        self
+    }
+
+    /// Trys to build an [`AccountPool`]. All accounts log in concurrently.
+    /// Accounts which are rejected while logging in are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if no account could log in or if logging in fails for another reason than invalid credentials.
+    pub async fn build(self) -> Result<AccountPool> {
+        let user_agent = self.user_agent;
+        let builds = self.accounts.into_iter().map(|account| match user_agent {
+            Some(ref user_agent) if !account.has_user_agent() => {
+                account.user_agent(user_agent).build()
+            }
+            _ => account.build(),
+        });
+
+        let mut accounts = Vec::new();
+        for res in join_all(builds).await {
+            match res {
+                Ok(client) => accounts.push(PooledAccount {
+                    client,
+                    last_used: None,
+                }),
+                Err(e) if is_auth_failure(&e) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        if accounts.is_empty() {
+            return Err(Error::from(PoolError::Empty));
+        }
+
+        Ok(AccountPool {
+            accounts,
+            strategy: self.strategy,
+            next: 0,
+        })
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum PoolError {
+    #[error("No account of the pool could log in")]
+    Empty,
+    #[error("No account of the pool has credentials for {0}")]
+    NoAccounts(AuthType),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::ClientError;
+    use futures::future;
+
+    fn pool(names: &[&str], strategy: SelectionStrategy) -> AccountPool {
+        AccountPool {
+            accounts: names
+                .iter()
+                .map(|name| {
+                    let mut client = NadeoClient::for_tests();
+                    client.meta_data.user_agent = name.to_string();
+                    PooledAccount {
+                        client,
+                        last_used: None,
+                    }
+                })
+                .collect(),
+            strategy,
+            next: 0,
+        }
+    }
+
+    /// Runs a request which fails with the given error for the accounts in `failing` and returns the used account.
+    async fn run(pool: &mut AccountPool, failing: &[&str], error: fn() -> Error) -> Result<String> {
+        pool.execute_with(AuthType::OAuth, |client| {
+            let name = client.meta_data.user_agent.clone();
+            let res = match failing.contains(&name.as_str()) {
+                true => Err(error()),
+                false => Ok(name),
+            };
+            future::ready(res).boxed()
+        })
+        .await
+    }
+
+    fn invalid_credentials() -> Error {
+        Error::from(AuthError::InvalidCredentials)
+    }
+
+    async fn sequence(pool: &mut AccountPool, failing: &[&str], count: usize) -> Vec<String> {
+        let mut used = Vec::new();
+        for _ in 0..count {
+            used.push(run(pool, failing, invalid_credentials).await.unwrap());
+        }
+
+        used
+    }
+
+    #[tokio::test]
+    async fn round_robin_uses_the_accounts_in_turn() {
+        let mut pool = pool(&["a", "b", "c"], SelectionStrategy::RoundRobin);
+
+        assert_eq!(sequence(&mut pool, &[], 4).await, vec!["a", "b", "c", "a"]);
+    }
+
+    #[tokio::test]
+    async fn round_robin_wraps_around_after_removing_the_last_account() {
+        let mut pool = pool(&["a", "b", "c"], SelectionStrategy::RoundRobin);
+        sequence(&mut pool, &[], 2).await;
+
+        // `c` is removed and the next account is `a`
+        assert_eq!(sequence(&mut pool, &["c"], 3).await, vec!["a", "b", "a"]);
+        assert_eq!(pool.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn round_robin_continues_after_removing_an_account() {
+        let mut pool = pool(&["a", "b", "c", "d"], SelectionStrategy::RoundRobin);
+        sequence(&mut pool, &[], 1).await;
+
+        assert_eq!(
+            sequence(&mut pool, &["b"], 4).await,
+            vec!["c", "d", "a", "c"]
+        );
+    }
+
+    #[tokio::test]
+    async fn least_recently_used_picks_the_oldest_account() {
+        let mut pool = pool(&["a", "b", "c"], SelectionStrategy::LeastRecentlyUsed);
+        // unused accounts come first
+        assert_eq!(sequence(&mut pool, &[], 3).await, vec!["a", "b", "c"]);
+
+        pool.accounts[1].last_used = Some(Instant::now() - std::time::Duration::from_secs(60));
+        assert_eq!(sequence(&mut pool, &[], 3).await, vec!["b", "a", "c"]);
+    }
+
+    #[tokio::test]
+    async fn removes_accounts_with_rejected_credentials() {
+        let mut pool = pool(&["a", "b"], SelectionStrategy::RoundRobin);
+
+        assert_eq!(
+            run(&mut pool, &["a"], invalid_credentials).await.unwrap(),
+            "b"
+        );
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn keeps_accounts_after_other_errors() {
+        let mut pool = pool(&["a", "b"], SelectionStrategy::RoundRobin);
+
+        let res = run(&mut pool, &["a"], || Error::from(ClientError::MissingOAuth)).await;
+
+        assert!(matches!(res, Err(Error::Client(ClientError::MissingOAuth))));
+        assert_eq!(pool.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn fails_without_accounts() {
+        let mut pool = pool(&["a", "b"], SelectionStrategy::RoundRobin);
+
+        let res = run(&mut pool, &["a", "b"], invalid_credentials).await;
+        assert!(matches!(
+            res,
+            Err(Error::Pool(PoolError::NoAccounts(AuthType::OAuth)))
+        ));
+        assert!(pool.is_empty());
+
+        // no account has credentials for the auth type
+        let mut pool = self::pool(&["a"], SelectionStrategy::LeastRecentlyUsed);
+        let request = NadeoRequest::builder()
+            .url("https://prod.trackmania.core.nadeo.online/")
+            .method(reqwest::Method::GET)
+            .auth_type(AuthType::NadeoServices)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            pool.execute(request).await,
+            Err(Error::Pool(PoolError::NoAccounts(AuthType::NadeoServices)))
+        ));
+        assert_eq!(pool.len(), 1);
+    }
+}
@@ -0,0 +1,381 @@
+//! Player profiles merged from the OAuth, core, live and meet services.
+
+use crate::auth::AuthType;
+use crate::loader::{load_all, ClubTags, DisplayNames, Zones};
+use crate::matchmaking::{MatchmakingType, PlayerRank};
+use crate::types::{AccountId, ZoneId};
+use crate::{NadeoClient, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::future::Future;
+
+const TROPHY_URL: &str =
+    "https://live-services.trackmania.nadeo.live/api/token/leaderboard/trophy/player";
+/// Maximum number of accounts per trophy request.
+const MAX_TROPHY_PLAYERS: usize = 50;
+
+/// Fields of a [`PlayerProfile`] which are requested separately.
+#[derive(strum::Display, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ProfileField {
+    DisplayName,
+    ClubTag,
+    Zone,
+    Trophies,
+    MatchmakingRank,
+}
+
+/// Trophy points of a player.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TrophyInfo {
+    #[serde(rename = "countPoint")]
+    pub points: u64,
+    /// The echelon from 0 to 9.
+    pub echelon: u32,
+}
+
+/// A player profile.
+///
+/// A field is `None` if the player has no value, e.g. no club tag, or if its request failed.
+/// Failed requests are listed in `errors`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PlayerProfile {
+    pub account_id: AccountId,
+    pub display_name: Option<String>,
+    pub club_tag: Option<String>,
+    pub zone_id: Option<ZoneId>,
+    pub trophies: Option<TrophyInfo>,
+    /// Rank in the ranked 3v3 matchmaking.
+    pub matchmaking_rank: Option<PlayerRank>,
+    pub errors: HashMap<ProfileField, String>,
+}
+
+impl PlayerProfile {
+    /// Whether every field was requested successfully.
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrophyRanking {
+    account_id: AccountId,
+    #[serde(flatten)]
+    trophies: TrophyInfo,
+}
+
+#[derive(Deserialize)]
+struct TrophyRankings {
+    rankings: Vec<TrophyRanking>,
+}
+
+impl NadeoClient {
+    /// Gets the profile of a player. See [`NadeoClient::player_profiles`].
+    pub async fn player_profile(&mut self, account_id: AccountId) -> PlayerProfile {
+        self.player_profiles(&[account_id])
+            .await
+            .pop()
+            .expect("one profile per account")
+    }
+
+    /// Gets the profiles of players in the order of the ids.
+    ///
+    /// Every field is requested for all players at once, using [`AuthType::OAuth`] for the display names,
+    /// [`AuthType::NadeoServices`] for club tags and zones and [`AuthType::NadeoLiveServices`] for trophies
+    /// and matchmaking ranks. The requests run concurrently and a failed request only affects its field.
+    /// The tokens of each [`AuthType`] are refreshed once before the requests. If that fails, the fields requiring it fail.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use nadeo_api::NadeoClient;
+    /// # async fn run(mut client: NadeoClient, account_id: nadeo_api::types::AccountId) {
+    /// let profile = client.player_profile(account_id).await;
+    /// println!("{:?} [{:?}]", profile.display_name, profile.club_tag);
+    ///
+    /// for (field, error) in &profile.errors {
+    ///     eprintln!("{field} failed: {error}");
+    /// }
+    /// # }
+    /// ```
+    pub async fn player_profiles(&mut self, account_ids: &[AccountId]) -> Vec<PlayerProfile> {
+        // clones don't share their tokens, so they are refreshed once here instead of by every request
+        let mut auth_errors = HashMap::new();
+        for auth_type in [
+            AuthType::OAuth,
+            AuthType::NadeoServices,
+            AuthType::NadeoLiveServices,
+        ] {
+            if let Err(e) = self.ensure_auth(auth_type).await {
+                auth_errors.insert(auth_type, e.to_string());
+            }
+        }
+
+        let (mut trophy_client, mut rank_client) = (self.clone(), self.clone());
+        let client = &*self;
+        let (display_names, club_tags, zones, trophies, ranks) = futures::join!(
+            authenticated(
+                auth_errors.get(&AuthType::OAuth),
+                load_all(client, &DisplayNames, account_ids)
+            ),
+            authenticated(
+                auth_errors.get(&AuthType::NadeoServices),
+                load_all(client, &ClubTags, account_ids)
+            ),
+            authenticated(
+                auth_errors.get(&AuthType::NadeoServices),
+                load_all(client, &Zones, account_ids)
+            ),
+            authenticated(
+                auth_errors.get(&AuthType::NadeoLiveServices),
+                trophy_client.trophies(account_ids)
+            ),
+            authenticated(
+                auth_errors.get(&AuthType::NadeoLiveServices),
+                rank_client.matchmaking_ranks(MatchmakingType::Ranked, account_ids)
+            ),
+        );
+
+        merge_profiles(
+            account_ids,
+            display_names,
+            club_tags,
+            zones,
+            trophies,
+            ranks,
+        )
+    }
+
+    /// Gets the trophy points of players. Requires [`AuthType::NadeoLiveServices`].
+    pub async fn trophies(
+        &mut self,
+        account_ids: &[AccountId],
+    ) -> Result<HashMap<AccountId, TrophyInfo>> {
+        let mut trophies = HashMap::new();
+        for chunk in account_ids.chunks(MAX_TROPHY_PLAYERS) {
+            let players = chunk
+                .iter()
+                .map(|account_id| json!({ "accountId": account_id }))
+                .collect::<Vec<_>>();
+            let rankings: TrophyRankings = self
+                .post_json(
+                    TROPHY_URL,
+                    AuthType::NadeoLiveServices,
+                    &json!({ "listPlayer": players }),
+                )
+                .await?;
+            trophies.extend(
+                rankings
+                    .rankings
+                    .into_iter()
+                    .map(|ranking| (ranking.account_id, ranking.trophies)),
+            );
+        }
+
+        Ok(trophies)
+    }
+}
+
+/// Merges the results of the fields into one profile per id. Every profile lists the errors of all failed fields.
+fn merge_profiles(
+    account_ids: &[AccountId],
+    display_names: std::result::Result<HashMap<AccountId, String>, String>,
+    club_tags: std::result::Result<HashMap<AccountId, String>, String>,
+    zones: std::result::Result<HashMap<AccountId, ZoneId>, String>,
+    trophies: std::result::Result<HashMap<AccountId, TrophyInfo>, String>,
+    ranks: std::result::Result<HashMap<AccountId, PlayerRank>, String>,
+) -> Vec<PlayerProfile> {
+    let mut errors = HashMap::new();
+    let display_names = or_error(&mut errors, ProfileField::DisplayName, display_names);
+    let club_tags = or_error(&mut errors, ProfileField::ClubTag, club_tags);
+    let zones = or_error(&mut errors, ProfileField::Zone, zones);
+    let trophies = or_error(&mut errors, ProfileField::Trophies, trophies);
+    let ranks = or_error(&mut errors, ProfileField::MatchmakingRank, ranks);
+
+    account_ids
+        .iter()
+        .map(|account_id| PlayerProfile {
+            account_id: *account_id,
+            display_name: display_names.get(account_id).cloned(),
+            club_tag: club_tags
+                .get(account_id)
+                .filter(|tag| !tag.is_empty())
+                .cloned(),
+            zone_id: zones.get(account_id).copied(),
+            trophies: trophies.get(account_id).copied(),
+            matchmaking_rank: ranks.get(account_id).copied(),
+            errors: errors.clone(),
+        })
+        .collect()
+}
+
+/// Runs the request of a field unless refreshing the tokens it requires failed.
+async fn authenticated<T>(
+    auth_error: Option<&String>,
+    request: impl Future<Output = Result<T>>,
+) -> std::result::Result<T, String> {
+    match auth_error {
+        Some(e) => Err(e.clone()),
+        None => request.await.map_err(|e| e.to_string()),
+    }
+}
+
+/// Returns the items of a field or records the error of the field.
+fn or_error<T: Default>(
+    errors: &mut HashMap<ProfileField, String>,
+    field: ProfileField,
+    res: std::result::Result<T, String>,
+) -> T {
+    res.unwrap_or_else(|e| {
+        errors.insert(field, e);
+        T::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn account(n: u128) -> AccountId {
+        AccountId::new(Uuid::from_u128(n))
+    }
+
+    fn names(names: &[(u128, &str)]) -> std::result::Result<HashMap<AccountId, String>, String> {
+        Ok(names
+            .iter()
+            .map(|(n, name)| (account(*n), name.to_string()))
+            .collect())
+    }
+
+    #[test]
+    fn merges_the_fields_by_account() {
+        let trophies = TrophyInfo {
+            points: 1234,
+            echelon: 3,
+        };
+        let rank = PlayerRank {
+            player: account(2),
+            rank: 10,
+            score: 3000,
+        };
+
+        let profiles = merge_profiles(
+            &[account(1), account(2)],
+            names(&[(1, "one"), (2, "two")]),
+            names(&[(1, "ONE"), (2, "")]),
+            Ok(HashMap::from([(
+                account(1),
+                ZoneId::new(Uuid::from_u128(9)),
+            )])),
+            Ok(HashMap::from([(account(2), trophies)])),
+            Ok(HashMap::from([(account(2), rank)])),
+        );
+
+        assert_eq!(
+            profiles,
+            vec![
+                PlayerProfile {
+                    account_id: account(1),
+                    display_name: Some("one".to_string()),
+                    club_tag: Some("ONE".to_string()),
+                    zone_id: Some(ZoneId::new(Uuid::from_u128(9))),
+                    trophies: None,
+                    matchmaking_rank: None,
+                    errors: HashMap::new(),
+                },
+                PlayerProfile {
+                    account_id: account(2),
+                    display_name: Some("two".to_string()),
+                    // an empty tag means no club tag
+                    club_tag: None,
+                    zone_id: None,
+                    trophies: Some(trophies),
+                    matchmaking_rank: Some(rank),
+                    errors: HashMap::new(),
+                },
+            ]
+        );
+        assert!(profiles.iter().all(PlayerProfile::is_complete));
+    }
+
+    #[test]
+    fn failed_field_is_recorded_in_every_profile() {
+        let profiles = merge_profiles(
+            &[account(1), account(2)],
+            names(&[(1, "one"), (2, "two")]),
+            Err("club tags failed".to_string()),
+            Ok(HashMap::new()),
+            Ok(HashMap::new()),
+            Err("ranks failed".to_string()),
+        );
+
+        for profile in &profiles {
+            assert!(profile.display_name.is_some());
+            assert_eq!(profile.club_tag, None);
+            assert!(!profile.is_complete());
+            assert_eq!(
+                profile.errors,
+                HashMap::from([
+                    (ProfileField::ClubTag, "club tags failed".to_string()),
+                    (ProfileField::MatchmakingRank, "ranks failed".to_string()),
+                ])
+            );
+        }
+    }
+
+    #[test]
+    fn missing_account_has_no_values_but_no_errors() {
+        let profiles = merge_profiles(
+            &[account(1), account(3)],
+            names(&[(1, "one")]),
+            names(&[(1, "ONE")]),
+            Ok(HashMap::new()),
+            Ok(HashMap::new()),
+            Ok(HashMap::new()),
+        );
+
+        assert_eq!(profiles[1].account_id, account(3));
+        assert_eq!(profiles[1].display_name, None);
+        assert_eq!(profiles[1].club_tag, None);
+        assert!(profiles[1].is_complete());
+    }
+
+    #[test]
+    fn duplicate_ids_get_the_same_values() {
+        let profiles = merge_profiles(
+            &[account(1), account(1)],
+            names(&[(1, "one")]),
+            names(&[(1, "ONE")]),
+            Ok(HashMap::new()),
+            Ok(HashMap::new()),
+            Ok(HashMap::new()),
+        );
+
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles[0], profiles[1]);
+        assert_eq!(profiles[1].display_name.as_deref(), Some("one"));
+        assert_eq!(profiles[1].club_tag.as_deref(), Some("ONE"));
+    }
+
+    #[test]
+    fn or_error_records_the_error() {
+        let mut errors = HashMap::new();
+
+        let ok = or_error(&mut errors, ProfileField::Zone, Ok(vec![1]));
+        let err: Vec<i32> = or_error(
+            &mut errors,
+            ProfileField::Trophies,
+            Err("failed".to_string()),
+        );
+
+        assert_eq!(ok, vec![1]);
+        assert!(err.is_empty());
+        assert_eq!(
+            errors,
+            HashMap::from([(ProfileField::Trophies, "failed".to_string())])
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use crate::auth::AuthType;
+use crate::types::{medal, AccountId, MapId, Medal, RaceTime};
+use crate::{NadeoClient, NadeoRequest, Result};
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+const MAP_RECORDS_URL: &str = "https://prod.trackmania.core.nadeo.online/v2/mapRecords/";
+
+/// The score of a [`MapRecord`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordScore {
+    pub time: RaceTime,
+    #[serde(default)]
+    pub score: i64,
+    #[serde(default)]
+    pub respawn_count: u32,
+}
+
+/// A record of a player on a map from the core services.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MapRecord {
+    pub map_record_id: String,
+    pub account_id: AccountId,
+    pub map_id: MapId,
+    #[serde(with = "medal::as_index")]
+    pub medal: Medal,
+    pub record_score: RecordScore,
+    #[serde(default)]
+    pub filename: String,
+    /// URL of the `.Ghost.Gbx` file.
+    pub url: String,
+    #[serde(default)]
+    pub removed: bool,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl NadeoClient {
+    /// Gets the records of accounts on a map. Accounts without a record are left out.
+    /// Requires [`AuthType::NadeoServices`].
+    pub async fn map_records(
+        &mut self,
+        map_id: &MapId,
+        account_ids: &[AccountId],
+    ) -> Result<Vec<MapRecord>> {
+        let account_ids = account_ids
+            .iter()
+            .map(AccountId::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let request = NadeoRequest::builder()
+            .url(&format!(
+                "{MAP_RECORDS_URL}?accountIdList={account_ids}&mapId={map_id}"
+            ))
+            .auth_type(AuthType::NadeoServices)
+            .method(Method::GET)
+            .build()?;
+
+        let res = self.execute(request).await?;
+
+        Ok(res.json().await?)
+    }
+}
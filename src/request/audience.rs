@@ -0,0 +1,159 @@
+use crate::auth::AuthType;
+use reqwest::Url;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Maps API hosts to the [`AuthType`] (audience) their endpoints require.
+/// Used by the [`NadeoRequestBuilder`] to infer the `AuthType` of a request from its URL.
+///
+/// The default table contains the known Nadeo, Trackmania and Ubisoft hosts.
+///
+/// # Examples
+///
+/// ```rust
+/// # use nadeo_api::auth::AuthType;
+/// # use nadeo_api::request::audience::AudienceTable;
+/// let table = AudienceTable::default().with_host("my-proxy.example.com", AuthType::NadeoLiveServices);
+///
+/// assert_eq!(
+///     table.get("https://live-services.trackmania.nadeo.live/api/token/campaign/official"),
+///     Some(AuthType::NadeoLiveServices)
+/// );
+/// ```
+///
+/// [`NadeoRequestBuilder`]: crate::request::request_builder::NadeoRequestBuilder
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AudienceTable {
+    hosts: HashMap<String, AuthType>,
+}
+
+impl Default for AudienceTable {
+    fn default() -> Self {
+        Self::empty()
+            .with_host("prod.trackmania.core.nadeo.online", AuthType::NadeoServices)
+            .with_host("core.trackmania.nadeo.online", AuthType::NadeoServices)
+            .with_host(
+                "live-services.trackmania.nadeo.live",
+                AuthType::NadeoLiveServices,
+            )
+            .with_host("meet.trackmania.nadeo.club", AuthType::NadeoLiveServices)
+            .with_host(
+                "competition.trackmania.nadeo.club",
+                AuthType::NadeoLiveServices,
+            )
+            .with_host(
+                "matchmaking.trackmania.nadeo.club",
+                AuthType::NadeoLiveServices,
+            )
+            .with_host("api.trackmania.com", AuthType::OAuth)
+            .with_host("public-ubiservices.ubi.com", AuthType::UbiServices)
+    }
+}
+
+impl AudienceTable {
+    /// Creates a table without any hosts.
+    pub fn empty() -> Self {
+        Self {
+            hosts: HashMap::new(),
+        }
+    }
+
+    /// Adds a host or replaces the [`AuthType`] of an existing one.
+    pub fn with_host(mut self, host: &str, auth_type: AuthType) -> Self {
+        self.hosts.insert(host.to_ascii_lowercase(), auth_type);
+
+        self
+    }
+
+    /// Removes a host.
+    pub fn without_host(mut self, host: &str) -> Self {
+        self.hosts.remove(&host.to_ascii_lowercase());
+
+        self
+    }
+
+    /// Returns the [`AuthType`] required by the host of the URL. Returns `None` if the URL is invalid or the host is unknown.
+    pub fn get(&self, url: &str) -> Option<AuthType> {
+        let url = Url::parse(url).ok()?;
+        let host = url.host_str()?.to_ascii_lowercase();
+
+        self.hosts.get(&host).copied()
+    }
+}
+
+/// Returns the default [`AudienceTable`].
+pub(crate) fn default_table() -> &'static AudienceTable {
+    static TABLE: OnceLock<AudienceTable> = OnceLock::new();
+    TABLE.get_or_init(AudienceTable::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infers_the_audience_from_the_host() {
+        let table = AudienceTable::default();
+
+        let urls = [
+            (
+                "https://prod.trackmania.core.nadeo.online/accounts/clubTags/",
+                AuthType::NadeoServices,
+            ),
+            (
+                "https://live-services.trackmania.nadeo.live/api/token/campaign/official",
+                AuthType::NadeoLiveServices,
+            ),
+            (
+                "https://meet.trackmania.nadeo.club/api/competitions/1",
+                AuthType::NadeoLiveServices,
+            ),
+            (
+                "https://api.trackmania.com/api/display-names",
+                AuthType::OAuth,
+            ),
+            (
+                "https://public-ubiservices.ubi.com/v3/profiles",
+                AuthType::UbiServices,
+            ),
+            // hosts are not case sensitive
+            ("HTTPS://API.TRACKMANIA.COM/api", AuthType::OAuth),
+        ];
+        for (url, auth_type) in urls {
+            assert_eq!(table.get(url), Some(auth_type), "{url}");
+        }
+    }
+
+    #[test]
+    fn unknown_hosts_and_invalid_urls_have_no_audience() {
+        let table = AudienceTable::default();
+
+        assert_eq!(table.get("https://example.com/api"), None);
+        // subdomains are not matched
+        assert_eq!(table.get("https://eu.api.trackmania.com/api"), None);
+        assert_eq!(table.get("not a url"), None);
+        assert_eq!(table.get("/relative/path"), None);
+    }
+
+    #[test]
+    fn hosts_can_be_changed() {
+        let table = AudienceTable::default()
+            .with_host("Proxy.example.com", AuthType::NadeoServices)
+            .with_host("api.trackmania.com", AuthType::NadeoLiveServices)
+            .without_host("public-ubiservices.ubi.com");
+
+        assert_eq!(
+            table.get("https://proxy.example.com/"),
+            Some(AuthType::NadeoServices)
+        );
+        assert_eq!(
+            table.get("https://api.trackmania.com/"),
+            Some(AuthType::NadeoLiveServices)
+        );
+        assert_eq!(table.get("https://public-ubiservices.ubi.com/"), None);
+        assert_eq!(
+            AudienceTable::empty().get("https://api.trackmania.com/"),
+            None
+        );
+    }
+}
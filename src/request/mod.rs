@@ -1,10 +1,14 @@
 use crate::auth::AuthType;
+use crate::request::multipart::MultipartForm;
 use crate::request::request_builder::NadeoRequestBuilder;
 use reqwest::header::HeaderMap;
+use reqwest::RequestBuilder;
 
 pub use reqwest::Method;
 pub use reqwest::Response;
 
+pub mod audience;
+pub mod multipart;
 pub mod request_builder;
 
 pub(crate) mod metadata;
@@ -40,7 +44,9 @@ pub struct NadeoRequest {
     pub(crate) url: String,
     pub(crate) method: Method,
     pub(crate) headers: HeaderMap,
-    pub(crate) body: Option<String>,
+    pub(crate) body: Option<RequestBody>,
+    pub(crate) bypass_cache: bool,
+    pub(crate) bypass_coalescing: bool,
 }
 
 impl NadeoRequest {
@@ -51,3 +57,28 @@ impl NadeoRequest {
         NadeoRequestBuilder::default()
     }
 }
+
+/// Body of a [`NadeoRequest`].
+#[derive(Debug, Clone)]
+pub(crate) enum RequestBody {
+    Text(String),
+    Multipart(MultipartForm),
+}
+
+impl RequestBody {
+    /// Returns the text body or `None` if it is a multipart body.
+    pub(crate) fn as_text(&self) -> Option<&str> {
+        match self {
+            RequestBody::Text(text) => Some(text),
+            RequestBody::Multipart(_) => None,
+        }
+    }
+
+    /// Adds the body to a `reqwest` request.
+    pub(crate) fn apply(self, request: RequestBuilder) -> crate::Result<RequestBuilder> {
+        match self {
+            RequestBody::Text(text) => Ok(request.body(text)),
+            RequestBody::Multipart(form) => Ok(request.multipart(form.to_form()?)),
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use bytes::Bytes;
+use reqwest::multipart::{Form, Part};
+
+/// A `multipart/form-data` body. Unlike [`reqwest::multipart::Form`] it can be cloned,
+/// which is required for retrying a [`NadeoRequest`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use nadeo_api::request::multipart::MultipartForm;
+/// let form = MultipartForm::new()
+///     .text("name", "My map")
+///     .file("data", "My map.Map.Gbx", vec![0u8; 16]);
+/// ```
+///
+/// [`NadeoRequest`]: crate::NadeoRequest
+#[derive(Debug, Clone, Default)]
+pub struct MultipartForm {
+    parts: Vec<(String, MultipartPart)>,
+}
+
+#[derive(Debug, Clone)]
+struct MultipartPart {
+    data: Bytes,
+    file_name: Option<String>,
+    mime: Option<String>,
+}
+
+impl MultipartForm {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn part(mut self, name: &str, part: MultipartPart) -> Self {
+        self.parts.push((name.to_string(), part));
+
+        self
+    }
+
+    /// Adds a text field.
+    pub fn text(self, name: &str, value: impl ToString) -> Self {
+        self.part(
+            name,
+            MultipartPart {
+                data: Bytes::from(value.to_string()),
+                file_name: None,
+                mime: None,
+            },
+        )
+    }
+
+    /// Adds a field containing JSON with the `application/json` content type.
+    pub fn json(self, name: &str, json: &str) -> Self {
+        self.part(
+            name,
+            MultipartPart {
+                data: Bytes::from(json.to_string()),
+                file_name: None,
+                mime: Some("application/json".to_string()),
+            },
+        )
+    }
+
+    /// Adds a file with the `application/octet-stream` content type.
+    pub fn file(self, name: &str, file_name: &str, data: impl Into<Bytes>) -> Self {
+        self.file_with_mime(name, file_name, "application/octet-stream", data)
+    }
+
+    /// Adds a file with the given content type.
+    pub fn file_with_mime(
+        self,
+        name: &str,
+        file_name: &str,
+        mime: &str,
+        data: impl Into<Bytes>,
+    ) -> Self {
+        self.part(
+            name,
+            MultipartPart {
+                data: data.into(),
+                file_name: Some(file_name.to_string()),
+                mime: Some(mime.to_string()),
+            },
+        )
+    }
+
+    /// Converts the form into a [`Form`] which can be sent by `reqwest`.
+    pub(crate) fn to_form(&self) -> reqwest::Result<Form> {
+        let mut form = Form::new();
+        for (name, part) in &self.parts {
+            let mut form_part = Part::stream(part.data.clone());
+            if let Some(ref file_name) = part.file_name {
+                form_part = form_part.file_name(file_name.clone());
+            }
+            if let Some(ref mime) = part.mime {
+                form_part = form_part.mime_str(mime)?;
+            }
+            form = form.part(name.clone(), form_part);
+        }
+
+        Ok(form)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_support::{response, TestServer};
+
+    #[tokio::test]
+    async fn to_form_sends_every_part() {
+        let mut server = TestServer::start(|_| response(200, "text/plain", b"")).await;
+        let form = MultipartForm::new()
+            .text("name", "My map")
+            .json("parameters", "{\"a\":1}")
+            .file("data", "map.Map.Gbx", vec![1u8, 2, 3])
+            .file_with_mime("thumbnail", "thumbnail.jpg", "image/jpeg", vec![4u8]);
+
+        // the form is cloned, so it can be sent again
+        for _ in 0..2 {
+            reqwest::Client::new()
+                .post(&server.url)
+                .multipart(form.to_form().unwrap())
+                .send()
+                .await
+                .unwrap();
+            let request = server.next_request().await;
+
+            assert!(request
+                .head
+                .to_lowercase()
+                .contains("content-type: multipart/form-data; boundary="));
+            let body = request.body;
+            let contains = |part: &[u8]| body.windows(part.len()).any(|w| w == part);
+            assert!(contains(
+                b"Content-Disposition: form-data; name=\"name\"\r\n\r\nMy map\r\n"
+            ));
+            assert!(contains(
+                b"Content-Disposition: form-data; name=\"parameters\"\r\nContent-Type: application/json\r\n\r\n{\"a\":1}\r\n"
+            ));
+            assert!(contains(
+                b"Content-Disposition: form-data; name=\"data\"; filename=\"map.Map.Gbx\"\r\nContent-Type: application/octet-stream\r\n\r\n\x01\x02\x03\r\n"
+            ));
+            assert!(contains(
+                b"Content-Disposition: form-data; name=\"thumbnail\"; filename=\"thumbnail.jpg\"\r\nContent-Type: image/jpeg\r\n\r\n\x04\r\n"
+            ));
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_mime() {
+        let form = MultipartForm::new().file_with_mime("data", "file", "not a mime", vec![0u8]);
+
+        assert!(form.to_form().is_err());
+    }
+}
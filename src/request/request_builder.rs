@@ -1,12 +1,15 @@
 use crate::auth::AuthType;
-use crate::request::NadeoRequest;
+use crate::request::audience::{default_table, AudienceTable};
+use crate::request::multipart::MultipartForm;
+use crate::request::{NadeoRequest, RequestBody};
 use crate::{Error, Result};
 use reqwest::header::{HeaderMap, IntoHeaderName};
 use reqwest::Method;
 use serde::{Deserialize, Serialize};
 
 /// Used for creating [`NadeoRequest`]s.
-/// `URL`, [`Method`] and [`AuthType`] must be provided.
+/// `URL` and [`Method`] must be provided.
+/// The [`AuthType`] is inferred from the host of the `URL` if it is not provided.
 ///
 /// [`NadeoRequest`]: NadeoRequest
 /// [`Method`]: Method
@@ -17,7 +20,10 @@ pub struct NadeoRequestBuilder {
     url: Option<String>,
     method: Option<Method>,
     headers: HeaderMap,
-    body: Option<String>,
+    body: Option<RequestBody>,
+    audience_table: Option<AudienceTable>,
+    bypass_cache: bool,
+    bypass_coalescing: bool,
 }
 
 /// Error when the Request is invalid. For example if a required field is missing.
@@ -27,14 +33,26 @@ pub enum RequestBuilderError {
     MissingUrl,
     #[error("no HTTP method was provided")]
     MissingHttpMethod,
-    #[error("no AuthType was provided")]
+    #[error("no AuthType was provided and it could not be inferred from the URL")]
     MissingAuthType,
+    #[error("the URL requires {expected} but {provided} was provided")]
+    AuthTypeMismatch {
+        expected: AuthType,
+        provided: AuthType,
+    },
 }
 
 impl NadeoRequestBuilder {
     /// Adds a text body to the request. Usually JSON.
     pub fn body(mut self, json: &str) -> Self {
-        self.body = Some(json.to_string());
+        self.body = Some(RequestBody::Text(json.to_string()));
+
+        self
+    }
+
+    /// Adds a `multipart/form-data` body to the request. Replaces any previously set body.
+    pub fn multipart(mut self, form: MultipartForm) -> Self {
+        self.body = Some(RequestBody::Multipart(form));
 
         self
     }
@@ -51,12 +69,35 @@ impl NadeoRequestBuilder {
         self
     }
 
+    /// Sets the [`AuthType`]. If the host of the `URL` is known the `AuthType` is inferred and this is not required.
     pub fn auth_type(mut self, auth_type: AuthType) -> Self {
         self.auth_type = Some(auth_type);
 
         self
     }
 
+    /// Replaces the [`AudienceTable`] used for inferring the [`AuthType`] from the host of the `URL`.
+    pub fn audience_table(mut self, table: AudienceTable) -> Self {
+        self.audience_table = Some(table);
+
+        self
+    }
+
+    /// Skips the response cache of the client for this request. The response is not stored in the cache either.
+    pub fn bypass_cache(mut self) -> Self {
+        self.bypass_cache = true;
+
+        self
+    }
+
+    /// Executes the request even if an identical request is in flight instead of sharing its response.
+    /// Shared responses are buffered in memory, so this should be used for large responses which are streamed.
+    pub fn bypass_coalescing(mut self) -> Self {
+        self.bypass_coalescing = true;
+
+        self
+    }
+
     /// Adds a header to the request. Adding a header should not be required in most cases.
     ///
     /// # Panics
@@ -71,7 +112,12 @@ impl NadeoRequestBuilder {
     }
 
     /// Converts the `NadeoRequestBuilder` into a [`NadeoRequest`].
-    /// `URL`, [`Method`] and [`AuthType`] are required.
+    /// `URL` and [`Method`] are required.
+    /// If no [`AuthType`] was provided it is inferred from the host of the `URL` using the [`AudienceTable`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if a required field is missing or if the provided `AuthType` does not match the one required by a known host.
     ///
     /// [`NadeoRequest`]: NadeoRequest
     /// [`Method`]: Method
@@ -83,16 +129,111 @@ impl NadeoRequestBuilder {
         if self.method.is_none() {
             return Err(Error::from(RequestBuilderError::MissingHttpMethod));
         }
-        if self.auth_type.is_none() {
-            return Err(Error::from(RequestBuilderError::MissingAuthType));
-        }
+
+        let table = self.audience_table.as_ref().unwrap_or(default_table());
+        let auth_type = match (self.auth_type, table.get(self.url.as_ref().unwrap())) {
+            (Some(provided), Some(expected)) if provided != expected => {
+                return Err(Error::from(RequestBuilderError::AuthTypeMismatch {
+                    expected,
+                    provided,
+                }));
+            }
+            (Some(auth_type), _) | (None, Some(auth_type)) => auth_type,
+            (None, None) => return Err(Error::from(RequestBuilderError::MissingAuthType)),
+        };
 
         Ok(NadeoRequest {
-            auth_type: self.auth_type.unwrap(),
+            auth_type,
             method: self.method.unwrap(),
             url: self.url.unwrap(),
             headers: self.headers,
             body: self.body,
+            bypass_cache: self.bypass_cache,
+            bypass_coalescing: self.bypass_coalescing,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORE_URL: &str = "https://prod.trackmania.core.nadeo.online/zones/";
+
+    fn builder(url: &str) -> NadeoRequestBuilder {
+        NadeoRequest::builder().url(url).method(Method::GET)
+    }
+
+    #[test]
+    fn infers_the_auth_type_from_the_url() {
+        let request = builder(CORE_URL).build().unwrap();
+
+        assert_eq!(request.auth_type, AuthType::NadeoServices);
+    }
+
+    #[test]
+    fn accepts_the_matching_auth_type() {
+        let request = builder(CORE_URL)
+            .auth_type(AuthType::NadeoServices)
+            .build()
+            .unwrap();
+
+        assert_eq!(request.auth_type, AuthType::NadeoServices);
+    }
+
+    #[test]
+    fn rejects_a_mismatching_auth_type() {
+        let res = builder(CORE_URL).auth_type(AuthType::OAuth).build();
+
+        assert!(matches!(
+            res,
+            Err(Error::Request(RequestBuilderError::AuthTypeMismatch {
+                expected: AuthType::NadeoServices,
+                provided: AuthType::OAuth,
+            }))
+        ));
+    }
+
+    #[test]
+    fn unknown_hosts_require_an_auth_type() {
+        assert!(matches!(
+            builder("https://example.com/api").build(),
+            Err(Error::Request(RequestBuilderError::MissingAuthType))
+        ));
+
+        let request = builder("https://example.com/api")
+            .auth_type(AuthType::NadeoLiveServices)
+            .build()
+            .unwrap();
+        assert_eq!(request.auth_type, AuthType::NadeoLiveServices);
+    }
+
+    #[test]
+    fn uses_the_given_audience_table() {
+        let table = AudienceTable::empty().with_host("example.com", AuthType::OAuth);
+
+        let request = builder("https://example.com/api")
+            .audience_table(table.clone())
+            .build()
+            .unwrap();
+        assert_eq!(request.auth_type, AuthType::OAuth);
+
+        // the default hosts are not known to the table
+        assert!(matches!(
+            builder(CORE_URL).audience_table(table).build(),
+            Err(Error::Request(RequestBuilderError::MissingAuthType))
+        ));
+    }
+
+    #[test]
+    fn requires_url_and_method() {
+        assert!(matches!(
+            NadeoRequest::builder().method(Method::GET).build(),
+            Err(Error::Request(RequestBuilderError::MissingUrl))
+        ));
+        assert!(matches!(
+            NadeoRequest::builder().url(CORE_URL).build(),
+            Err(Error::Request(RequestBuilderError::MissingHttpMethod))
+        ));
+    }
+}
@@ -0,0 +1,482 @@
+//! Parser for Trackmania formatted text.
+//!
+//! Names of maps, clubs and rooms can contain formatting codes like `$f00` (color), `$o` (bold) or `$l[url]` (link).
+//! [`parse`] turns such a string into a tree of styled [`Node`]s which can be rendered as plain text, HTML or ANSI colored text.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use nadeo_api::text;
+//! let name = text::parse("$f00Red $o$iBold $zplain");
+//!
+//! assert_eq!(name.to_plain(), "Red Bold plain");
+//! assert_eq!(text::strip("$l[https://example.com]link$l $$5"), "link $5");
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+mod render;
+
+/// A RGB color. Trackmania colors have 4 bits per channel, they are scaled to 8 bits.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    /// Parses a color code like `f00`. Missing digits are treated as `0`.
+    fn from_code(code: &str) -> Self {
+        let mut channels = code
+            .chars()
+            .map(|c| c.to_digit(16).unwrap_or(0) as u8 * 17)
+            .chain(std::iter::repeat(0));
+
+        Self {
+            r: channels.next().unwrap(),
+            g: channels.next().unwrap(),
+            b: channels.next().unwrap(),
+        }
+    }
+
+    /// Formats the color as hex, e.g. `#ff0000`.
+    pub fn to_hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+/// Width of the text.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Width {
+    #[default]
+    Normal,
+    /// `$w`
+    Wide,
+    /// `$n`
+    Narrow,
+}
+
+/// Style of a piece of text.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct Style {
+    /// `$rgb`, `None` if the default color is used (`$g`).
+    pub color: Option<Color>,
+    /// `$o`
+    pub bold: bool,
+    /// `$i`
+    pub italic: bool,
+    /// `$t`
+    pub uppercase: bool,
+    /// `$s`
+    pub shadow: bool,
+    pub width: Width,
+}
+
+/// A node of a parsed formatted text.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Node {
+    /// Text with a style.
+    Text { text: String, style: Style },
+    /// A link (`$l`) or a manialink (`$h`, `$p`). `url` is `None` for manialinks.
+    Link {
+        url: Option<String>,
+        children: Vec<Node>,
+    },
+    /// Nodes between `$<` and `$>`. Style changes inside the group do not affect text after it.
+    Group(Vec<Node>),
+}
+
+/// A parsed formatted text. Created with [`parse`].
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FormattedText {
+    pub nodes: Vec<Node>,
+}
+
+impl Display for FormattedText {
+    /// Displays the text without formatting.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_plain())
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum LinkKind {
+    Link,
+    Manialink,
+}
+
+enum Container {
+    Root,
+    Group { style: Style },
+    Link { kind: LinkKind, url: Option<String> },
+}
+
+struct Parser {
+    /// Open containers with their children.
+    stack: Vec<(Container, Vec<Node>)>,
+    style: Style,
+    text: String,
+}
+
+impl Parser {
+    fn new() -> Self {
+        Self {
+            stack: vec![(Container::Root, Vec::new())],
+            style: Style::default(),
+            text: String::new(),
+        }
+    }
+
+    /// Adds the pending text to the current container.
+    fn flush(&mut self) {
+        if self.text.is_empty() {
+            return;
+        }
+        let text = std::mem::take(&mut self.text);
+        let nodes = &mut self.stack.last_mut().unwrap().1;
+
+        match nodes.last_mut() {
+            Some(Node::Text {
+                text: prev,
+                style: prev_style,
+            }) if *prev_style == self.style => prev.push_str(&text),
+            _ => nodes.push(Node::Text {
+                text,
+                style: self.style,
+            }),
+        }
+    }
+
+    fn set_style(&mut self, style: Style) {
+        if style != self.style {
+            self.flush();
+            self.style = style;
+        }
+    }
+
+    fn open(&mut self, container: Container) {
+        self.flush();
+        self.stack.push((container, Vec::new()));
+    }
+
+    /// Closes the innermost container and adds it to its parent.
+    fn close(&mut self) {
+        self.flush();
+        let (container, children) = self.stack.pop().unwrap();
+        let node = match container {
+            Container::Root => unreachable!("the root container is never closed"),
+            Container::Group { style } => {
+                self.style = style;
+                Node::Group(children)
+            }
+            Container::Link { url, .. } => Node::Link { url, children },
+        };
+        self.stack.last_mut().unwrap().1.push(node);
+    }
+
+    fn open_link(&mut self) -> Option<LinkKind> {
+        self.stack
+            .iter()
+            .rev()
+            .find_map(|(container, _)| match container {
+                Container::Link { kind, .. } => Some(*kind),
+                _ => None,
+            })
+    }
+
+    /// Closes the open link and all groups opened inside of it.
+    fn close_link(&mut self) {
+        if self.open_link().is_none() {
+            return;
+        }
+        while !matches!(self.stack.last().unwrap().0, Container::Link { .. }) {
+            self.close();
+        }
+        self.close();
+    }
+
+    fn finish(mut self) -> FormattedText {
+        while self.stack.len() > 1 {
+            self.close();
+        }
+        self.flush();
+
+        FormattedText {
+            nodes: self.stack.pop().unwrap().1,
+        }
+    }
+}
+
+/// Parses a string containing Trackmania formatting codes.
+pub fn parse(input: &str) -> FormattedText {
+    let mut parser = Parser::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            parser.text.push(c);
+            continue;
+        }
+        let Some(code) = chars.next() else {
+            break;
+        };
+
+        let mut style = parser.style;
+        match code.to_ascii_lowercase() {
+            '$' => parser.text.push('$'),
+            c if c.is_ascii_hexdigit() => {
+                let mut color = String::from(c);
+                while color.len() < 3 {
+                    match chars.peek() {
+                        Some(c) if c.is_ascii_hexdigit() => color.push(chars.next().unwrap()),
+                        _ => break,
+                    }
+                }
+                style.color = Some(Color::from_code(&color));
+                parser.set_style(style);
+            }
+            'g' => {
+                style.color = None;
+                parser.set_style(style);
+            }
+            'o' => {
+                style.bold = true;
+                parser.set_style(style);
+            }
+            'i' => {
+                style.italic = true;
+                parser.set_style(style);
+            }
+            't' => {
+                style.uppercase = true;
+                parser.set_style(style);
+            }
+            's' => {
+                style.shadow = true;
+                parser.set_style(style);
+            }
+            'w' => {
+                style.width = Width::Wide;
+                parser.set_style(style);
+            }
+            'n' => {
+                style.width = Width::Narrow;
+                parser.set_style(style);
+            }
+            'm' => {
+                style.width = Width::Normal;
+                parser.set_style(style);
+            }
+            'z' => parser.set_style(Style::default()),
+            '<' => parser.open(Container::Group {
+                style: parser.style,
+            }),
+            '>' => {
+                if let Some(depth) = parser
+                    .stack
+                    .iter()
+                    .rposition(|(container, _)| matches!(container, Container::Group { .. }))
+                {
+                    while parser.stack.len() > depth {
+                        parser.close();
+                    }
+                }
+            }
+            'l' | 'h' | 'p' => {
+                let kind = if code.eq_ignore_ascii_case(&'l') {
+                    LinkKind::Link
+                } else {
+                    LinkKind::Manialink
+                };
+                let mut target = None;
+                if chars.peek() == Some(&'[') {
+                    chars.next();
+                    target = Some(chars.by_ref().take_while(|&c| c != ']').collect::<String>());
+                }
+
+                // a code without a target closes the open link
+                let closes = parser.open_link().is_some() && target.is_none();
+                parser.close_link();
+                if !closes {
+                    let url = match kind {
+                        LinkKind::Link => Some(target.unwrap_or_default()),
+                        LinkKind::Manialink => None,
+                    };
+                    parser.open(Container::Link { kind, url });
+                }
+            }
+            // unknown codes are ignored
+            _ => {}
+        }
+    }
+
+    parser.finish()
+}
+
+/// Removes all formatting codes from a string.
+pub fn strip(input: &str) -> String {
+    let mut nodes = parse(input).nodes;
+    strip_nodes(&mut nodes);
+
+    FormattedText { nodes }.to_plain()
+}
+
+/// Resets the style of all nodes, so the plain text is not uppercased.
+fn strip_nodes(nodes: &mut [Node]) {
+    for node in nodes {
+        match node {
+            Node::Text { style, .. } => *style = Style::default(),
+            Node::Link { children, .. } | Node::Group(children) => strip_nodes(children),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str, style: Style) -> Node {
+        Node::Text {
+            text: text.to_string(),
+            style,
+        }
+    }
+
+    fn bold() -> Style {
+        Style {
+            bold: true,
+            ..Default::default()
+        }
+    }
+
+    fn red() -> Style {
+        Style {
+            color: Some(Color { r: 255, g: 0, b: 0 }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn groups_restore_the_style() {
+        let parsed = parse("a$<$ob$<$f00c$>d$>e");
+
+        assert_eq!(
+            parsed.nodes,
+            vec![
+                text("a", Style::default()),
+                Node::Group(vec![
+                    text("b", bold()),
+                    Node::Group(vec![text(
+                        "c",
+                        Style {
+                            bold: true,
+                            ..red()
+                        }
+                    )]),
+                    text("d", bold()),
+                ]),
+                text("e", Style::default()),
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed_groups_are_closed_at_the_end() {
+        let parsed = parse("$<$oa$<b");
+
+        assert_eq!(
+            parsed.nodes,
+            vec![Node::Group(vec![
+                text("a", bold()),
+                Node::Group(vec![text("b", bold())]),
+            ])]
+        );
+        assert_eq!(parse("a$>b").nodes, vec![text("ab", Style::default())]);
+    }
+
+    #[test]
+    fn links() {
+        let parsed = parse("$l[https://example.com]link$l text");
+
+        assert_eq!(
+            parsed.nodes,
+            vec![
+                Node::Link {
+                    url: Some("https://example.com".to_string()),
+                    children: vec![text("link", Style::default())],
+                },
+                text(" text", Style::default()),
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed_links_end_with_the_text() {
+        let parsed = parse("a$l[https://example.com]b$<$oc");
+
+        assert_eq!(
+            parsed.nodes,
+            vec![
+                text("a", Style::default()),
+                Node::Link {
+                    url: Some("https://example.com".to_string()),
+                    children: vec![
+                        text("b", Style::default()),
+                        Node::Group(vec![text("c", bold())]),
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn link_with_a_target_replaces_the_open_link() {
+        let parsed = parse("$l[a]x$l[b]y");
+
+        assert_eq!(
+            parsed.nodes,
+            vec![
+                Node::Link {
+                    url: Some("a".to_string()),
+                    children: vec![text("x", Style::default())],
+                },
+                Node::Link {
+                    url: Some("b".to_string()),
+                    children: vec![text("y", Style::default())],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn escaped_dollar() {
+        assert_eq!(parse("$$5 $$$o").to_plain(), "$5 $");
+        assert_eq!(strip("a$$b"), "a$b");
+        assert_eq!(strip("trailing$"), "trailing");
+    }
+
+    #[test]
+    fn codes_are_case_insensitive() {
+        assert_eq!(parse("$Oa").nodes, vec![text("a", bold())]);
+        assert_eq!(parse("$F00a").nodes, vec![text("a", red())]);
+        assert_eq!(parse("$oa$Zb").nodes[1], text("b", Style::default()));
+        assert_eq!(
+            parse("$L[url]a$Lb").nodes,
+            vec![
+                Node::Link {
+                    url: Some("url".to_string()),
+                    children: vec![text("a", Style::default())],
+                },
+                text("b", Style::default()),
+            ]
+        );
+    }
+
+    #[test]
+    fn short_colors_are_padded() {
+        assert_eq!(parse("$f0 a").nodes, vec![text(" a", red())]);
+        assert_eq!(parse("$fxa").nodes, vec![text("xa", red())]);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::text::{FormattedText, Node, Style, Width};
+
+impl FormattedText {
+    /// Renders the text without any formatting.
+    pub fn to_plain(&self) -> String {
+        let mut out = String::new();
+        plain(&self.nodes, &mut out);
+        out
+    }
+
+    /// Renders the text as HTML using `<span>`s with inline styles and `<a>`s for links.
+    /// Text and URLs are escaped and only `http` and `https` links are rendered as `<a>`.
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        html(&self.nodes, &mut out);
+        out
+    }
+
+    /// Renders the text with ANSI escape codes for terminals with true color support.
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::new();
+        ansi(&self.nodes, &mut out);
+        if !out.is_empty() {
+            out.push_str("\x1b[0m");
+        }
+        out
+    }
+}
+
+fn apply_case(text: &str, style: &Style) -> String {
+    if style.uppercase {
+        text.to_uppercase()
+    } else {
+        text.to_string()
+    }
+}
+
+fn plain(nodes: &[Node], out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text { text, style } => out.push_str(&apply_case(text, style)),
+            Node::Link { children, .. } | Node::Group(children) => plain(children, out),
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn css(style: &Style) -> String {
+    let mut css = Vec::new();
+    if let Some(color) = style.color {
+        css.push(format!("color:{}", color.to_hex()));
+    }
+    if style.bold {
+        css.push("font-weight:bold".to_string());
+    }
+    if style.italic {
+        css.push("font-style:italic".to_string());
+    }
+    if style.uppercase {
+        css.push("text-transform:uppercase".to_string());
+    }
+    if style.shadow {
+        css.push("text-shadow:1px 1px 1px rgba(0,0,0,0.5)".to_string());
+    }
+    match style.width {
+        Width::Normal => {}
+        Width::Wide => css.push("letter-spacing:0.1em".to_string()),
+        Width::Narrow => css.push("letter-spacing:-0.1em".to_string()),
+    }
+    css.join(";")
+}
+
+/// Returns the URL of a link. Links without a target (`$lexample.com$l`) use their text.
+/// Returns `None` if the URL is not safe to use in HTML.
+fn link_url(url: &str, children: &[Node]) -> Option<String> {
+    let mut url = url.trim().to_string();
+    if url.is_empty() {
+        plain(children, &mut url);
+    }
+    let lower = url.to_ascii_lowercase();
+    if lower.starts_with("https://") || lower.starts_with("http://") {
+        Some(url)
+    } else if !url.is_empty() && !lower.contains(':') {
+        Some(format!("https://{url}"))
+    } else {
+        None
+    }
+}
+
+fn html(nodes: &[Node], out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text { text, style } => {
+                let css = css(style);
+                if css.is_empty() {
+                    out.push_str(&escape_html(text));
+                } else {
+                    out.push_str(&format!(
+                        "<span style=\"{}\">{}</span>",
+                        css,
+                        escape_html(text)
+                    ));
+                }
+            }
+            Node::Link { url, children } => {
+                match url.as_deref().and_then(|url| link_url(url, children)) {
+                    Some(url) => {
+                        out.push_str(&format!("<a href=\"{}\">", escape_html(&url)));
+                        html(children, out);
+                        out.push_str("</a>");
+                    }
+                    None => html(children, out),
+                }
+            }
+            Node::Group(children) => html(children, out),
+        }
+    }
+}
+
+fn ansi(nodes: &[Node], out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text { text, style } => {
+                let mut codes = vec!["0".to_string()];
+                if let Some(color) = style.color {
+                    codes.push(format!("38;2;{};{};{}", color.r, color.g, color.b));
+                }
+                if style.bold {
+                    codes.push("1".to_string());
+                }
+                if style.italic {
+                    codes.push("3".to_string());
+                }
+                out.push_str(&format!(
+                    "\x1b[{}m{}",
+                    codes.join(";"),
+                    apply_case(text, style)
+                ));
+            }
+            Node::Link { children, .. } | Node::Group(children) => ansi(children, out),
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use thiserror::Error;
+use uuid::Uuid;
+
+macro_rules! uuid_id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Display, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name(Uuid);
+
+        impl $name {
+            pub fn new(uuid: Uuid) -> Self {
+                Self(uuid)
+            }
+
+            pub fn as_uuid(&self) -> &Uuid {
+                &self.0
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = IdError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Uuid::parse_str(s)
+                    .map(Self)
+                    .map_err(|_| IdError::InvalidUuid(s.to_string()))
+            }
+        }
+
+        impl From<Uuid> for $name {
+            fn from(uuid: Uuid) -> Self {
+                Self(uuid)
+            }
+        }
+    };
+}
+
+uuid_id!(
+    /// The *accountID* of a Trackmania player. This is the same as the Ubisoft user id.
+    AccountId
+);
+impl AccountId {
+    /// Converts an in-game login, e.g. the author login of a map, into an accountID.
+    /// Logins are the URL-safe base64 encoding of the accountID.
+    pub fn from_login(login: &str) -> Result<Self, IdError> {
+        URL_SAFE_NO_PAD
+            .decode(login)
+            .ok()
+            .and_then(|bytes| Uuid::from_slice(&bytes).ok())
+            .map(Self)
+            .ok_or_else(|| IdError::InvalidLogin(login.to_string()))
+    }
+
+    /// Converts the accountID into an in-game login.
+    pub fn to_login(&self) -> String {
+        URL_SAFE_NO_PAD.encode(self.0.as_bytes())
+    }
+}
+
+uuid_id!(
+    /// The *mapID* of a map. Not to be confused with the [`MapUid`].
+    MapId
+);
+uuid_id!(
+    /// The *zoneID* of a zone, e.g. a country or region.
+    ZoneId
+);
+uuid_id!(
+    /// The *seasonID* of a season, e.g. of a campaign.
+    SeasonId
+);
+
+/// The *mapUID* of a map. Not to be confused with the [`MapId`].
+#[derive(Debug, Display, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct MapUid(String);
+
+impl MapUid {
+    /// Maximum length of a mapUID.
+    const MAX_LEN: usize = 32;
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for MapUid {
+    type Err = IdError;
+
+    /// A mapUID consists of up to 32 ASCII letters, digits and underscores.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let valid = !s.is_empty()
+            && s.len() <= Self::MAX_LEN
+            && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(IdError::InvalidMapUid(s.to_string()));
+        }
+
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl TryFrom<String> for MapUid {
+    type Error = IdError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<MapUid> for String {
+    fn from(value: MapUid) -> Self {
+        value.0
+    }
+}
+
+macro_rules! numeric_id {
+    ($(#[$meta:meta])* $name:ident, $id_type:literal) => {
+        $(#[$meta])*
+        #[derive(
+            Debug, Display, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize,
+        )]
+        #[serde(transparent)]
+        pub struct $name(u64);
+
+        impl $name {
+            pub fn new(id: u64) -> Self {
+                Self(id)
+            }
+
+            pub fn get(&self) -> u64 {
+                self.0
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = IdError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.parse()
+                    .map(Self)
+                    .map_err(|_| IdError::InvalidNumericId {
+                        id_type: $id_type,
+                        value: s.to_string(),
+                    })
+            }
+        }
+
+        impl From<u64> for $name {
+            fn from(id: u64) -> Self {
+                Self(id)
+            }
+        }
+    };
+}
+
+numeric_id!(
+    /// The *clubID* of a club.
+    ClubId,
+    "clubID"
+);
+numeric_id!(
+    /// The *activityID* of a club activity, e.g. a room or a campaign.
+    ActivityId,
+    "activityID"
+);
+numeric_id!(
+    /// The *campaignID* of a campaign.
+    CampaignId,
+    "campaignID"
+);
+numeric_id!(
+    /// The *roomID* of a club room.
+    RoomId,
+    "roomID"
+);
+numeric_id!(
+    /// The ID of a competition of the meet services.
+    CompetitionId,
+    "competition ID"
+);
+numeric_id!(
+    /// The ID of a round of a competition.
+    RoundId,
+    "round ID"
+);
+numeric_id!(
+    /// The ID of a match of a competition round.
+    MatchId,
+    "match ID"
+);
+
+/// Errors for parsing identifiers.
+#[derive(Error, Debug)]
+pub enum IdError {
+    #[error("{0:?} is not a valid UUID")]
+    InvalidUuid(String),
+    #[error("{0:?} is not a valid mapUID")]
+    InvalidMapUid(String),
+    #[error("{value:?} is not a valid {id_type}")]
+    InvalidNumericId {
+        id_type: &'static str,
+        value: String,
+    },
+    #[error("{0:?} is not a valid login")]
+    InvalidLogin(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACCOUNT: &str = "5b4d42f4-c2de-407d-b367-cbff3fe817bc";
+
+    #[test]
+    fn parses_map_uids() {
+        for uid in ["olsKnq_qAghcVAnEkoeUnVHFZei", "a", &"a".repeat(32)] {
+            assert_eq!(uid.parse::<MapUid>().unwrap().as_str(), uid);
+        }
+        for uid in ["", &"a".repeat(33), "map uid", "map-uid", "mäp"] {
+            assert!(
+                matches!(uid.parse::<MapUid>(), Err(IdError::InvalidMapUid(ref val)) if val == uid),
+                "{uid:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn converts_logins() {
+        let account_id = ACCOUNT.parse::<AccountId>().unwrap();
+
+        let login = account_id.to_login();
+        assert_eq!(login, "W01C9MLeQH2zZ8v_P-gXvA");
+        assert_eq!(AccountId::from_login(&login).unwrap(), account_id);
+        for login in ["", "not a login", "W01C9MLeQH2zZ8v_P-gX"] {
+            assert!(matches!(
+                AccountId::from_login(login),
+                Err(IdError::InvalidLogin(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn numeric_id_errors_name_the_type() {
+        let err = "abc".parse::<ClubId>().unwrap_err();
+        assert!(matches!(
+            err,
+            IdError::InvalidNumericId {
+                id_type: "clubID",
+                ..
+            }
+        ));
+        assert_eq!(err.to_string(), "\"abc\" is not a valid clubID");
+        assert_eq!(
+            "-1".parse::<RoomId>().unwrap_err().to_string(),
+            "\"-1\" is not a valid roomID"
+        );
+        assert_eq!("42".parse::<MatchId>().unwrap(), MatchId::new(42));
+    }
+
+    #[test]
+    fn ids_are_serialized_transparently() {
+        let account_id = ACCOUNT.parse::<AccountId>().unwrap();
+        assert_eq!(
+            serde_json::to_string(&account_id).unwrap(),
+            format!("\"{ACCOUNT}\"")
+        );
+        assert_eq!(
+            serde_json::from_str::<AccountId>(&format!("\"{ACCOUNT}\"")).unwrap(),
+            account_id
+        );
+
+        assert_eq!(serde_json::to_string(&ClubId::new(42)).unwrap(), "42");
+        assert_eq!(
+            serde_json::from_str::<ClubId>("42").unwrap(),
+            ClubId::new(42)
+        );
+
+        let map_uid = "olsKnq_qAghcVAnEkoeUnVHFZei".parse::<MapUid>().unwrap();
+        assert_eq!(
+            serde_json::to_string(&map_uid).unwrap(),
+            "\"olsKnq_qAghcVAnEkoeUnVHFZei\""
+        );
+        assert_eq!(
+            serde_json::from_str::<MapUid>("\"olsKnq_qAghcVAnEkoeUnVHFZei\"").unwrap(),
+            map_uid
+        );
+        // mapUIDs are validated while deserializing
+        assert!(serde_json::from_str::<MapUid>("\"map uid\"").is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use crate::types::RaceTime;
+use serde::{Deserialize, Serialize};
+
+/// Medals which can be achieved on a map. Medals are ordered from [`Medal::None`] to [`Medal::Author`].
+#[derive(
+    strum::Display, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize,
+)]
+pub enum Medal {
+    None,
+    Bronze,
+    Silver,
+    Gold,
+    Author,
+}
+
+/// The medal thresholds of a map.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct MedalTimes {
+    pub author: RaceTime,
+    pub gold: RaceTime,
+    pub silver: RaceTime,
+    pub bronze: RaceTime,
+}
+
+impl MedalTimes {
+    /// Returns the best medal achieved with the given time.
+    /// Negative times, e.g. the `-1` used by the API for a missing time, get [`Medal::None`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use nadeo_api::types::{Medal, MedalTimes, RaceTime};
+    /// let medals = MedalTimes {
+    ///     author: RaceTime::from_millis(40_000),
+    ///     gold: RaceTime::from_millis(43_000),
+    ///     silver: RaceTime::from_millis(49_000),
+    ///     bronze: RaceTime::from_millis(60_000),
+    /// };
+    ///
+    /// assert_eq!(medals.medal_for(RaceTime::from_millis(42_500)), Medal::Gold);
+    /// ```
+    pub fn medal_for(&self, time: RaceTime) -> Medal {
+        if time.is_negative() {
+            Medal::None
+        } else if time <= self.author {
+            Medal::Author
+        } else if time <= self.gold {
+            Medal::Gold
+        } else if time <= self.silver {
+            Medal::Silver
+        } else if time <= self.bronze {
+            Medal::Bronze
+        } else {
+            Medal::None
+        }
+    }
+
+    /// Returns the time required for the given medal. Returns `None` for [`Medal::None`].
+    pub fn time_for(&self, medal: Medal) -> Option<RaceTime> {
+        match medal {
+            Medal::None => None,
+            Medal::Bronze => Some(self.bronze),
+            Medal::Silver => Some(self.silver),
+            Medal::Gold => Some(self.gold),
+            Medal::Author => Some(self.author),
+        }
+    }
+}
+
+impl Medal {
+    /// Returns the medal for the index used by the API (`0` = none, `4` = author). Returns `None` for unknown indices.
+    pub fn from_index(index: u8) -> Option<Self> {
+        match index {
+            0 => Some(Self::None),
+            1 => Some(Self::Bronze),
+            2 => Some(Self::Silver),
+            3 => Some(Self::Gold),
+            4 => Some(Self::Author),
+            _ => None,
+        }
+    }
+
+    /// Returns the index used by the API.
+    pub fn index(&self) -> u8 {
+        *self as u8
+    }
+}
+
+/// (De)serializes a [`Medal`] as its index, as used by the API.
+pub(crate) mod as_index {
+    use super::Medal;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        medal: &Medal,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(medal.index())
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Medal, D::Error> {
+        let index = u8::deserialize(deserializer)?;
+        Medal::from_index(index).ok_or_else(|| D::Error::custom(format!("invalid medal {index}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEDALS: MedalTimes = MedalTimes {
+        author: RaceTime::from_millis(40_000),
+        gold: RaceTime::from_millis(43_000),
+        silver: RaceTime::from_millis(49_000),
+        bronze: RaceTime::from_millis(60_000),
+    };
+
+    #[test]
+    fn medal_for_thresholds() {
+        assert_eq!(
+            MEDALS.medal_for(RaceTime::from_millis(39_999)),
+            Medal::Author
+        );
+        assert_eq!(
+            MEDALS.medal_for(RaceTime::from_millis(40_000)),
+            Medal::Author
+        );
+        assert_eq!(MEDALS.medal_for(RaceTime::from_millis(40_001)), Medal::Gold);
+        assert_eq!(
+            MEDALS.medal_for(RaceTime::from_millis(49_000)),
+            Medal::Silver
+        );
+        assert_eq!(
+            MEDALS.medal_for(RaceTime::from_millis(60_000)),
+            Medal::Bronze
+        );
+        assert_eq!(MEDALS.medal_for(RaceTime::from_millis(60_001)), Medal::None);
+    }
+
+    #[test]
+    fn medal_for_missing_time() {
+        assert_eq!(MEDALS.medal_for(RaceTime::from_millis(-1)), Medal::None);
+        assert_eq!(
+            MEDALS.medal_for(RaceTime::from_millis(i64::MIN)),
+            Medal::None
+        );
+    }
+}
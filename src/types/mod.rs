@@ -0,0 +1,12 @@
+//! Domain types shared by the typed API endpoints.
+
+pub mod id;
+pub mod medal;
+pub mod race_time;
+
+pub use id::{
+    AccountId, ActivityId, CampaignId, ClubId, CompetitionId, MapId, MapUid, MatchId, RoomId,
+    RoundId, SeasonId, ZoneId,
+};
+pub use medal::{Medal, MedalTimes};
+pub use race_time::RaceTime;
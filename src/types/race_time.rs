@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+use std::time::Duration;
+use thiserror::Error;
+
+/// A race time (or a difference of race times) in milliseconds.
+///
+/// Formatted like in Trackmania, e.g. `0:45.123` or `1:02:03.456`.
+/// Differences are prefixed with a `-` if they are negative.
+///
+/// # Examples
+///
+/// ```rust
+/// # use nadeo_api::types::RaceTime;
+/// let time: RaceTime = "0:45.123".parse().unwrap();
+/// assert_eq!(time, RaceTime::from_millis(45_123));
+///
+/// let split = time - RaceTime::from_millis(46_000);
+/// assert_eq!(split.to_string(), "-0:00.877");
+/// ```
+#[derive(
+    Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct RaceTime(i64);
+
+impl RaceTime {
+    pub const ZERO: Self = Self(0);
+
+    pub const fn from_millis(millis: i64) -> Self {
+        Self(millis)
+    }
+
+    pub const fn as_millis(&self) -> i64 {
+        self.0
+    }
+
+    /// Returns `true` if the time is a negative difference.
+    pub const fn is_negative(&self) -> bool {
+        self.0 < 0
+    }
+
+    pub const fn abs(&self) -> Self {
+        Self(self.0.abs())
+    }
+
+    /// Converts the time into a [`Duration`]. Returns `None` if it is negative.
+    pub fn to_duration(&self) -> Option<Duration> {
+        u64::try_from(self.0).ok().map(Duration::from_millis)
+    }
+}
+
+impl From<Duration> for RaceTime {
+    fn from(duration: Duration) -> Self {
+        Self(duration.as_millis() as i64)
+    }
+}
+
+impl Display for RaceTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.is_negative() { "-" } else { "" };
+        let millis = self.0.unsigned_abs();
+
+        let ms = millis % 1000;
+        let secs = millis / 1000 % 60;
+        let mins = millis / 60_000 % 60;
+        let hours = millis / 3_600_000;
+
+        if hours > 0 {
+            write!(f, "{sign}{hours}:{mins:02}:{secs:02}.{ms:03}")
+        } else {
+            write!(f, "{sign}{mins}:{secs:02}.{ms:03}")
+        }
+    }
+}
+
+impl FromStr for RaceTime {
+    type Err = ParseRaceTimeError;
+
+    /// Parses times like `45.123`, `0:45.123` or `1:02:03.456`. The fraction may have 1 to 3 digits.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseRaceTimeError(s.to_string());
+
+        let (negative, time) = match s.trim().strip_prefix('-') {
+            Some(time) => (true, time),
+            None => (false, s.trim()),
+        };
+        let (whole, fraction) = time.split_once('.').unwrap_or((time, ""));
+
+        let parts = whole.split(':').collect::<Vec<_>>();
+        if parts.len() > 3 || parts.iter().any(|part| part.is_empty()) {
+            return Err(err());
+        }
+        let mut millis = 0i64;
+        for (idx, part) in parts.iter().enumerate() {
+            if !part.chars().all(|c| c.is_ascii_digit()) {
+                return Err(err());
+            }
+            let val = part.parse::<i64>().map_err(|_| err())?;
+            // minutes and seconds following a larger unit must be below 60
+            if idx > 0 && val >= 60 {
+                return Err(err());
+            }
+            millis = millis
+                .checked_mul(60)
+                .and_then(|millis| millis.checked_add(val))
+                .ok_or_else(err)?;
+        }
+        millis = millis.checked_mul(1000).ok_or_else(err)?;
+
+        if !fraction.is_empty() {
+            if fraction.len() > 3 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+                return Err(err());
+            }
+            let padded = format!("{:0<3}", fraction);
+            millis = millis
+                .checked_add(padded.parse::<i64>().map_err(|_| err())?)
+                .ok_or_else(err)?;
+        }
+
+        Ok(Self(if negative { -millis } else { millis }))
+    }
+}
+
+impl Add for RaceTime {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for RaceTime {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for RaceTime {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for RaceTime {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Neg for RaceTime {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(-self.0)
+    }
+}
+
+impl Sum for RaceTime {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, Add::add)
+    }
+}
+
+/// Error when a string is not a valid [`RaceTime`].
+#[derive(Error, Debug, Eq, PartialEq)]
+#[error("{0:?} is not a valid race time")]
+pub struct ParseRaceTimeError(String);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_formats() {
+        assert_eq!("45.123".parse(), Ok(RaceTime::from_millis(45_123)));
+        assert_eq!("0:45.1".parse(), Ok(RaceTime::from_millis(45_100)));
+        assert_eq!("1:02:03.456".parse(), Ok(RaceTime::from_millis(3_723_456)));
+        assert_eq!("-0:00.877".parse(), Ok(RaceTime::from_millis(-877)));
+        assert_eq!("61".parse(), Ok(RaceTime::from_millis(61_000)));
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        for input in [
+            "",
+            "1:60.000",
+            "1::00",
+            "0:45.1234",
+            "a:00",
+            "1:2:3:4",
+            "0:45.+1",
+        ] {
+            assert!(input.parse::<RaceTime>().is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn rejects_overflowing_input() {
+        for input in [
+            "9223372036854775807",
+            "153722867280912930",
+            "99999999999999999999:00",
+            "2562047788015215:30:08.000",
+        ] {
+            assert_eq!(
+                input.parse::<RaceTime>(),
+                Err(ParseRaceTimeError(input.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        for millis in [0, 877, 45_123, 3_723_456, -61_000] {
+            let time = RaceTime::from_millis(millis);
+            assert_eq!(time.to_string().parse(), Ok(time));
+        }
+    }
+}
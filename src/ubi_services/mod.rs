@@ -0,0 +1,148 @@
+use crate::auth::AuthType;
+use crate::types::AccountId;
+use crate::{NadeoClient, NadeoRequest, Result};
+use reqwest::{Method, Url};
+use serde::{Deserialize, Serialize};
+
+const UBI_PROFILES_URL: &str = "https://public-ubiservices.ubi.com/v3/profiles";
+
+/// Platforms a Ubisoft profile can be linked to.
+#[derive(strum::Display, Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum PlatformType {
+    Uplay,
+    Steam,
+    Psn,
+    Xbl,
+    /// A platform which is not known to this crate.
+    #[serde(other)]
+    Unknown,
+}
+
+/// A Ubisoft profile.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UbiProfile {
+    pub profile_id: String,
+    /// The Ubisoft user id. For Trackmania this is the same as the *accountID*.
+    pub user_id: AccountId,
+    pub platform_type: PlatformType,
+    pub id_on_platform: String,
+    pub name_on_platform: String,
+}
+
+#[derive(Deserialize)]
+struct UbiProfiles {
+    profiles: Vec<UbiProfile>,
+}
+
+impl NadeoClient {
+    /// Looks up Ubisoft profiles by their name on a platform. Requires [`AuthType::UbiServices`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use nadeo_api::NadeoClient;
+    /// # use nadeo_api::ubi_services::PlatformType;
+    /// # async fn run(mut client: NadeoClient) -> nadeo_api::Result<()> {
+    /// let profiles = client.ubi_profiles_by_name("TgZ39", PlatformType::Uplay).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn ubi_profiles_by_name(
+        &mut self,
+        name: &str,
+        platform: PlatformType,
+    ) -> Result<Vec<UbiProfile>> {
+        self.ubi_profiles(&[
+            ("nameOnPlatform", name),
+            ("platformType", &platform.to_string()),
+        ])
+        .await
+    }
+
+    /// Looks up Ubisoft profiles by their user ids (*accountIDs*). Requires [`AuthType::UbiServices`].
+    pub async fn ubi_profiles_by_user_ids(
+        &mut self,
+        user_ids: &[AccountId],
+    ) -> Result<Vec<UbiProfile>> {
+        let user_ids = user_ids
+            .iter()
+            .map(AccountId::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        self.ubi_profiles(&[("userIds", &user_ids)]).await
+    }
+
+    async fn ubi_profiles(&mut self, params: &[(&str, &str)]) -> Result<Vec<UbiProfile>> {
+        let url = Url::parse_with_params(UBI_PROFILES_URL, params).unwrap();
+        let request = NadeoRequest::builder()
+            .url(url.as_str())
+            .auth_type(AuthType::UbiServices)
+            .method(Method::GET)
+            .build()?;
+
+        let res = self.execute(request).await?;
+
+        Ok(res.json::<UbiProfiles>().await?.profiles)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserializes_profiles() {
+        let json = r#"{
+            "profiles": [
+                {
+                    "profileId": "5b4d42f4-c2de-407d-b367-cbff3fe817bc",
+                    "userId": "5b4d42f4-c2de-407d-b367-cbff3fe817bc",
+                    "platformType": "uplay",
+                    "idOnPlatform": "5B4D42F4-C2DE-407D-B367-CBFF3FE817BC",
+                    "nameOnPlatform": "TgZ39"
+                },
+                {
+                    "profileId": "8a1b5a3e-0bb1-4a4c-9e4b-0f0e2b0a7c11",
+                    "userId": "5b4d42f4-c2de-407d-b367-cbff3fe817bc",
+                    "platformType": "switch",
+                    "idOnPlatform": "123",
+                    "nameOnPlatform": "TgZ39"
+                }
+            ]
+        }"#;
+
+        let profiles = serde_json::from_str::<UbiProfiles>(json).unwrap().profiles;
+
+        assert_eq!(
+            profiles[0],
+            UbiProfile {
+                profile_id: "5b4d42f4-c2de-407d-b367-cbff3fe817bc".to_string(),
+                user_id: "5b4d42f4-c2de-407d-b367-cbff3fe817bc".parse().unwrap(),
+                platform_type: PlatformType::Uplay,
+                id_on_platform: "5B4D42F4-C2DE-407D-B367-CBFF3FE817BC".to_string(),
+                name_on_platform: "TgZ39".to_string(),
+            }
+        );
+        assert_eq!(profiles[1].platform_type, PlatformType::Unknown);
+    }
+
+    #[test]
+    fn platform_types_match_the_query_values() {
+        for (platform, name) in [
+            (PlatformType::Uplay, "uplay"),
+            (PlatformType::Steam, "steam"),
+            (PlatformType::Psn, "psn"),
+            (PlatformType::Xbl, "xbl"),
+        ] {
+            assert_eq!(platform.to_string(), name);
+            assert_eq!(
+                serde_json::from_value::<PlatformType>(serde_json::json!(name)).unwrap(),
+                platform
+            );
+        }
+    }
+}
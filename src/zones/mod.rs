@@ -0,0 +1,460 @@
+//! The zone hierarchy of the core services, e.g. World → Europe → France → Île-de-France.
+
+use crate::auth::AuthType;
+use crate::types::ZoneId;
+use crate::{NadeoClient, NadeoRequest, Result};
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::Path;
+use std::time::Duration;
+
+const ZONES_URL: &str = "https://prod.trackmania.core.nadeo.online/zones/";
+
+/// A zone of the core services.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Zone {
+    pub zone_id: ZoneId,
+    /// `None` for the world.
+    #[serde(default)]
+    pub parent_id: Option<ZoneId>,
+    pub name: String,
+    /// Path of the flag, e.g. `file://Media/Flags/FRA.dds`.
+    #[serde(default)]
+    pub icon: String,
+}
+
+impl Zone {
+    /// The name of the flag, e.g. `FRA`. Countries use their ISO 3166 alpha-3 code.
+    pub fn flag(&self) -> Option<&str> {
+        let file = self.icon.rsplit('/').next()?;
+        let flag = file.strip_suffix(".dds").unwrap_or(file);
+
+        (!flag.is_empty()).then_some(flag)
+    }
+}
+
+/// Levels of the zone hierarchy.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum ZoneLevel {
+    World,
+    Continent,
+    Country,
+    /// A region of a country, or a smaller zone within a region.
+    Region,
+}
+
+/// The zones as a tree.
+///
+/// # Examples
+///
+/// ```rust
+/// # use nadeo_api::NadeoClient;
+/// # use nadeo_api::zones::ZoneTree;
+/// # use std::time::Duration;
+/// # async fn run(client: &mut NadeoClient, zone_id: nadeo_api::types::ZoneId) -> nadeo_api::Result<()> {
+/// let zones = ZoneTree::load(client, "zones.json", Duration::from_secs(7 * 24 * 60 * 60)).await?;
+///
+/// let path = zones.path(zone_id).iter().map(|zone| zone.name.as_str()).collect::<Vec<_>>();
+/// println!("{}", path.join(" > "));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ZoneTree {
+    zones: HashMap<ZoneId, Zone>,
+    children: HashMap<ZoneId, Vec<ZoneId>>,
+    root: Option<ZoneId>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ZoneCache {
+    fetched_at: DateTime<Utc>,
+    zones: Vec<Zone>,
+}
+
+impl ZoneTree {
+    /// Builds the tree from the flat list of zones.
+    pub fn new(zones: Vec<Zone>) -> Self {
+        let mut tree = Self::default();
+        for zone in zones {
+            match zone.parent_id {
+                Some(parent_id) => tree
+                    .children
+                    .entry(parent_id)
+                    .or_default()
+                    .push(zone.zone_id),
+                None => tree.root = Some(zone.zone_id),
+            }
+            tree.zones.insert(zone.zone_id, zone);
+        }
+        for children in tree.children.values_mut() {
+            children.sort_by(|a, b| tree.zones[a].name.cmp(&tree.zones[b].name));
+        }
+
+        tree
+    }
+
+    /// Loads the tree from a cache file, or requests the zones if the file is missing, invalid or older than `max_age`.
+    /// Requested zones are written to the file. Failing to write the file is ignored, the zones are requested again
+    /// on the next load then. Requires [`AuthType::NadeoServices`].
+    pub async fn load(
+        client: &mut NadeoClient,
+        path: impl AsRef<Path>,
+        max_age: Duration,
+    ) -> Result<Self> {
+        Self::load_with(path.as_ref(), max_age, client.zones()).await
+    }
+
+    /// Loads the tree from the cache file or awaits `zones` if the cache can't be used.
+    async fn load_with(
+        path: &Path,
+        max_age: Duration,
+        zones: impl Future<Output = Result<Vec<Zone>>>,
+    ) -> Result<Self> {
+        let cached = tokio::fs::read(path)
+            .await
+            .ok()
+            .and_then(|data| serde_json::from_slice::<ZoneCache>(&data).ok())
+            .filter(|cache| {
+                (Utc::now() - cache.fetched_at)
+                    .to_std()
+                    .is_ok_and(|age| age <= max_age)
+            });
+        if let Some(cache) = cached {
+            return Ok(Self::new(cache.zones));
+        }
+
+        let zones = zones.await?;
+        let cache = ZoneCache {
+            fetched_at: Utc::now(),
+            zones,
+        };
+        let data = serde_json::to_vec(&cache).expect("zones can be serialized");
+        let _ = tokio::fs::write(path, data).await;
+
+        Ok(Self::new(cache.zones))
+    }
+
+    pub fn len(&self) -> usize {
+        self.zones.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.zones.is_empty()
+    }
+
+    pub fn world(&self) -> Option<&Zone> {
+        self.get(self.root?)
+    }
+
+    pub fn get(&self, zone_id: ZoneId) -> Option<&Zone> {
+        self.zones.get(&zone_id)
+    }
+
+    /// Finds zones by their name, ignoring the case. Names are not unique, e.g. regions of different countries.
+    pub fn by_name(&self, name: &str) -> Vec<&Zone> {
+        let name = name.to_lowercase();
+
+        self.zones
+            .values()
+            .filter(|zone| zone.name.to_lowercase() == name)
+            .collect()
+    }
+
+    /// Finds a country by its ISO 3166 alpha-3 code, e.g. `FRA`.
+    pub fn by_country_code(&self, code: &str) -> Option<&Zone> {
+        self.zones.values().find(|zone| {
+            self.level(zone.zone_id) == Some(ZoneLevel::Country)
+                && zone
+                    .flag()
+                    .is_some_and(|flag| flag.eq_ignore_ascii_case(code))
+        })
+    }
+
+    /// The sub-zones of a zone ordered by name.
+    pub fn children(&self, zone_id: ZoneId) -> Vec<&Zone> {
+        self.children
+            .get(&zone_id)
+            .map(|children| children.iter().filter_map(|id| self.get(*id)).collect())
+            .unwrap_or_default()
+    }
+
+    /// The zones from the world down to the given zone, the same chain as the zone leaderboards of a player.
+    /// Empty if the zone is unknown.
+    pub fn path(&self, zone_id: ZoneId) -> Vec<&Zone> {
+        let mut path = Vec::new();
+        let mut seen = HashSet::new();
+        let mut next = self.get(zone_id);
+        // stops at a zone which was already visited, in case of cycles in invalid data
+        while let Some(zone) = next.filter(|zone| seen.insert(zone.zone_id)) {
+            path.push(zone);
+            next = zone.parent_id.and_then(|parent_id| self.get(parent_id));
+        }
+        path.reverse();
+
+        path
+    }
+
+    pub fn level(&self, zone_id: ZoneId) -> Option<ZoneLevel> {
+        match self.path(zone_id).len() {
+            0 => None,
+            1 => Some(ZoneLevel::World),
+            2 => Some(ZoneLevel::Continent),
+            3 => Some(ZoneLevel::Country),
+            _ => Some(ZoneLevel::Region),
+        }
+    }
+
+    /// The country of a zone. `None` for the world and continents.
+    pub fn country(&self, zone_id: ZoneId) -> Option<&Zone> {
+        self.path(zone_id).get(2).copied()
+    }
+
+    /// The ISO 3166 alpha-3 code of the country of a zone, e.g. `FRA`.
+    pub fn country_code(&self, zone_id: ZoneId) -> Option<&str> {
+        self.country(zone_id)?.flag()
+    }
+
+    /// Whether `zone_id` is `ancestor_id` or one of its sub-zones.
+    pub fn is_within(&self, zone_id: ZoneId, ancestor_id: ZoneId) -> bool {
+        self.path(zone_id)
+            .iter()
+            .any(|zone| zone.zone_id == ancestor_id)
+    }
+}
+
+impl From<Vec<Zone>> for ZoneTree {
+    fn from(zones: Vec<Zone>) -> Self {
+        Self::new(zones)
+    }
+}
+
+impl NadeoClient {
+    /// Gets all zones. Requires [`AuthType::NadeoServices`].
+    pub async fn zones(&mut self) -> Result<Vec<Zone>> {
+        let request = NadeoRequest::builder()
+            .url(ZONES_URL)
+            .auth_type(AuthType::NadeoServices)
+            .method(Method::GET)
+            .build()?;
+
+        let res = self.execute(request).await?;
+
+        Ok(res.json().await?)
+    }
+
+    /// Gets all zones as a [`ZoneTree`]. Requires [`AuthType::NadeoServices`].
+    pub async fn zone_tree(&mut self) -> Result<ZoneTree> {
+        self.zones().await.map(ZoneTree::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_support::temp_path;
+    use uuid::Uuid;
+
+    fn id(n: u128) -> ZoneId {
+        ZoneId::new(Uuid::from_u128(n))
+    }
+
+    fn zone(n: u128, parent: Option<u128>, name: &str, flag: &str) -> Zone {
+        Zone {
+            zone_id: id(n),
+            parent_id: parent.map(id),
+            name: name.to_string(),
+            icon: format!("file://Media/Flags/{flag}.dds"),
+        }
+    }
+
+    fn tree() -> ZoneTree {
+        ZoneTree::new(vec![
+            zone(1, None, "World", "WOR"),
+            zone(2, Some(1), "Europe", "europe"),
+            zone(3, Some(2), "France", "FRA"),
+            zone(4, Some(3), "Île-de-France", "FRA"),
+            zone(5, Some(4), "Paris", "FRA"),
+            zone(6, Some(2), "Germany", "GER"),
+        ])
+    }
+
+    fn names(zones: Vec<&Zone>) -> Vec<&str> {
+        zones.into_iter().map(|zone| zone.name.as_str()).collect()
+    }
+
+    #[test]
+    fn path_goes_from_the_world_to_the_zone() {
+        let tree = tree();
+
+        assert_eq!(
+            names(tree.path(id(5))),
+            vec!["World", "Europe", "France", "Île-de-France", "Paris"]
+        );
+        assert_eq!(names(tree.path(id(1))), vec!["World"]);
+        assert!(tree.path(id(99)).is_empty());
+    }
+
+    #[test]
+    fn level_follows_the_depth() {
+        let tree = tree();
+
+        assert_eq!(tree.level(id(1)), Some(ZoneLevel::World));
+        assert_eq!(tree.level(id(2)), Some(ZoneLevel::Continent));
+        assert_eq!(tree.level(id(3)), Some(ZoneLevel::Country));
+        assert_eq!(tree.level(id(4)), Some(ZoneLevel::Region));
+        assert_eq!(tree.level(id(5)), Some(ZoneLevel::Region));
+        assert_eq!(tree.level(id(99)), None);
+    }
+
+    #[test]
+    fn country_code_of_zones() {
+        let tree = tree();
+
+        assert_eq!(tree.country_code(id(5)), Some("FRA"));
+        assert_eq!(tree.country_code(id(6)), Some("GER"));
+        assert_eq!(tree.country_code(id(2)), None);
+        assert_eq!(tree.country_code(id(1)), None);
+    }
+
+    #[test]
+    fn finds_countries_by_code() {
+        let tree = tree();
+
+        // regions with the flag of their country are not returned
+        assert_eq!(
+            tree.by_country_code("fra").map(|zone| zone.zone_id),
+            Some(id(3))
+        );
+        assert_eq!(tree.by_country_code("WOR"), None);
+        assert_eq!(tree.by_country_code("USA"), None);
+    }
+
+    #[test]
+    fn children_and_ancestors() {
+        let tree = tree();
+
+        assert_eq!(names(tree.children(id(2))), vec!["France", "Germany"]);
+        assert!(tree.is_within(id(5), id(2)));
+        assert!(!tree.is_within(id(6), id(3)));
+    }
+
+    #[test]
+    fn path_stops_on_cycles() {
+        let tree = ZoneTree::new(vec![
+            zone(1, Some(2), "A", "A"),
+            zone(2, Some(1), "B", "B"),
+            zone(3, Some(1), "C", "C"),
+        ]);
+
+        assert_eq!(names(tree.path(id(1))), vec!["B", "A"]);
+        assert_eq!(names(tree.path(id(3))), vec!["B", "A", "C"]);
+        assert_eq!(tree.world(), None);
+    }
+
+    /// Zones which were not requested.
+    fn cached_zones() -> Vec<Zone> {
+        vec![
+            zone(1, None, "World", "WOR"),
+            zone(2, Some(1), "Cached", "C"),
+        ]
+    }
+
+    /// Zones which were requested.
+    fn requested_zones() -> Vec<Zone> {
+        vec![
+            zone(1, None, "World", "WOR"),
+            zone(3, Some(1), "Requested", "R"),
+        ]
+    }
+
+    async fn write_cache(path: &Path, age: chrono::Duration) {
+        let cache = ZoneCache {
+            fetched_at: Utc::now() - age,
+            zones: cached_zones(),
+        };
+        tokio::fs::write(path, serde_json::to_vec(&cache).unwrap())
+            .await
+            .unwrap();
+    }
+
+    async fn load(path: &Path) -> Result<ZoneTree> {
+        ZoneTree::load_with(path, Duration::from_secs(3600), async {
+            Ok(requested_zones())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn load_uses_a_fresh_cache() {
+        let path = temp_path("zones-fresh.json");
+        write_cache(&path, chrono::Duration::minutes(59)).await;
+
+        let tree = ZoneTree::load_with(&path, Duration::from_secs(3600), async {
+            unreachable!("the cache is used")
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(names(tree.children(id(1))), vec!["Cached"]);
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn load_replaces_an_expired_cache() {
+        let path = temp_path("zones-expired.json");
+        write_cache(&path, chrono::Duration::minutes(61)).await;
+
+        let tree = load(&path).await.unwrap();
+
+        assert_eq!(names(tree.children(id(1))), vec!["Requested"]);
+        let cache: ZoneCache =
+            serde_json::from_slice(&tokio::fs::read(&path).await.unwrap()).unwrap();
+        assert_eq!(cache.zones, requested_zones());
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn load_replaces_an_invalid_cache() {
+        let path = temp_path("zones-invalid.json");
+        tokio::fs::write(&path, b"not json").await.unwrap();
+
+        let tree = load(&path).await.unwrap();
+
+        assert_eq!(names(tree.children(id(1))), vec!["Requested"]);
+        assert!(
+            serde_json::from_slice::<ZoneCache>(&tokio::fs::read(&path).await.unwrap()).is_ok()
+        );
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn load_ignores_an_unwritable_cache() {
+        let path = temp_path("missing-dir").join("zones.json");
+
+        let tree = load(&path).await.unwrap();
+
+        assert_eq!(names(tree.children(id(1))), vec!["Requested"]);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn load_returns_request_errors() {
+        let path = temp_path("zones-error.json");
+
+        let res = ZoneTree::load_with(&path, Duration::from_secs(3600), async {
+            Err(crate::Error::from(
+                crate::client::ClientError::MissingNadeoAuth,
+            ))
+        })
+        .await;
+
+        assert!(res.is_err());
+        assert!(!path.exists());
+    }
+}